@@ -1,6 +1,8 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::str::FromStr;
 
-use clap::{ArgAction, Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
 
 /// typst creates PDF files from .typ files
 #[derive(Debug, Clone, Parser)]
@@ -14,6 +16,17 @@ pub struct CliArguments {
     #[clap(long = "root", env = "TYPST_ROOT", value_name = "DIR")]
     pub root: Option<PathBuf>,
 
+    /// Add a string key/value pair that is visible to documents through
+    /// `sys.inputs`
+    #[clap(
+        long = "input",
+        value_name = "KEY=VALUE",
+        value_parser = parse_input_pair,
+        action = ArgAction::Append,
+        global = true
+    )]
+    pub inputs: Vec<(String, String)>,
+
     /// The typst command to run
     #[command(subcommand)]
     pub command: Command,
@@ -31,6 +44,15 @@ pub enum Command {
     #[command(visible_alias = "w")]
     Watch(CompileCommand),
 
+    /// Processes an input file to extract provided metadata
+    Query(QueryCommand),
+
+    /// Serves incremental compilations over a JSON-RPC protocol on stdio
+    Serve(ServeCommand),
+
+    /// Runs a language server on stdio for editor integration
+    Lsp(LspCommand),
+
     /// List all discovered fonts in system and custom font paths
     Fonts(FontsCommand),
 }
@@ -41,12 +63,172 @@ pub struct CompileCommand {
     /// Path to input Typst file
     pub input: PathBuf,
 
-    /// Path to output PDF file
+    /// Path to output file (PDF, PNG, SVG, HTML, TXT, MD or EPUB). Use `{n}` in
+    /// the file name to produce one image per page
     pub output: Option<PathBuf>,
 
     /// Opens the output file after compilation using the default PDF viewer
     #[arg(long = "open")]
     pub open: Option<Option<String>>,
+
+    /// The resolution for PNG export, in pixels per inch
+    #[arg(long = "ppi", default_value_t = 144.0, value_parser = parse_ppi)]
+    pub ppi: f32,
+
+    /// Which pages to export, e.g. `1-3,7` or `5-`
+    #[arg(long = "pages", value_name = "PAGES", value_delimiter = ',')]
+    pub pages: Vec<PageRange>,
+
+    /// A standard that the exported PDF file must conform to
+    #[arg(long = "pdf-standard", value_enum)]
+    pub pdf_standard: Option<PdfStandard>,
+
+    /// Tags the exported PDF file with the logical structure of the document
+    /// for screen readers
+    #[arg(long = "pdf-tagged")]
+    pub pdf_tagged: bool,
+
+    /// The creation and modification date of the exported PDF file, in
+    /// seconds since the Unix epoch. Without it, the file has no dates
+    #[arg(
+        long = "creation-timestamp",
+        env = "SOURCE_DATE_EPOCH",
+        value_name = "UNIX_TIMESTAMP"
+    )]
+    pub creation_timestamp: Option<i64>,
+
+    /// The format in which diagnostics are printed
+    #[arg(long = "diagnostic-format", value_enum, default_value_t = DiagnosticFormat::Human)]
+    pub diagnostic_format: DiagnosticFormat,
+}
+
+/// Processes an input file to extract provided metadata
+#[derive(Debug, Clone, Parser)]
+pub struct QueryCommand {
+    /// Path to input Typst file
+    pub input: PathBuf,
+
+    /// Defines which elements to retrieve, e.g. `heading` or `<label>`
+    pub selector: String,
+
+    /// Extracts just one field from all retrieved elements
+    #[arg(long = "field")]
+    pub field: Option<String>,
+
+    /// Expects and retrieves exactly one element
+    #[arg(long = "one")]
+    pub one: bool,
+
+    /// The format to serialize the retrieved elements in
+    #[arg(long = "format", value_enum, default_value_t = SerializationFormat::Json)]
+    pub format: SerializationFormat,
+
+    /// The format in which diagnostics are printed
+    #[arg(long = "diagnostic-format", value_enum, default_value_t = DiagnosticFormat::Human)]
+    pub diagnostic_format: DiagnosticFormat,
+}
+
+/// Serves incremental compilations over a JSON-RPC protocol on stdio
+#[derive(Debug, Clone, Parser)]
+pub struct ServeCommand {
+    /// Path to input Typst file
+    pub input: PathBuf,
+}
+
+/// Runs a language server on stdio for editor integration
+#[derive(Debug, Clone, Parser)]
+pub struct LspCommand {
+    /// Path to the main Typst file. Defaults to the first opened file
+    pub main: Option<PathBuf>,
+}
+
+/// How query results are serialized.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum SerializationFormat {
+    /// Pretty-printed JSON.
+    Json,
+    /// YAML.
+    Yaml,
+}
+
+/// How diagnostics are printed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum DiagnosticFormat {
+    /// Colored, human-readable output with source excerpts.
+    Human,
+    /// One JSON object per line and diagnostic.
+    Json,
+}
+
+/// A PDF standard that exported files can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfStandard {
+    /// PDF/A-2b, for long-term archival.
+    #[value(name = "a-2b")]
+    A2b,
+}
+
+/// A range of pages, with both ends inclusive and counting from one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PageRange {
+    /// The first page in the range. `None` means the first page overall.
+    pub start: Option<NonZeroUsize>,
+    /// The last page in the range. `None` means the last page overall.
+    pub end: Option<NonZeroUsize>,
+}
+
+impl FromStr for PageRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let page = |s: &str| -> Result<Option<NonZeroUsize>, String> {
+            let s = s.trim();
+            if s.is_empty() {
+                return Ok(None);
+            }
+            s.parse().map(Some).map_err(|_| format!("invalid page number: {s}"))
+        };
+
+        let (start, end) = match s.split_once('-') {
+            Some((start, end)) => (page(start)?, page(end)?),
+            None => {
+                let page = page(s)?;
+                if page.is_none() {
+                    return Err("page range must not be empty".into());
+                }
+                (page, page)
+            }
+        };
+
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err(format!("page range {start}-{end} is reversed"));
+            }
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+/// Parse a `key=value` pair for `--input`.
+fn parse_input_pair(raw: &str) -> Result<(String, String), String> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or("input must be a key and a value separated by an equal sign")?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("input key must not be empty".into());
+    }
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+/// Parse a resolution for `--ppi`.
+fn parse_ppi(raw: &str) -> Result<f32, String> {
+    let ppi: f32 = raw.parse().map_err(|_| format!("invalid resolution: {raw}"))?;
+    if !ppi.is_finite() || ppi <= 0.0 {
+        return Err("resolution must be positive".into());
+    }
+    Ok(ppi)
 }
 
 /// List all discovered fonts in system and custom font paths
@@ -56,3 +238,44 @@ pub struct FontsCommand {
     #[arg(long)]
     pub variants: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_range_from_str() {
+        let range = |start, end| PageRange {
+            start: NonZeroUsize::new(start),
+            end: NonZeroUsize::new(end),
+        };
+
+        assert_eq!("3".parse(), Ok(range(3, 3)));
+        assert_eq!("2-5".parse(), Ok(range(2, 5)));
+        assert_eq!(" 2 - 5 ".parse(), Ok(range(2, 5)));
+        assert_eq!("-4".parse(), Ok(range(0, 4)));
+        assert_eq!("7-".parse(), Ok(range(7, 0)));
+        assert_eq!("-".parse(), Ok(range(0, 0)));
+        assert_eq!("4-4".parse(), Ok(range(4, 4)));
+    }
+
+    #[test]
+    fn test_page_range_from_str_invalid() {
+        let err = |s: &str| s.parse::<PageRange>().unwrap_err();
+        assert_eq!(err(""), "page range must not be empty");
+        assert_eq!(err("0"), "invalid page number: 0");
+        assert_eq!(err("a-3"), "invalid page number: a");
+        assert_eq!(err("1-2-3"), "invalid page number: 2-3");
+        assert_eq!(err("5-2"), "page range 5-2 is reversed");
+    }
+
+    #[test]
+    fn test_parse_ppi() {
+        assert_eq!(parse_ppi("300"), Ok(300.0));
+        assert_eq!(parse_ppi("72.5"), Ok(72.5));
+        assert_eq!(parse_ppi("abc"), Err("invalid resolution: abc".into()));
+        for raw in ["0", "-144", "NaN", "inf"] {
+            assert_eq!(parse_ppi(raw), Err("resolution must be positive".into()));
+        }
+    }
+}
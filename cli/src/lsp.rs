@@ -0,0 +1,864 @@
+//! A language server that exposes Typst's IDE capabilities to editors.
+//!
+//! The server speaks the language server protocol on stdio and supports
+//! completions, hover tooltips, semantic highlighting and diagnostics. Each
+//! time a document is opened or changed, the main file is compiled and the
+//! resulting errors and warnings are published for all involved files. The
+//! main file is given on the command line or, failing that, is the first
+//! opened document.
+//!
+//! Positions are exchanged in UTF-16 code units, as the protocol requires by
+//! default. Only `file://` URIs are supported.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use typst::diag::{Severity, SourceError, StrResult};
+use typst::doc::{Document, Frame};
+use typst::eval::Tracer;
+use typst::ide::{self, CompletionKind, Tag, Tooltip};
+use typst::syntax::{is_newline, LinkedNode, Source};
+use typst::World;
+
+use crate::args::{CliArguments, Command};
+use crate::rpc::{self, Error, Request};
+use crate::{typst_version, SystemWorld};
+
+/// The semantic token types, in the order of the legend sent to the client.
+const TOKEN_TYPES: &[&str] = &[
+    "comment",
+    "punctuation",
+    "escape",
+    "strong",
+    "emph",
+    "link",
+    "raw",
+    "label",
+    "ref",
+    "heading",
+    "marker",
+    "term",
+    "delimiter",
+    "operator",
+    "keyword",
+    "number",
+    "string",
+    "function",
+    "variable",
+];
+
+/// A summary of the input arguments relevant to the language server.
+pub struct LspSettings {
+    /// The root directory for absolute paths.
+    root: Option<PathBuf>,
+
+    /// The paths to search for fonts.
+    font_paths: Vec<PathBuf>,
+
+    /// The key/value pairs exposed through `sys.inputs`.
+    inputs: Vec<(String, String)>,
+
+    /// The main file to compile, if given.
+    main: Option<PathBuf>,
+}
+
+impl LspSettings {
+    /// Create new language server settings from the CLI arguments.
+    ///
+    /// # Panics
+    /// Panics if the command is not an lsp command.
+    pub fn with_arguments(args: CliArguments) -> Self {
+        let Command::Lsp(command) = args.command else { unreachable!() };
+        Self {
+            root: args.root,
+            font_paths: args.font_paths,
+            inputs: args.inputs,
+            main: command.main,
+        }
+    }
+}
+
+/// Execute an lsp command.
+pub fn lsp(command: LspSettings) -> StrResult<()> {
+    let root = command
+        .root
+        .clone()
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+
+    let mut server = LanguageServer {
+        world: SystemWorld::new(root, &command.font_paths, &command.inputs),
+        fixed_root: command.root.is_some(),
+        fixed_main: command.main.is_some(),
+        main: command.main,
+        open: vec![],
+        document: None,
+        published: vec![],
+        outbox: vec![],
+    };
+
+    let mut reader = io::stdin().lock();
+    let mut writer = io::stdout().lock();
+    while let Some(body) = rpc::read(&mut reader).map_err(|_| "failed to read message")? {
+        let request = body
+            .and_then(|body| {
+                serde_json::from_slice::<Value>(&body)
+                    .map_err(|err| Error::new(Error::PARSE_ERROR, err.to_string()))
+            })
+            .and_then(|message| {
+                serde_json::from_value::<Request>(message)
+                    .map_err(|err| Error::new(Error::INVALID_REQUEST, err.to_string()))
+            });
+
+        let response = match request {
+            Ok(request) if request.method == "exit" => break,
+            Ok(Request { id: Some(id), method, params }) => {
+                Some(rpc::response(id, server.handle(&method, params)))
+            }
+            Ok(Request { id: None, method, params }) => {
+                if let Err(error) = server.notify(&method, params) {
+                    server.log(&error.message);
+                }
+                None
+            }
+            Err(error) => Some(rpc::response(Value::Null, Err(error))),
+        };
+
+        for message in response.iter().chain(&server.outbox) {
+            rpc::write(&mut writer, message).map_err(|_| "failed to write message")?;
+        }
+
+        server.outbox.clear();
+    }
+
+    Ok(())
+}
+
+/// The state of the language server.
+struct LanguageServer {
+    /// The world that serves sources, fonts and files.
+    world: SystemWorld,
+    /// Whether the root was given on the command line and must not be taken
+    /// from the client.
+    fixed_root: bool,
+    /// Whether the main file was given on the command line and must not be
+    /// replaced by an opened document.
+    fixed_main: bool,
+    /// The file that is compiled to produce diagnostics.
+    main: Option<PathBuf>,
+    /// The files that are open in the editor. Their text is owned by the
+    /// client.
+    open: Vec<PathBuf>,
+    /// The last successfully compiled document.
+    document: Option<Document>,
+    /// The URIs that diagnostics were last published for.
+    published: Vec<String>,
+    /// Notifications to send to the client after the current message.
+    outbox: Vec<Value>,
+}
+
+impl LanguageServer {
+    /// Dispatch a request to the method with the given name.
+    fn handle(&mut self, method: &str, params: Value) -> Result<Value, Error> {
+        match method {
+            "initialize" => rpc::result(self.initialize(rpc::params(params)?)),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/completion" => {
+                rpc::result(self.completion(rpc::params(params)?)?)
+            }
+            "textDocument/hover" => rpc::result(self.hover(rpc::params(params)?)?),
+            "textDocument/semanticTokens/full" => {
+                rpc::result(self.semantic_tokens(rpc::params(params)?)?)
+            }
+            _ => Err(Error::new(
+                Error::METHOD_NOT_FOUND,
+                format!("unknown method: {method}"),
+            )),
+        }
+    }
+
+    /// Dispatch a notification to the method with the given name.
+    ///
+    /// Unknown notifications are ignored, as the protocol demands.
+    fn notify(&mut self, method: &str, params: Value) -> Result<(), Error> {
+        match method {
+            "textDocument/didOpen" => self.did_open(rpc::params(params)?),
+            "textDocument/didChange" => self.did_change(rpc::params(params)?),
+            "textDocument/didSave" => self.did_save(rpc::params(params)?),
+            "textDocument/didClose" => self.did_close(rpc::params(params)?),
+            _ => Ok(()),
+        }
+    }
+
+    /// Answer the client's initialization request with our capabilities.
+    fn initialize(&mut self, params: InitializeParams) -> Value {
+        if !self.fixed_root {
+            if let Some(root) = params.root_uri.and_then(|uri| uri_to_path(&uri).ok()) {
+                self.world.root = root;
+            }
+        }
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": { "openClose": true, "change": 2, "save": true },
+                "completionProvider": { "triggerCharacters": ["#", ".", "@"] },
+                "hoverProvider": true,
+                "semanticTokensProvider": {
+                    "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                    "full": true,
+                },
+            },
+            "serverInfo": { "name": "typst", "version": typst_version() },
+        })
+    }
+
+    /// Take over the text of a newly opened document.
+    fn did_open(&mut self, params: DidOpenParams) -> Result<(), Error> {
+        let path = uri_to_path(&params.text_document.uri)?;
+        self.world
+            .update(&path, params.text_document.text)
+            .map_err(Error::failed)?;
+        if !self.open.contains(&path) {
+            self.open.push(path.clone());
+        }
+        if self.main.is_none() {
+            self.main = Some(path);
+        }
+        self.check()
+    }
+
+    /// Apply changes to an open document.
+    fn did_change(&mut self, params: DidChangeParams) -> Result<(), Error> {
+        let path = uri_to_path(&params.text_document.uri)?;
+        for change in params.content_changes {
+            match change.range {
+                Some(range) => {
+                    let source = self.source(&path)?;
+                    let range = offset(source, range.start)?..offset(source, range.end)?;
+                    self.world.edit(&path, range, &change.text).map_err(Error::failed)?;
+                }
+                None => self.world.update(&path, change.text).map_err(Error::failed)?,
+            }
+        }
+        self.check()
+    }
+
+    /// Pick up changes to files on disk that are not open.
+    fn did_save(&mut self, _: DocumentParams) -> Result<(), Error> {
+        self.reload()
+    }
+
+    /// Hand the text of a closed document back to the file system.
+    fn did_close(&mut self, params: DocumentParams) -> Result<(), Error> {
+        let path = uri_to_path(&params.text_document.uri)?;
+        self.open.retain(|open| *open != path);
+        if !self.fixed_main && self.main.as_ref() == Some(&path) {
+            self.main = self.open.first().cloned();
+        }
+        self.reload()
+    }
+
+    /// Complete the code at a position.
+    fn completion(
+        &self,
+        params: CompletionParams,
+    ) -> Result<Option<Vec<CompletionItem>>, Error> {
+        let path = uri_to_path(&params.text_document.uri)?;
+        let source = self.source(&path)?;
+        let cursor = offset(source, params.position)?;
+        let explicit = params.context.map_or(false, |context| context.trigger_kind == 1);
+
+        let Some((from, completions)) =
+            ide::autocomplete(&self.world, self.frames(), source, cursor, explicit)
+        else {
+            return Ok(None);
+        };
+
+        let range = LspRange::new(source, from..cursor);
+        Ok(Some(
+            completions
+                .into_iter()
+                .map(|completion| CompletionItem {
+                    kind: match completion.kind {
+                        CompletionKind::Syntax => 15,
+                        CompletionKind::Func => 3,
+                        CompletionKind::Param => 6,
+                        CompletionKind::Constant | CompletionKind::Symbol(_) => 21,
+                    },
+                    text_edit: TextEdit {
+                        range,
+                        new_text: snippet(
+                            completion.apply.as_deref().unwrap_or(&completion.label),
+                        ),
+                    },
+                    insert_text_format: 2,
+                    label: completion.label.to_string(),
+                    detail: completion.detail.map(|detail| detail.to_string()),
+                })
+                .collect(),
+        ))
+    }
+
+    /// Describe the item under a position.
+    fn hover(&self, params: PositionParams) -> Result<Option<Value>, Error> {
+        let path = uri_to_path(&params.text_document.uri)?;
+        let source = self.source(&path)?;
+        let cursor = offset(source, params.position)?;
+        let value = match ide::tooltip(&self.world, self.frames(), source, cursor) {
+            Some(Tooltip::Text(text)) => text.to_string(),
+            Some(Tooltip::Code(code)) => format!("```typst\n{code}\n```"),
+            None => return Ok(None),
+        };
+
+        Ok(Some(json!({ "contents": { "kind": "markdown", "value": value } })))
+    }
+
+    /// Highlight a whole document.
+    fn semantic_tokens(&self, params: DocumentParams) -> Result<Value, Error> {
+        let path = uri_to_path(&params.text_document.uri)?;
+        let source = self.source(&path)?;
+        let mut tokens = SemanticTokens { source, data: vec![], line: 0, character: 0 };
+        tokens.visit(&LinkedNode::new(source.root()), None);
+        Ok(json!({ "data": tokens.data }))
+    }
+
+    /// Reread all files that are not open and compile again.
+    fn reload(&mut self) -> Result<(), Error> {
+        self.world.reload(&self.open).map_err(Error::failed)?;
+        // Reloading reassigns source ids, so the spans in the last document
+        // would point into the wrong files.
+        self.document = None;
+        self.check()
+    }
+
+    /// Compile the main file and publish its diagnostics.
+    fn check(&mut self) -> Result<(), Error> {
+        let Some(main) = &self.main else { return Ok(()) };
+        self.world.main = self.world.resolve(main).map_err(Error::failed)?;
+
+        let mut tracer = Tracer::default();
+        let mut diagnostics = match typst::compile(&self.world, &mut tracer) {
+            Ok(document) => {
+                self.document = Some(document);
+                vec![]
+            }
+            Err(errors) => *errors,
+        };
+
+        diagnostics.extend(tracer.warnings().iter().cloned());
+        comemo::evict(30);
+
+        self.publish(&diagnostics);
+        Ok(())
+    }
+
+    /// Publish diagnostics, grouped by file.
+    ///
+    /// Files that had diagnostics before, but have none now, are cleared.
+    fn publish(&mut self, diagnostics: &[SourceError]) {
+        let mut grouped = BTreeMap::<String, Vec<Diagnostic>>::new();
+        for error in diagnostics {
+            let (id, range) = if error.span.is_detached() {
+                (self.world.main, 0..0)
+            } else {
+                (error.span.source(), error.range(&self.world))
+            };
+
+            let source = self.world.source(id);
+            let uri = path_to_uri(source.path());
+            grouped.entry(uri).or_default().push(Diagnostic {
+                range: LspRange::new(source, range),
+                severity: match error.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                },
+                source: "typst",
+                message: error.message.to_string(),
+            });
+        }
+
+        for uri in std::mem::take(&mut self.published) {
+            grouped.entry(uri).or_default();
+        }
+
+        for (uri, diagnostics) in grouped {
+            if !diagnostics.is_empty() {
+                self.published.push(uri.clone());
+            }
+
+            self.outbox.push(rpc::notification(
+                "textDocument/publishDiagnostics",
+                json!({ "uri": uri, "diagnostics": diagnostics }),
+            ));
+        }
+    }
+
+    /// Show an error in the client's log.
+    fn log(&mut self, message: &str) {
+        self.outbox.push(rpc::notification(
+            "window/logMessage",
+            json!({ "type": 1, "message": message }),
+        ));
+    }
+
+    /// The source file at a path.
+    fn source(&self, path: &Path) -> Result<&Source, Error> {
+        let id = self.world.resolve(path).map_err(Error::failed)?;
+        Ok(self.world.source(id))
+    }
+
+    /// The pages of the last successfully compiled document.
+    fn frames(&self) -> &[Frame] {
+        self.document.as_ref().map_or(&[][..], |document| &document.pages)
+    }
+}
+
+/// Collects delta-encoded semantic tokens.
+struct SemanticTokens<'a> {
+    /// The source file the tokens belong to.
+    source: &'a Source,
+    /// The tokens, five numbers each.
+    data: Vec<u32>,
+    /// The line of the previous token.
+    line: usize,
+    /// The start character of the previous token.
+    character: usize,
+}
+
+impl SemanticTokens<'_> {
+    /// Emit tokens for all leaves below a node.
+    ///
+    /// Leaves without a highlighting tag of their own inherit the tag of
+    /// their closest highlighted ancestor.
+    fn visit(&mut self, node: &LinkedNode, inherited: Option<Tag>) {
+        let tag = ide::highlight(node).or(inherited);
+        let mut leaf = true;
+        for child in node.children() {
+            leaf = false;
+            self.visit(&child, tag);
+        }
+
+        if leaf {
+            if let Some(ty) = tag.and_then(token_type) {
+                self.push(node, ty);
+            }
+        }
+    }
+
+    /// Emit a token for a leaf, split into one token per line.
+    fn push(&mut self, node: &LinkedNode, ty: u32) {
+        let mut offset = node.offset();
+        for line in node.text().split_inclusive('\n') {
+            let text = line.trim_end_matches(['\r', '\n']);
+            if !text.is_empty() {
+                let start = Position::new(self.source, offset);
+                let delta_line = start.line - self.line;
+                let delta_character = if delta_line == 0 {
+                    start.character - self.character
+                } else {
+                    start.character
+                };
+
+                self.data.extend([
+                    delta_line as u32,
+                    delta_character as u32,
+                    text.encode_utf16().count() as u32,
+                    ty,
+                    0,
+                ]);
+
+                self.line = start.line;
+                self.character = start.character;
+            }
+            offset += line.len();
+        }
+    }
+}
+
+/// The index of the semantic token type for a highlighting tag.
+fn token_type(tag: Tag) -> Option<u32> {
+    let name = match tag {
+        Tag::Comment => "comment",
+        Tag::Punctuation => "punctuation",
+        Tag::Escape => "escape",
+        Tag::Strong => "strong",
+        Tag::Emph => "emph",
+        Tag::Link => "link",
+        Tag::Raw => "raw",
+        Tag::Label => "label",
+        Tag::Ref => "ref",
+        Tag::Heading => "heading",
+        Tag::ListMarker => "marker",
+        Tag::ListTerm => "term",
+        Tag::MathDelimiter => "delimiter",
+        Tag::MathOperator | Tag::Operator => "operator",
+        Tag::Keyword => "keyword",
+        Tag::Number => "number",
+        Tag::String => "string",
+        Tag::Function => "function",
+        Tag::Interpolated => "variable",
+        Tag::Error => return None,
+    };
+
+    TOKEN_TYPES.iter().position(|&ty| ty == name).map(|i| i as u32)
+}
+
+/// Convert Typst's snippet syntax into the protocol's.
+///
+/// Typst writes placeholders as `${name}` or `${}`, while the protocol numbers
+/// them and requires literal dollar signs and braces to be escaped.
+fn snippet(apply: &str) -> String {
+    let mut output = String::new();
+    let mut index = 0;
+    let mut rest = apply;
+    while let Some(c) = rest.chars().next() {
+        if let Some(placeholder) = rest.strip_prefix("${") {
+            if let Some((name, after)) = placeholder.split_once('}') {
+                index += 1;
+                if name.is_empty() {
+                    write!(output, "${index}").unwrap();
+                } else {
+                    write!(output, "${{{index}:{name}}}").unwrap();
+                }
+                rest = after;
+                continue;
+            }
+        }
+
+        if matches!(c, '$' | '}' | '\\') {
+            output.push('\\');
+        }
+        output.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    output
+}
+
+/// Convert a `file://` URI into a path.
+fn uri_to_path(uri: &str) -> Result<PathBuf, Error> {
+    let invalid = || Error::failed(format!("unsupported uri: {uri}"));
+    let encoded = uri.strip_prefix("file://").ok_or_else(invalid)?;
+
+    let mut bytes = vec![];
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let high = iter.next().ok_or_else(invalid)?;
+            let low = iter.next().ok_or_else(invalid)?;
+            let digits = [high, low];
+            let hex = std::str::from_utf8(&digits).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    let path = String::from_utf8(bytes).map_err(|_| invalid())?;
+    if cfg!(windows) {
+        // Windows paths look like `file:///C:/...`.
+        return Ok(path.trim_start_matches('/').into());
+    }
+
+    Ok(path.into())
+}
+
+/// Convert a path into a `file://` URI.
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => uri.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            b'\\' => uri.push('/'),
+            _ => write!(uri, "%{byte:02X}").unwrap(),
+        }
+    }
+
+    uri
+}
+
+/// Convert a protocol position into a byte offset.
+///
+/// A character past the end of the line refers to the end of the line, as
+/// the protocol demands.
+fn offset(source: &Source, position: Position) -> Result<usize, Error> {
+    let start = source.line_to_byte(position.line).ok_or_else(|| {
+        Error::failed(format!(
+            "position {}:{} is out of bounds",
+            position.line, position.character,
+        ))
+    })?;
+
+    let end = source.line_to_byte(position.line + 1).unwrap_or(source.len_bytes());
+    let line = source
+        .get(start..end)
+        .unwrap_or_default()
+        .trim_end_matches(is_newline);
+
+    let mut utf16 = 0;
+    for (i, c) in line.char_indices() {
+        if utf16 >= position.character {
+            return Ok(start + i);
+        }
+        utf16 += c.len_utf16();
+    }
+
+    Ok(start + line.len())
+}
+
+/// A position in a text document, with the character counted in UTF-16 code
+/// units.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+struct Position {
+    /// The zero-based line.
+    line: usize,
+    /// The zero-based character offset within the line.
+    character: usize,
+}
+
+impl Position {
+    /// The position of a byte offset.
+    fn new(source: &Source, offset: usize) -> Self {
+        let line = source.byte_to_line(offset).unwrap_or_default();
+        let start = source.line_to_byte(line).unwrap_or_default();
+        let character = source
+            .get(start..offset)
+            .map_or(0, |head| head.encode_utf16().count());
+        Self { line, character }
+    }
+}
+
+/// A range in a text document.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+struct LspRange {
+    /// The start position.
+    start: Position,
+    /// The end position, exclusive.
+    end: Position,
+}
+
+impl LspRange {
+    /// The range of a byte range.
+    fn new(source: &Source, range: std::ops::Range<usize>) -> Self {
+        Self {
+            start: Position::new(source, range.start),
+            end: Position::new(source, range.end),
+        }
+    }
+}
+
+/// The parameters of an `initialize` request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InitializeParams {
+    /// The root folder of the workspace.
+    #[serde(default)]
+    root_uri: Option<String>,
+}
+
+/// Identifies a text document.
+#[derive(Deserialize)]
+struct DocumentIdentifier {
+    /// The document's URI.
+    uri: String,
+}
+
+/// A text document with its content.
+#[derive(Deserialize)]
+struct DocumentItem {
+    /// The document's URI.
+    uri: String,
+    /// The document's full text.
+    text: String,
+}
+
+/// The parameters of notifications and requests that concern a whole
+/// document.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentParams {
+    /// The document.
+    text_document: DocumentIdentifier,
+}
+
+/// The parameters of a `textDocument/didOpen` notification.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidOpenParams {
+    /// The opened document.
+    text_document: DocumentItem,
+}
+
+/// The parameters of a `textDocument/didChange` notification.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidChangeParams {
+    /// The changed document.
+    text_document: DocumentIdentifier,
+    /// The changes, to be applied in order.
+    content_changes: Vec<ContentChange>,
+}
+
+/// A change to a document.
+#[derive(Deserialize)]
+struct ContentChange {
+    /// The replaced range. Replaces the whole document if absent.
+    #[serde(default)]
+    range: Option<LspRange>,
+    /// The replacement text.
+    text: String,
+}
+
+/// The parameters of requests that concern a position in a document.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionParams {
+    /// The document.
+    text_document: DocumentIdentifier,
+    /// The position within the document.
+    position: Position,
+}
+
+/// The parameters of a `textDocument/completion` request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionParams {
+    /// The document.
+    text_document: DocumentIdentifier,
+    /// The position to complete at.
+    position: Position,
+    /// How the completion was triggered.
+    #[serde(default)]
+    context: Option<CompletionContext>,
+}
+
+/// How a completion was triggered.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionContext {
+    /// One if the user requested completions explicitly.
+    trigger_kind: u8,
+}
+
+/// A completion sent to the client.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionItem {
+    /// The label the completion is shown with.
+    label: String,
+    /// The protocol's kind of completion item.
+    kind: u8,
+    /// A short description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    /// The edit that applies the completion.
+    text_edit: TextEdit,
+    /// Always two, meaning that the edit's text is a snippet.
+    insert_text_format: u8,
+}
+
+/// An edit of a text document.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TextEdit {
+    /// The range to replace.
+    range: LspRange,
+    /// The replacement text.
+    new_text: String,
+}
+
+/// A diagnostic sent to the client.
+#[derive(Serialize)]
+struct Diagnostic {
+    /// The range the diagnostic applies to.
+    range: LspRange,
+    /// One for errors and two for warnings.
+    severity: u8,
+    /// The tool that produced the diagnostic.
+    source: &'static str,
+    /// The diagnostic message.
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(line: usize, character: usize) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn test_lsp_snippet() {
+        assert_eq!(snippet("none"), "none");
+        assert_eq!(snippet("image(${})"), "image($1)");
+        assert_eq!(snippet("let ${name} = ${value}"), "let ${1:name} = ${2:value}");
+        assert_eq!(snippet("$x$ {}"), "\\$x\\$ {\\}");
+        assert_eq!(snippet("a\\b ${"), "a\\\\b \\${");
+    }
+
+    #[test]
+    fn test_lsp_uri_to_path() {
+        if cfg!(windows) {
+            return;
+        }
+
+        assert_eq!(uri_to_path("file:///a/b.typ").unwrap(), Path::new("/a/b.typ"));
+        assert_eq!(
+            uri_to_path("file:///my%20dir/%C3%A4.typ").unwrap(),
+            Path::new("/my dir/ä.typ"),
+        );
+        assert!(uri_to_path("https://typst.app/a.typ").is_err());
+        assert!(uri_to_path("file:///a%2").is_err());
+        assert!(uri_to_path("file:///a%zz").is_err());
+        assert!(uri_to_path("file:///%FF").is_err());
+    }
+
+    #[test]
+    fn test_lsp_path_to_uri() {
+        if cfg!(windows) {
+            return;
+        }
+
+        assert_eq!(path_to_uri(Path::new("/a/b.typ")), "file:///a/b.typ");
+        assert_eq!(
+            path_to_uri(Path::new("/my dir/ä.typ")),
+            "file:///my%20dir/%C3%A4.typ"
+        );
+        for path in ["/a/b-c_d~e.typ", "/x y/#?%.typ", "/ü/€/😀.typ"] {
+            assert_eq!(
+                uri_to_path(&path_to_uri(Path::new(path))).unwrap(),
+                Path::new(path)
+            );
+        }
+    }
+
+    #[test]
+    fn test_lsp_offset() {
+        let source = Source::detached("ab\n🏳️‍🌈c\r\nd");
+        assert_eq!(offset(&source, position(0, 0)).unwrap(), 0);
+        assert_eq!(offset(&source, position(0, 2)).unwrap(), 2);
+        assert_eq!(offset(&source, position(1, 0)).unwrap(), 3);
+        assert_eq!(offset(&source, position(1, 2)).unwrap(), 7);
+        assert_eq!(offset(&source, position(1, 6)).unwrap(), 17);
+        assert_eq!(offset(&source, position(1, 7)).unwrap(), 18);
+        assert_eq!(offset(&source, position(2, 0)).unwrap(), 20);
+        assert_eq!(offset(&source, position(2, 1)).unwrap(), 21);
+
+        // Characters past the end of the line are clamped to the line end.
+        assert_eq!(offset(&source, position(0, 10)).unwrap(), 2);
+        assert_eq!(offset(&source, position(1, 100)).unwrap(), 18);
+        assert_eq!(offset(&source, position(2, 5)).unwrap(), 21);
+
+        assert!(offset(&source, position(3, 0)).is_err());
+    }
+}
@@ -1,33 +1,50 @@
 mod args;
+mod lsp;
+mod rpc;
+mod serve;
 
 use std::cell::{RefCell, RefMut};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::hash::Hash;
 use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use clap::Parser;
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term::{self, termcolor};
-use comemo::Prehashed;
+use comemo::{Prehashed, Track};
+use ecow::{eco_format, EcoString};
 use elsa::FrozenVec;
 use memmap2::Mmap;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use once_cell::unsync::OnceCell;
 use same_file::{is_same_file, Handle};
+use serde::Serialize;
 use siphasher::sip128::{Hasher128, SipHasher};
 use termcolor::{ColorChoice, StandardStream, WriteColor};
-use typst::diag::{FileError, FileResult, SourceError, StrResult};
-use typst::eval::Library;
+use typst::diag::{FileError, FileResult, Severity, SourceError, StrResult};
+use typst::doc::{Destination, Document, Frame};
+use typst::eval::{Dict, Library, Tracer, Value};
+use typst::export::{EpubOptions, HtmlDocument, PdfOptions, PdfTimestamp};
 use typst::font::{Font, FontBook, FontInfo, FontVariant};
-use typst::syntax::{Source, SourceId};
+use typst::geom::Color;
+use typst::model::{Content, Introspector, LocatableSelector};
+use typst::syntax::{Source, SourceId, Span};
 use typst::util::{Buffer, PathExt};
 use typst::World;
 use walkdir::WalkDir;
 
-use crate::args::{CliArguments, Command, CompileCommand};
+use crate::args::{
+    CliArguments, Command, CompileCommand, DiagnosticFormat, PageRange, PdfStandard,
+    SerializationFormat,
+};
+use crate::lsp::LspSettings;
+use crate::serve::ServeSettings;
 
 type CodespanResult<T> = Result<T, CodespanError>;
 type CodespanError = codespan_reporting::files::Error;
@@ -53,25 +70,69 @@ struct CompileSettings {
     /// The paths to search for fonts.
     font_paths: Vec<PathBuf>,
 
+    /// The key/value pairs exposed through `sys.inputs`.
+    inputs: Vec<(String, String)>,
+
     /// The open command to use.
     open: Option<Option<String>>,
+
+    /// The resolution for PNG export, in pixels per inch.
+    ppi: f32,
+
+    /// The pages to export. Empty means all pages.
+    pages: Vec<PageRange>,
+
+    /// The standard that exported PDF files must conform to.
+    pdf_standard: Option<PdfStandard>,
+
+    /// Whether to tag exported PDF files with the document's structure.
+    pdf_tagged: bool,
+
+    /// The creation date of exported PDF files, in seconds since the Unix
+    /// epoch.
+    creation_timestamp: Option<i64>,
+
+    /// How to print diagnostics.
+    diagnostic_format: DiagnosticFormat,
 }
 
 impl CompileSettings {
     /// Create a new compile settings from the field values.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         input: PathBuf,
         output: Option<PathBuf>,
         watch: bool,
         root: Option<PathBuf>,
         font_paths: Vec<PathBuf>,
+        inputs: Vec<(String, String)>,
         open: Option<Option<String>>,
+        ppi: f32,
+        pages: Vec<PageRange>,
+        pdf_standard: Option<PdfStandard>,
+        pdf_tagged: bool,
+        creation_timestamp: Option<i64>,
+        diagnostic_format: DiagnosticFormat,
     ) -> Self {
         let output = match output {
             Some(path) => path,
             None => input.with_extension("pdf"),
         };
-        Self { input, output, watch, root, font_paths, open }
+        Self {
+            input,
+            output,
+            watch,
+            root,
+            font_paths,
+            inputs,
+            open,
+            ppi,
+            pages,
+            pdf_standard,
+            pdf_tagged,
+            creation_timestamp,
+            diagnostic_format,
+        }
     }
 
     /// Create a new compile settings from the CLI arguments and a compile command.
@@ -80,12 +141,87 @@ impl CompileSettings {
     /// Panics if the command is not a compile or watch command.
     pub fn with_arguments(args: CliArguments) -> Self {
         let watch = matches!(args.command, Command::Watch(_));
-        let CompileCommand { input, output, open } = match args.command {
+        let CompileCommand {
+            input,
+            output,
+            open,
+            ppi,
+            pages,
+            pdf_standard,
+            pdf_tagged,
+            creation_timestamp,
+            diagnostic_format,
+        } = match args.command {
             Command::Compile(command) => command,
             Command::Watch(command) => command,
             _ => unreachable!(),
         };
-        Self::new(input, output, watch, args.root, args.font_paths, open)
+        Self::new(
+            input,
+            output,
+            watch,
+            args.root,
+            args.font_paths,
+            args.inputs,
+            open,
+            ppi,
+            pages,
+            pdf_standard,
+            pdf_tagged,
+            creation_timestamp,
+            diagnostic_format,
+        )
+    }
+}
+
+/// A summary of the input arguments relevant to querying.
+struct QuerySettings {
+    /// The path to the input file.
+    input: PathBuf,
+
+    /// The root directory for absolute paths.
+    root: Option<PathBuf>,
+
+    /// The paths to search for fonts.
+    font_paths: Vec<PathBuf>,
+
+    /// The key/value pairs exposed through `sys.inputs`.
+    inputs: Vec<(String, String)>,
+
+    /// The selector for the elements to retrieve.
+    selector: String,
+
+    /// A single field to extract from all retrieved elements.
+    field: Option<String>,
+
+    /// Whether exactly one element is expected.
+    one: bool,
+
+    /// How to serialize the retrieved elements.
+    format: SerializationFormat,
+
+    /// How to print diagnostics.
+    diagnostic_format: DiagnosticFormat,
+}
+
+impl QuerySettings {
+    /// Create new query settings from the CLI arguments.
+    ///
+    /// # Panics
+    /// Panics if the command is not a query command.
+    pub fn with_arguments(args: CliArguments) -> Self {
+        let Command::Query(command) = args.command else { unreachable!() };
+        Self {
+            input: command.input,
+            root: args.root,
+            font_paths: args.font_paths,
+            inputs: args.inputs,
+            selector: command.selector,
+            field: command.field,
+            one: command.one,
+            format: command.format,
+            diagnostic_format: command.diagnostic_format,
+        }
     }
 }
 
@@ -123,6 +259,9 @@ fn main() {
         Command::Compile(_) | Command::Watch(_) => {
             compile(CompileSettings::with_arguments(arguments))
         }
+        Command::Query(_) => query(QuerySettings::with_arguments(arguments)),
+        Command::Serve(_) => serve::serve(ServeSettings::with_arguments(arguments)),
+        Command::Lsp(_) => lsp::lsp(LspSettings::with_arguments(arguments)),
         Command::Fonts(_) => fonts(FontsSettings::with_arguments(arguments)),
     };
 
@@ -143,24 +282,25 @@ fn print_error(msg: &str) -> io::Result<()> {
     writeln!(w, ": {msg}.")
 }
 
-/// Execute a compilation command.
-fn compile(mut command: CompileSettings) -> StrResult<()> {
-    let root = if let Some(root) = &command.root {
+/// Determine the root directory for absolute paths, which defaults to the
+/// directory of the input file.
+fn determine_root(root: Option<&PathBuf>, input: &Path) -> PathBuf {
+    if let Some(root) = root {
         root.clone()
-    } else if let Some(dir) = command
-        .input
-        .canonicalize()
-        .ok()
-        .as_ref()
-        .and_then(|path| path.parent())
+    } else if let Some(dir) =
+        input.canonicalize().ok().as_ref().and_then(|path| path.parent())
     {
         dir.into()
     } else {
         PathBuf::new()
-    };
+    }
+}
 
+/// Execute a compilation command.
+fn compile(mut command: CompileSettings) -> StrResult<()> {
     // Create the world that serves sources, fonts and files.
-    let mut world = SystemWorld::new(root, &command.font_paths);
+    let root = determine_root(command.root.as_ref(), &command.input);
+    let mut world = SystemWorld::new(root, &command.font_paths, &command.inputs);
 
     // Perform initial compilation.
     let failed = compile_once(&mut world, &command)?;
@@ -231,28 +371,271 @@ fn compile_once(world: &mut SystemWorld, command: &CompileSettings) -> StrResult
     world.reset();
     world.main = world.resolve(&command.input).map_err(|err| err.to_string())?;
 
-    match typst::compile(world) {
-        // Export the PDF.
-        Ok(document) => {
-            let buffer = typst::export::pdf(&document);
-            fs::write(&command.output, buffer).map_err(|_| "failed to write PDF file")?;
-            status(command, Status::Success).unwrap();
+    // HTML and text are produced from the content instead of the laid-out
+    // pages.
+    let semantic = command.output.extension().map_or(false, |ext| {
+        ["html", "txt", "md", "epub"]
+            .iter()
+            .any(|name| ext.eq_ignore_ascii_case(name))
+    });
+
+    let mut tracer = Tracer::default();
+    let result = if semantic {
+        typst::compile_html(world, &mut tracer)
+            .map(|document| export_html(&document, command))
+    } else {
+        typst::compile(world, &mut tracer).map(|document| export(&document, command))
+    };
+    let warnings = tracer.warnings().to_vec();
+
+    // Report a document that can't be exported like one that doesn't compile,
+    // but give up if the output can't be written.
+    let result = match result {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(ExportError::Document(message))) => {
+            Err(Box::new(vec![SourceError::new(Span::detached(), message)]))
+        }
+        Ok(Err(ExportError::Io(message))) => return Err(message),
+        Err(errors) => Err(errors),
+    };
+
+    match result {
+        // Print warnings after a successful export.
+        Ok(()) => {
+            if warnings.is_empty() {
+                status(command, Status::Success).unwrap();
+            } else {
+                status(command, Status::PartialSuccess).unwrap();
+                print_diagnostics(world, warnings, command.diagnostic_format)?;
+            }
             Ok(false)
         }
 
-        // Print diagnostics.
+        // Print errors and warnings.
         Err(errors) => {
             status(command, Status::Error).unwrap();
-            print_diagnostics(world, *errors)
-                .map_err(|_| "failed to print diagnostics")?;
+            let mut diagnostics = *errors;
+            diagnostics.extend(warnings);
+            print_diagnostics(world, diagnostics, command.diagnostic_format)?;
             Ok(true)
         }
     }
 }
 
+/// An error that occurred during export.
+enum ExportError {
+    /// The document can't be exported with the given settings.
+    Document(EcoString),
+    /// The output file could not be written.
+    Io(EcoString),
+}
+
+impl From<EcoString> for ExportError {
+    fn from(message: EcoString) -> Self {
+        Self::Document(message)
+    }
+}
+
+impl From<&str> for ExportError {
+    fn from(message: &str) -> Self {
+        Self::Document(message.into())
+    }
+}
+
+/// Export into the target format, which is determined by the output path's
+/// extension.
+fn export(document: &Document, command: &CompileSettings) -> Result<(), ExportError> {
+    match command.output.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("png") => {
+            export_image(document, command, ImageExportFormat::Png)
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("svg") => {
+            export_image(document, command, ImageExportFormat::Svg)
+        }
+        _ => export_pdf(document, command),
+    }
+}
+
+/// Export into an HTML, plain text, Markdown or EPUB file, depending on the
+/// output path's extension.
+fn export_html(
+    document: &HtmlDocument,
+    command: &CompileSettings,
+) -> Result<(), ExportError> {
+    if !command.pages.is_empty() {
+        return Err("pages can only be selected for PDF and image export".into());
+    }
+
+    let buffer = match command.output.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("txt") => {
+            typst::export::text(document).into()
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("md") => {
+            typst::export::markdown(document).into()
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("epub") => {
+            // Reading systems require a modification date.
+            let seconds = command.creation_timestamp.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs() as i64)
+            });
+            let options = EpubOptions {
+                timestamp: Some(
+                    PdfTimestamp::from_unix(seconds)
+                        .ok_or("creation timestamp is out of range")?,
+                ),
+            };
+            typst::export::epub(document, &options)?
+        }
+        _ => typst::export::html(document).into(),
+    };
+
+    fs::write(&command.output, buffer)
+        .map_err(|_| ExportError::Io("failed to write file".into()))?;
+    Ok(())
+}
+
+/// Export the selected pages into a PDF file.
+fn export_pdf(document: &Document, command: &CompileSettings) -> Result<(), ExportError> {
+    let mut options = PdfOptions::default();
+    if !command.pages.is_empty() {
+        let pages = selected_pages(document, &command.pages);
+        if pages.is_empty() {
+            return Err("no pages were selected for export".into());
+        }
+        options.pages = Some(pages.into_iter().map(|(number, _)| number).collect());
+    }
+
+    options.standard = command.pdf_standard.map(|standard| match standard {
+        PdfStandard::A2b => typst::export::PdfStandard::A2b,
+    });
+    options.tagged = command.pdf_tagged;
+    if let Some(seconds) = command.creation_timestamp {
+        let timestamp = PdfTimestamp::from_unix(seconds)
+            .ok_or("creation timestamp is out of range")?;
+        options.timestamp = Some(timestamp);
+    }
+
+    let buffer = typst::export::pdf_with_options(document, &options)?;
+    fs::write(&command.output, buffer)
+        .map_err(|_| ExportError::Io("failed to write PDF file".into()))?;
+    Ok(())
+}
+
+/// An image format to export in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ImageExportFormat {
+    Png,
+    Svg,
+}
+
+impl ImageExportFormat {
+    /// The name of the format for use in error messages.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Svg => "SVG",
+        }
+    }
+}
+
+/// Export the selected pages into one image file each.
+fn export_image(
+    document: &Document,
+    command: &CompileSettings,
+    fmt: ImageExportFormat,
+) -> Result<(), ExportError> {
+    let pages = selected_pages(document, &command.pages);
+    if pages.is_empty() {
+        return Err("no pages were selected for export".into());
+    }
+
+    // Determine whether we have a `{n}` numbering.
+    let template = command.output.to_str().ok_or("output path is not valid UTF-8")?;
+    let numbered = template.contains("{n}");
+    if !numbered && pages.len() > 1 {
+        return Err(eco_format!(
+            "cannot export multiple {}s without `{{n}}` in output path",
+            fmt.name()
+        )
+        .into());
+    }
+
+    // Find a number width that accommodates all pages. For instance, the
+    // first page should be numbered "001" if there are between 100 and 999
+    // pages.
+    let width = document.pages.len().to_string().len();
+    let path = |number: NonZeroUsize| {
+        if numbered {
+            PathBuf::from(template.replace("{n}", &format!("{number:0width$}")))
+        } else {
+            command.output.clone()
+        }
+    };
+
+    // Links within the document point to the files of the exported pages,
+    // which all end up in the same directory.
+    let introspector = Introspector::new(&document.pages);
+    let resolve = |dest: &Destination| {
+        let page = match dest {
+            Destination::Url(url) => return Some(url.clone()),
+            Destination::Position(pos) => pos.page,
+            Destination::Location(loc) => introspector.position(*loc).page,
+        };
+        pages.iter().find(|&&(number, _)| number == page)?;
+        let name = path(page).file_name()?.to_str()?.into();
+        Some(name)
+    };
+
+    for &(number, frame) in &pages {
+        let path = path(number);
+        match fmt {
+            ImageExportFormat::Png => {
+                let pixmap =
+                    typst::export::render(frame, command.ppi / 72.0, Color::WHITE)?;
+                pixmap
+                    .save_png(path)
+                    .map_err(|_| ExportError::Io("failed to write PNG file".into()))?;
+            }
+            ImageExportFormat::Svg => {
+                let svg = typst::export::svg_with_links(frame, &resolve);
+                fs::write(path, svg)
+                    .map_err(|_| ExportError::Io("failed to write SVG file".into()))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Select the pages in the given ranges alongside their page numbers.
+///
+/// If no ranges are given, all pages are selected.
+fn selected_pages<'a>(
+    document: &'a Document,
+    ranges: &[PageRange],
+) -> Vec<(NonZeroUsize, &'a Frame)> {
+    document
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| (NonZeroUsize::new(i + 1).unwrap(), frame))
+        .filter(|&(number, _)| {
+            ranges.is_empty()
+                || ranges.iter().any(|range| {
+                    range.start.map_or(true, |start| start <= number)
+                        && range.end.map_or(true, |end| number <= end)
+                })
+        })
+        .collect()
+}
+
 /// Clear the terminal and render the status message.
 fn status(command: &CompileSettings, status: Status) -> io::Result<()> {
-    if !command.watch {
+    // Machine-readable output must not be interleaved with terminal control
+    // sequences.
+    if !command.watch || command.diagnostic_format == DiagnosticFormat::Json {
         return Ok(());
     }
 
@@ -288,6 +671,7 @@ fn status(command: &CompileSettings, status: Status) -> io::Result<()> {
 enum Status {
     Compiling,
     Success,
+    PartialSuccess,
     Error,
 }
 
@@ -296,6 +680,7 @@ impl Status {
         match self {
             Self::Compiling => "compiling ...",
             Self::Success => "compiled successfully",
+            Self::PartialSuccess => "compiled with warnings",
             Self::Error => "compiled with errors",
         }
     }
@@ -304,13 +689,29 @@ impl Status {
         let styles = term::Styles::default();
         match self {
             Self::Error => styles.header_error,
+            Self::PartialSuccess => styles.header_warning,
             _ => styles.header_note,
         }
     }
 }
 
-/// Print diagnostic messages to the terminal.
+/// Print diagnostic messages in the given format.
 fn print_diagnostics(
+    world: &SystemWorld,
+    diagnostics: Vec<SourceError>,
+    format: DiagnosticFormat,
+) -> StrResult<()> {
+    match format {
+        DiagnosticFormat::Human => print_human_diagnostics(world, diagnostics)
+            .map_err(|_| "failed to print diagnostics")?,
+        DiagnosticFormat::Json => print_json_diagnostics(world, diagnostics)
+            .map_err(|_| "failed to print diagnostics")?,
+    }
+    Ok(())
+}
+
+/// Print diagnostic messages to the terminal.
+fn print_human_diagnostics(
     world: &SystemWorld,
     errors: Vec<SourceError>,
 ) -> Result<(), codespan_reporting::files::Error> {
@@ -318,11 +719,18 @@ fn print_diagnostics(
     let config = term::Config { tab_width: 2, ..Default::default() };
 
     for error in errors {
-        // The main diagnostic.
-        let range = error.range(world);
-        let diag = Diagnostic::error()
-            .with_message(error.message)
-            .with_labels(vec![Label::primary(error.span.source(), range)]);
+        // The main diagnostic. Errors that don't stem from the source code,
+        // like those from export, have no location.
+        let diag = match error.severity {
+            Severity::Error => Diagnostic::error(),
+            Severity::Warning => Diagnostic::warning(),
+        };
+        let labels = if error.span.is_detached() {
+            vec![]
+        } else {
+            vec![Label::primary(error.span.source(), error.range(world))]
+        };
+        let diag = diag.with_message(error.message).with_labels(labels);
 
         term::emit(&mut w, &config, world, &diag)?;
 
@@ -343,6 +751,120 @@ fn print_diagnostics(
     Ok(())
 }
 
+/// Print diagnostic messages as JSON, one object per line.
+fn print_json_diagnostics(
+    world: &SystemWorld,
+    errors: Vec<SourceError>,
+) -> io::Result<()> {
+    let mut w = io::stderr().lock();
+    for error in &errors {
+        serde_json::to_writer(&mut w, &JsonDiagnostic::new(world, error))?;
+        writeln!(w)?;
+    }
+
+    w.flush()
+}
+
+/// A diagnostic in the JSON output format.
+#[derive(Serialize)]
+struct JsonDiagnostic {
+    /// How severe the diagnostic is.
+    severity: &'static str,
+    /// The diagnostic message.
+    message: String,
+    /// Where the diagnostic occurred.
+    #[serde(flatten)]
+    location: JsonLocation,
+    /// The trace of calls leading to the diagnostic, innermost first.
+    trace: Vec<JsonTracepoint>,
+}
+
+impl JsonDiagnostic {
+    /// Convert a source error into the JSON output format.
+    fn new(world: &SystemWorld, error: &SourceError) -> Self {
+        let range = (!error.span.is_detached()).then(|| error.range(world));
+        let location = JsonLocation::new(world, error.span.source(), range);
+
+        let trace = error
+            .trace
+            .iter()
+            .map(|point| {
+                let id = point.span.source();
+                let range = (!point.span.is_detached())
+                    .then(|| world.source(id).range(point.span));
+                JsonTracepoint {
+                    message: point.v.to_string(),
+                    location: JsonLocation::new(world, id, range),
+                }
+            })
+            .collect();
+
+        Self {
+            severity: match error.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+            message: error.message.to_string(),
+            location,
+            trace,
+        }
+    }
+}
+
+/// A part of a diagnostic's trace in the JSON output format.
+#[derive(Serialize)]
+struct JsonTracepoint {
+    /// A description of the trace point.
+    message: String,
+    /// Where the trace point is located.
+    #[serde(flatten)]
+    location: JsonLocation,
+}
+
+/// A location in a source file in the JSON output format.
+///
+/// All fields are `null` if the location is unknown. Lines and columns start
+/// at one, byte offsets at zero.
+#[derive(Default, Serialize)]
+struct JsonLocation {
+    /// The path of the source file.
+    path: Option<String>,
+    /// The byte range in the source file.
+    range: Option<Range<usize>>,
+    /// The line and column of the start of the range.
+    start: Option<JsonPosition>,
+    /// The line and column of the end of the range.
+    end: Option<JsonPosition>,
+}
+
+impl JsonLocation {
+    /// Resolve a byte range in a source file.
+    fn new(world: &SystemWorld, id: SourceId, range: Option<Range<usize>>) -> Self {
+        let Some(range) = range else { return Self::default() };
+        let source = world.source(id);
+        let position = |byte| {
+            Some(JsonPosition {
+                line: source.byte_to_line(byte)? + 1,
+                column: source.byte_to_column(byte)? + 1,
+            })
+        };
+
+        Self {
+            path: Some(source.path().display().to_string()),
+            start: position(range.start),
+            end: position(range.end),
+            range: Some(range),
+        }
+    }
+}
+
+/// A line and column in the JSON output format.
+#[derive(Serialize)]
+struct JsonPosition {
+    line: usize,
+    column: usize,
+}
+
 /// Opens the given file using:
 /// - The default file viewer if `open` is `None`.
 /// - The given viewer provided by `open` if it is `Some`.
@@ -356,6 +878,125 @@ fn open_file(open: Option<&str>, path: &Path) -> StrResult<()> {
     Ok(())
 }
 
+/// Execute a query command.
+fn query(command: QuerySettings) -> StrResult<()> {
+    // Create the world that serves sources, fonts and files.
+    let root = determine_root(command.root.as_ref(), &command.input);
+    let mut world = SystemWorld::new(root, &command.font_paths, &command.inputs);
+    world.main = world.resolve(&command.input).map_err(|err| err.to_string())?;
+
+    // Compile the document.
+    let mut tracer = Tracer::default();
+    let result = typst::compile(&world, &mut tracer);
+    let warnings = tracer.warnings().to_vec();
+    let document = match result {
+        Ok(document) => {
+            if !warnings.is_empty() {
+                print_diagnostics(&world, warnings, command.diagnostic_format)?;
+            }
+            document
+        }
+        Err(errors) => {
+            let mut diagnostics = *errors;
+            diagnostics.extend(warnings);
+            print_diagnostics(&world, diagnostics, command.diagnostic_format)?;
+            process::exit(1);
+        }
+    };
+
+    // Evaluate the selector.
+    let selector = typst::eval::eval_string(
+        (&world as &dyn World).track(),
+        &command.selector,
+        Span::detached(),
+    )
+    .map_err(|errors| {
+        let mut message = String::from("failed to evaluate selector");
+        for (i, error) in errors.into_iter().enumerate() {
+            message.push_str(if i == 0 { ": " } else { ", " });
+            message.push_str(&error.message);
+        }
+        message
+    })?
+    .cast::<LocatableSelector>()?;
+
+    // Retrieve the matching elements.
+    let introspector = Introspector::new(&document.pages);
+    let elements = introspector.query(&selector.0);
+
+    if command.one && elements.len() != 1 {
+        let found = elements.len();
+        return Err(format!("expected exactly one element, found {found}").into());
+    }
+
+    let output = match &command.field {
+        Some(field) => {
+            let values: Vec<_> =
+                elements.iter().filter_map(|element| element.field(field)).collect();
+            if command.one {
+                serialize(&values.first(), command.format)?
+            } else {
+                serialize(&values, command.format)?
+            }
+        }
+        None => {
+            let results: Vec<_> = elements
+                .into_iter()
+                .map(|element| {
+                    let position = introspector.position(element.location().unwrap());
+                    QueryResult {
+                        element,
+                        position: QueryPosition {
+                            page: position.page.get(),
+                            x: position.point.x.to_pt(),
+                            y: position.point.y.to_pt(),
+                        },
+                    }
+                })
+                .collect();
+            if command.one {
+                serialize(&results[0], command.format)?
+            } else {
+                serialize(&results, command.format)?
+            }
+        }
+    };
+
+    println!("{output}");
+    Ok(())
+}
+
+/// Serialize query results in the given format.
+fn serialize<T: Serialize>(data: &T, format: SerializationFormat) -> StrResult<String> {
+    match format {
+        SerializationFormat::Json => serde_json::to_string_pretty(data)
+            .map_err(|err| format!("failed to serialize to JSON ({err})").into()),
+        SerializationFormat::Yaml => serde_yaml::to_string(data)
+            .map_err(|err| format!("failed to serialize to YAML ({err})").into()),
+    }
+}
+
+/// An element retrieved by a query, together with its position.
+#[derive(Serialize)]
+struct QueryResult {
+    /// The element's function name and fields.
+    #[serde(flatten)]
+    element: Content,
+    /// Where the element is located in the document.
+    position: QueryPosition,
+}
+
+/// The position of a retrieved element.
+#[derive(Serialize)]
+struct QueryPosition {
+    /// The page number, starting at one.
+    page: usize,
+    /// The horizontal offset from the top-left corner of the page, in points.
+    x: f64,
+    /// The vertical offset from the top-left corner of the page, in points.
+    y: f64,
+}
+
 /// Execute a font listing command.
 fn fonts(command: FontsSettings) -> StrResult<()> {
     let mut searcher = FontSearcher::new();
@@ -382,6 +1023,7 @@ struct SystemWorld {
     fonts: Vec<FontSlot>,
     hashes: RefCell<HashMap<PathBuf, FileResult<PathHash>>>,
     paths: RefCell<HashMap<PathHash, PathSlot>>,
+    memory: HashMap<PathBuf, SourceId>,
     sources: FrozenVec<Box<Source>>,
     main: SourceId,
 }
@@ -401,17 +1043,23 @@ struct PathSlot {
 }
 
 impl SystemWorld {
-    fn new(root: PathBuf, font_paths: &[PathBuf]) -> Self {
+    fn new(root: PathBuf, font_paths: &[PathBuf], inputs: &[(String, String)]) -> Self {
         let mut searcher = FontSearcher::new();
         searcher.search(font_paths);
 
+        let inputs: Dict = inputs
+            .iter()
+            .map(|(key, value)| (key.as_str().into(), Value::Str(value.as_str().into())))
+            .collect();
+
         Self {
             root,
-            library: Prehashed::new(typst_library::build()),
+            library: Prehashed::new(typst_library::build_with_inputs(inputs)),
             book: Prehashed::new(searcher.book),
             fonts: searcher.fonts,
             hashes: RefCell::default(),
             paths: RefCell::default(),
+            memory: HashMap::new(),
             sources: FrozenVec::new(),
             main: SourceId::detached(),
         }
@@ -432,6 +1080,10 @@ impl World for SystemWorld {
     }
 
     fn resolve(&self, path: &Path) -> FileResult<SourceId> {
+        if let Some(&id) = self.memory.get(&path.normalize()) {
+            return Ok(id);
+        }
+
         self.slot(path)?
             .source
             .get_or_init(|| {
@@ -524,6 +1176,63 @@ impl SystemWorld {
         self.sources.as_mut().clear();
         self.hashes.borrow_mut().clear();
         self.paths.borrow_mut().clear();
+        self.memory.clear();
+    }
+
+    /// Reread all files from disk, except for the given ones, which keep their
+    /// text from memory.
+    fn reload(&mut self, keep: &[PathBuf]) -> FileResult<()> {
+        let kept = keep
+            .iter()
+            .map(|path| {
+                let id = self.resolve(path)?;
+                Ok((path, self.source(id).text().to_string()))
+            })
+            .collect::<FileResult<Vec<_>>>()?;
+
+        self.reset();
+        for (path, text) in kept {
+            self.update(path, text)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the text of a source file in memory, without touching the file
+    /// on disk. If there is no such file on disk, it is created in memory.
+    fn update(&mut self, path: &Path, text: String) -> FileResult<()> {
+        match self.resolve(path) {
+            Ok(id) => self.sources.as_mut()[id.into_u16() as usize].replace(text),
+            Err(FileError::NotFound(_)) => {
+                let id = self.insert(path, text);
+                self.memory.insert(path.normalize(), id);
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(())
+    }
+
+    /// Replace a byte range of a source file in memory, without touching the
+    /// file on disk. Only the affected part of the syntax tree is reparsed.
+    fn edit(&mut self, path: &Path, range: Range<usize>, with: &str) -> StrResult<()> {
+        let id = self.resolve(path).map_err(|err| err.to_string())?;
+        let source = &mut self.sources.as_mut()[id.into_u16() as usize];
+        let text = source.text();
+        if range.start > range.end
+            || range.end > text.len()
+            || !text.is_char_boundary(range.start)
+            || !text.is_char_boundary(range.end)
+        {
+            return Err(format!(
+                "range {}..{} is invalid for a source of length {}",
+                range.start,
+                range.end,
+                text.len(),
+            )
+            .into());
+        }
+        source.edit(range, with);
+        Ok(())
     }
 }
 
@@ -0,0 +1,207 @@
+//! JSON-RPC 2.0 messages, framed with `Content-Length` headers like in the
+//! language server protocol.
+
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// A request or notification from the client.
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// The id to answer with. Notifications don't have one.
+    #[serde(default)]
+    pub id: Option<Value>,
+    /// The method to invoke.
+    pub method: String,
+    /// The parameters for the method.
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// An error that is reported back to the client.
+#[derive(Debug, Clone)]
+pub struct Error {
+    /// The error code.
+    pub code: i64,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl Error {
+    /// The message was not valid JSON.
+    pub const PARSE_ERROR: i64 = -32700;
+    /// The message was not a valid request.
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// The requested method does not exist.
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// The parameters did not fit the method.
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// The request was valid, but could not be carried out.
+    pub const REQUEST_FAILED: i64 = -32000;
+
+    /// Create a new error.
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    /// The parameters did not fit the method.
+    pub fn invalid_params(message: impl Display) -> Self {
+        Self::new(Self::INVALID_PARAMS, message.to_string())
+    }
+
+    /// The request was valid, but could not be carried out.
+    pub fn failed(message: impl Display) -> Self {
+        Self::new(Self::REQUEST_FAILED, message.to_string())
+    }
+}
+
+/// Deserialize the parameters of a request.
+pub fn params<T: DeserializeOwned>(params: Value) -> Result<T, Error> {
+    serde_json::from_value(params).map_err(Error::invalid_params)
+}
+
+/// Serialize the result of a request.
+pub fn result<T: Serialize>(result: T) -> Result<Value, Error> {
+    serde_json::to_value(result).map_err(Error::failed)
+}
+
+/// Create the response to a request.
+pub fn response(id: Value, result: Result<Value, Error>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+/// Create a notification, which the client does not answer.
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// The header that holds the length of a message's body.
+const CONTENT_LENGTH: &str = "content-length:";
+
+/// Read the body of the next message.
+///
+/// Returns `None` once the input is exhausted. A message with a malformed
+/// header yields a parse error, after which reading can continue.
+pub fn read(reader: &mut impl BufRead) -> io::Result<Option<Result<Vec<u8>, Error>>> {
+    let mut length = None;
+    let mut headers = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            // Stray line breaks between messages are skipped.
+            if headers {
+                break;
+            }
+            continue;
+        }
+
+        // The header is also found behind the remains of a malformed message,
+        // so that we get back in sync with the client.
+        headers = true;
+        if let Some(i) = line.to_ascii_lowercase().find(CONTENT_LENGTH) {
+            length = Some(line[i + CONTENT_LENGTH.len()..].trim().parse::<usize>());
+        }
+    }
+
+    let length = match length {
+        Some(Ok(length)) => length,
+        Some(Err(_)) => return Ok(Some(Err(parse_error("invalid content length")))),
+        None => return Ok(Some(Err(parse_error("missing content length")))),
+    };
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(Ok(body)))
+}
+
+/// An error for a message that could not be parsed.
+fn parse_error(message: &str) -> Error {
+    Error::new(Error::PARSE_ERROR, message)
+}
+
+/// Write a message.
+pub fn write(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_vec(message)?;
+    write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(input: &str) -> Vec<Result<String, i64>> {
+        let mut reader = input.as_bytes();
+        let mut messages = vec![];
+        while let Some(body) = read(&mut reader).unwrap() {
+            messages.push(
+                body.map(|body| String::from_utf8(body).unwrap())
+                    .map_err(|err| err.code),
+            );
+        }
+        messages
+    }
+
+    #[test]
+    fn test_rpc_read() {
+        assert_eq!(read_all(""), vec![]);
+        assert_eq!(read_all("Content-Length: 2\r\n\r\n{}"), vec![Ok("{}".into())]);
+        assert_eq!(
+            read_all(
+                "content-length: 2\r\nContent-Type: utf-8\r\n\r\n[]\
+                 \r\nCONTENT-LENGTH:1\n\n1"
+            ),
+            vec![Ok("[]".into()), Ok("1".into())],
+        );
+    }
+
+    #[test]
+    fn test_rpc_read_malformed() {
+        let error = Err(Error::PARSE_ERROR);
+        assert_eq!(read_all("Content-Type: utf-8\r\n\r\n"), vec![error.clone()]);
+        assert_eq!(read_all("Content-Length: two\r\n\r\n"), vec![error.clone()]);
+
+        // After a message without length, the next one is still found.
+        assert_eq!(
+            read_all("Content-Type: utf-8\r\n\r\n{}Content-Length: 2\r\n\r\n[]"),
+            vec![error, Ok("[]".into())],
+        );
+    }
+
+    #[test]
+    fn test_rpc_read_truncated() {
+        let mut reader = "Content-Length: 10\r\n\r\n{}".as_bytes();
+        assert!(read(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_rpc_write() {
+        let mut buf = vec![];
+        let message = notification("exit", Value::Null);
+        write(&mut buf, &message).unwrap();
+
+        let mut reader = buf.as_slice();
+        let body = read(&mut reader).unwrap().unwrap().unwrap();
+        assert!(
+            buf.starts_with(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        );
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), message);
+        assert!(read(&mut reader).unwrap().is_none());
+    }
+}
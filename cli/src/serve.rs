@@ -0,0 +1,310 @@
+//! A long-running compilation server that speaks JSON-RPC on stdio.
+//!
+//! Messages are framed with `Content-Length` headers. The server understands
+//! the following methods:
+//!
+//! - `compile`: Compiles the input file and returns diagnostics and a hash for
+//!   each page. A page's hash only changes if the page's content does.
+//! - `update`: Replaces the text of a source file in memory or creates it in
+//!   memory if it doesn't exist on disk. Parameters are the `path` of the file
+//!   and its new `text`.
+//! - `edit`: Replaces a byte `range` of a source file in memory with `text`.
+//!   Only the affected part of the file is reparsed.
+//! - `render`: Renders a `page` of the last successfully compiled document as
+//!   a base64-encoded PNG with the given `ppi` or as an SVG, depending on the
+//!   `format`.
+//! - `reload`: Rereads all files that were not edited in memory from disk.
+//! - `exit`: Stops the server.
+//!
+//! In-memory edits are never written back to disk.
+
+use std::io;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use typst::diag::StrResult;
+use typst::doc::Document;
+use typst::eval::Tracer;
+use typst::geom::Color;
+use typst::util::hash128;
+use typst::World;
+
+use crate::args::{CliArguments, Command};
+use crate::rpc::{self, Error, Request};
+use crate::{determine_root, JsonDiagnostic, SystemWorld};
+
+/// A summary of the input arguments relevant to serving.
+pub struct ServeSettings {
+    /// The path to the input file.
+    input: PathBuf,
+
+    /// The root directory for absolute paths.
+    root: Option<PathBuf>,
+
+    /// The paths to search for fonts.
+    font_paths: Vec<PathBuf>,
+
+    /// The key/value pairs exposed through `sys.inputs`.
+    inputs: Vec<(String, String)>,
+}
+
+impl ServeSettings {
+    /// Create new serve settings from the CLI arguments.
+    ///
+    /// # Panics
+    /// Panics if the command is not a serve command.
+    pub fn with_arguments(args: CliArguments) -> Self {
+        let Command::Serve(command) = args.command else { unreachable!() };
+        Self {
+            input: command.input,
+            root: args.root,
+            font_paths: args.font_paths,
+            inputs: args.inputs,
+        }
+    }
+}
+
+/// Execute a serve command.
+pub fn serve(command: ServeSettings) -> StrResult<()> {
+    let root = determine_root(command.root.as_ref(), &command.input);
+    let mut server = Server {
+        world: SystemWorld::new(root, &command.font_paths, &command.inputs),
+        input: command.input,
+        edited: vec![],
+        document: None,
+    };
+
+    let mut reader = io::stdin().lock();
+    let mut writer = io::stdout().lock();
+    while let Some(body) = rpc::read(&mut reader).map_err(|_| "failed to read message")? {
+        let request = body
+            .and_then(|body| {
+                serde_json::from_slice::<Value>(&body)
+                    .map_err(|err| Error::new(Error::PARSE_ERROR, err.to_string()))
+            })
+            .and_then(|message| {
+                serde_json::from_value::<Request>(message)
+                    .map_err(|err| Error::new(Error::INVALID_REQUEST, err.to_string()))
+            });
+
+        let response = match request {
+            Ok(request) if request.method == "exit" => break,
+            Ok(request) => {
+                let result = server.handle(&request.method, request.params);
+                match request.id {
+                    Some(id) => rpc::response(id, result),
+                    None => continue,
+                }
+            }
+            Err(error) => rpc::response(Value::Null, Err(error)),
+        };
+
+        rpc::write(&mut writer, &response).map_err(|_| "failed to write message")?;
+    }
+
+    Ok(())
+}
+
+/// The state of the compilation server.
+struct Server {
+    /// The world that serves sources, fonts and files.
+    world: SystemWorld,
+    /// The path to the input file.
+    input: PathBuf,
+    /// The files whose text was changed in memory.
+    edited: Vec<PathBuf>,
+    /// The last successfully compiled document.
+    document: Option<Document>,
+}
+
+impl Server {
+    /// Dispatch a request to the method with the given name.
+    fn handle(&mut self, method: &str, params: Value) -> Result<Value, Error> {
+        match method {
+            "compile" => rpc::result(self.compile()?),
+            "update" => rpc::result(self.update(rpc::params(params)?)?),
+            "edit" => rpc::result(self.edit(rpc::params(params)?)?),
+            "render" => rpc::result(self.render(rpc::params(params)?)?),
+            "reload" => rpc::result(self.reload()?),
+            _ => Err(Error::new(
+                Error::METHOD_NOT_FOUND,
+                format!("unknown method: {method}"),
+            )),
+        }
+    }
+
+    /// Compile the input file.
+    fn compile(&mut self) -> Result<CompileResult, Error> {
+        self.world.main = self.world.resolve(&self.input).map_err(Error::failed)?;
+
+        let mut tracer = Tracer::default();
+        let result = typst::compile(&self.world, &mut tracer);
+        let warnings = tracer.warnings().to_vec();
+
+        let (success, errors) = match result {
+            Ok(document) => {
+                self.document = Some(document);
+                (true, vec![])
+            }
+            Err(errors) => (false, *errors),
+        };
+
+        let diagnostics = errors
+            .iter()
+            .chain(&warnings)
+            .map(|error| JsonDiagnostic::new(&self.world, error))
+            .collect();
+
+        let pages = success.then(|| {
+            self.document
+                .iter()
+                .flat_map(|document| &document.pages)
+                .map(|frame| PageInfo {
+                    hash: format!("{:032x}", hash128(frame)),
+                    width: frame.width().to_pt(),
+                    height: frame.height().to_pt(),
+                })
+                .collect()
+        });
+
+        comemo::evict(30);
+
+        Ok(CompileResult { success, diagnostics, pages })
+    }
+
+    /// Replace the text of a source file in memory.
+    fn update(&mut self, params: UpdateParams) -> Result<(), Error> {
+        self.world.update(&params.path, params.text).map_err(Error::failed)?;
+        self.mark_edited(params.path);
+        Ok(())
+    }
+
+    /// Replace a range of a source file in memory.
+    fn edit(&mut self, params: EditParams) -> Result<(), Error> {
+        self.world
+            .edit(&params.path, params.range, &params.text)
+            .map_err(Error::failed)?;
+        self.mark_edited(params.path);
+        Ok(())
+    }
+
+    /// Render a page of the last successfully compiled document.
+    fn render(&self, params: RenderParams) -> Result<RenderResult, Error> {
+        let document = self
+            .document
+            .as_ref()
+            .ok_or_else(|| Error::failed("no document was compiled successfully yet"))?;
+
+        let frame = document.pages.get(params.page.get() - 1).ok_or_else(|| {
+            Error::failed(format!("page {} does not exist", params.page))
+        })?;
+
+        let data = match params.format {
+            RenderFormat::Png => {
+                if !params.ppi.is_finite() || params.ppi <= 0.0 {
+                    return Err(Error::invalid_params("resolution must be positive"));
+                }
+
+                let pixel_per_pt = params.ppi / 72.0;
+                let pixmap = typst::export::render(frame, pixel_per_pt, Color::WHITE)
+                    .map_err(Error::failed)?;
+                let png = pixmap.encode_png().map_err(Error::failed)?;
+                base64::encode(png)
+            }
+            RenderFormat::Svg => typst::export::svg(frame),
+        };
+
+        Ok(RenderResult { data })
+    }
+
+    /// Reread all files that were not edited in memory from disk.
+    fn reload(&mut self) -> Result<(), Error> {
+        self.world.reload(&self.edited).map_err(Error::failed)
+    }
+
+    /// Remember that a file was changed in memory.
+    fn mark_edited(&mut self, path: PathBuf) {
+        if !self.edited.contains(&path) {
+            self.edited.push(path);
+        }
+    }
+}
+
+/// The parameters of an `update` request.
+#[derive(Deserialize)]
+struct UpdateParams {
+    /// The path of the source file.
+    path: PathBuf,
+    /// The new text of the file.
+    text: String,
+}
+
+/// The parameters of an `edit` request.
+#[derive(Deserialize)]
+struct EditParams {
+    /// The path of the source file.
+    path: PathBuf,
+    /// The byte range to replace, given as `{"start": .., "end": ..}`.
+    range: Range<usize>,
+    /// The replacement text.
+    text: String,
+}
+
+/// The parameters of a `render` request.
+#[derive(Deserialize)]
+struct RenderParams {
+    /// The page to render, starting at one.
+    page: NonZeroUsize,
+    /// The image format to render into.
+    #[serde(default)]
+    format: RenderFormat,
+    /// The resolution for PNG rendering, in pixels per inch.
+    #[serde(default = "default_ppi")]
+    ppi: f32,
+}
+
+/// The default resolution for rendering.
+fn default_ppi() -> f32 {
+    144.0
+}
+
+/// An image format to render pages into.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RenderFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+/// The result of a `compile` request.
+#[derive(Serialize)]
+struct CompileResult {
+    /// Whether the document compiled without errors.
+    success: bool,
+    /// The errors and warnings that occurred during compilation.
+    diagnostics: Vec<JsonDiagnostic>,
+    /// The pages of the document, if compilation succeeded.
+    pages: Option<Vec<PageInfo>>,
+}
+
+/// Information about a compiled page.
+#[derive(Serialize)]
+struct PageInfo {
+    /// A hash of the page's content, as 32 hexadecimal digits.
+    hash: String,
+    /// The width of the page in points.
+    width: f64,
+    /// The height of the page in points.
+    height: f64,
+}
+
+/// The result of a `render` request.
+#[derive(Serialize)]
+struct RenderResult {
+    /// The PNG image encoded as base64 or the SVG image.
+    data: String,
+}
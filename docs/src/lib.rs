@@ -743,6 +743,8 @@ const TYPE_ORDER: &[&str] = &[
     "relative length",
     "fraction",
     "color",
+    "gradient",
+    "pattern",
     "string",
     "regex",
     "label",
@@ -4,6 +4,7 @@ pub mod calc;
 mod construct;
 mod data;
 mod foundations;
+pub mod sys;
 
 pub use self::construct::*;
 pub use self::data::*;
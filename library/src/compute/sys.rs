@@ -0,0 +1,16 @@
+//! Access to the environment the document is compiled in.
+
+use typst::eval::{Module, Scope};
+
+use crate::prelude::*;
+
+/// A module with system-related definitions.
+///
+/// The `inputs` dictionary holds the key/value pairs that were passed to the
+/// compiler from the outside, for instance with `--input key=value` on the
+/// command line.
+pub fn module(inputs: Dict) -> Module {
+    let mut scope = Scope::new();
+    scope.define("inputs", inputs);
+    Module::new("sys").with_scope(scope)
+}
@@ -0,0 +1,381 @@
+//! Conversion of content into semantic HTML.
+
+use typst::export::{HtmlElement, HtmlNode};
+use typst::image::{Image, ImageFormat, VectorFormat};
+use typst::model::{apply_recipes, realize, Guard};
+use typst::util::hash128;
+
+use crate::layout::{EnumItem, HElem, ListItem, PageElem, Paper, ParbreakElem, TermItem};
+use crate::prelude::*;
+use crate::text::{LinebreakElem, SmartQuoteElem, SpaceElem, TextElem};
+use crate::visualize::ImageElem;
+
+/// Convert content into the nodes of an HTML document's body.
+///
+/// Elements with an [`Html`] rule become the HTML elements the rule produces.
+/// User-defined show rules still apply first. Content without an HTML
+/// equivalent is laid out and embedded as a frame.
+pub fn html(
+    vt: &mut Vt,
+    content: &Content,
+    styles: StyleChain,
+) -> SourceResult<Vec<HtmlNode>> {
+    let mut builder = HtmlBuilder::default();
+    builder.accept(vt, content, styles)?;
+    Ok(builder.finish(true, true))
+}
+
+/// An element that has a semantic representation in HTML.
+pub trait Html {
+    /// Produce the element's HTML form.
+    ///
+    /// The result is built from [`HtmlElem`]s, whose bodies may in turn hold
+    /// arbitrary content.
+    fn html(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content>;
+}
+
+/// An HTML element whose body is converted to HTML in turn.
+///
+/// Display: HTML Element
+/// Category: special
+#[element]
+pub struct HtmlElem {
+    /// The element's tag name.
+    #[required]
+    pub tag: EcoString,
+
+    /// The element's attributes.
+    #[default]
+    pub attrs: Dict,
+
+    /// The element's contents.
+    #[default]
+    pub body: Content,
+}
+
+/// The attributes that make a located element the target of links.
+pub fn anchor_attrs(elem: &Content) -> Dict {
+    let mut attrs = Dict::new();
+    if let Some(anchor) = anchor(elem) {
+        attrs.insert("id".into(), anchor.into());
+    }
+    attrs
+}
+
+/// The anchor under which a located element can be linked to.
+///
+/// This is the element's label if it has one and is otherwise derived from
+/// its location.
+fn anchor(elem: &Content) -> Option<EcoString> {
+    if let Some(label) = elem.label() {
+        return Some(label.0.clone());
+    }
+
+    elem.location()
+        .map(|location| eco_format!("loc-{:x}", hash128(&location) as u64))
+}
+
+/// Builds a sequence of HTML nodes from content.
+#[derive(Default)]
+struct HtmlBuilder {
+    /// The pieces collected so far.
+    pieces: Vec<Piece>,
+}
+
+/// A piece of a sequence of HTML nodes that is not yet grouped into
+/// paragraphs and lists.
+enum Piece {
+    /// A node that is either inline or a block.
+    Node(HtmlNode),
+    /// A space between words.
+    Space,
+    /// A break between paragraphs.
+    Parbreak,
+    /// An item of a bullet, numbered or term list, which is grouped with
+    /// adjacent items of the same kind.
+    Item(ElemFunc, Vec<HtmlNode>),
+}
+
+impl HtmlBuilder {
+    fn accept(
+        &mut self,
+        vt: &mut Vt,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        if content.needs_preparation() {
+            if let Some(prepared) = realize(vt, content, styles)? {
+                return self.accept(vt, &prepared, styles);
+            }
+        }
+
+        if let Some(realized) = apply_recipes(vt, content, styles)? {
+            return self.accept(vt, &realized, styles);
+        }
+
+        if let Some((elem, local)) = content.to_styled() {
+            return self.styled(vt, elem, local, styles);
+        }
+
+        if let Some(children) = content.to_sequence() {
+            for child in children {
+                self.accept(vt, child, styles)?;
+            }
+            return Ok(());
+        }
+
+        if let Some(elem) = content.to::<TextElem>() {
+            self.push(HtmlNode::Text(elem.text()));
+        } else if content.is::<SpaceElem>() || content.is::<HElem>() {
+            self.pieces.push(Piece::Space);
+        } else if content.is::<ParbreakElem>() {
+            self.pieces.push(Piece::Parbreak);
+        } else if content.is::<LinebreakElem>() {
+            self.push(HtmlNode::Element(HtmlElement::new("br")));
+        } else if let Some(elem) = content.to::<SmartQuoteElem>() {
+            let quote = if elem.double(styles) { "\"" } else { "'" };
+            self.push(HtmlNode::Text(quote.into()));
+        } else if let Some(elem) = content.to::<HtmlElem>() {
+            let tag = elem.tag();
+            let lists = !matches!(tag.as_str(), "ul" | "ol" | "dl");
+            let children = convert(vt, &elem.body(styles), styles, lists)?;
+            let attrs = elem
+                .attrs(styles)
+                .into_iter()
+                .map(|(name, value)| {
+                    (name.into(), value.cast::<EcoString>().unwrap_or_default())
+                })
+                .collect();
+            self.push(HtmlNode::Element(HtmlElement { tag, attrs, children }));
+        } else if let Some(rule) = content
+            .with::<dyn Html>()
+            .filter(|_| !content.is_guarded(Guard::Base(content.func())))
+        {
+            let realized = rule.html(vt, styles)?;
+            if content.is::<ListItem>()
+                || content.is::<EnumItem>()
+                || content.is::<TermItem>()
+            {
+                let nodes = convert(vt, &realized, styles, false)?;
+                self.pieces.push(Piece::Item(content.func(), nodes));
+            } else {
+                self.accept(vt, &realized, styles)?;
+            }
+        } else if let Some(image) = web_image(vt, content, styles)? {
+            self.push(HtmlNode::Image(image));
+        } else if let Some(page) = content.to::<PageElem>() {
+            self.accept(vt, &page.body(), styles)?;
+        } else if let Some(realized) = realize(vt, content, styles)? {
+            self.accept(vt, &realized, styles)?;
+        } else if content.can::<dyn Layout>() {
+            // Lay out with the width of the text area under default margins.
+            let width = PageElem::width_in(styles).unwrap_or(Paper::A4.width());
+            let size = Size::new(width * (1.0 - 2.0 * 0.1190), Abs::inf());
+            let regions = Regions::one(size, Axes::splat(false));
+            let frame = content.layout(vt, styles, regions)?.into_frame();
+            self.push(HtmlNode::Frame(frame));
+        }
+
+        Ok(())
+    }
+
+    fn styled(
+        &mut self,
+        vt: &mut Vt,
+        elem: &Content,
+        local: &Styles,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let data = MetaElem::data_in(StyleChain::new(local));
+        if data.contains(&Meta::Hide) {
+            return Ok(());
+        }
+
+        let styles = styles.chain(local);
+        let link = data.into_iter().find_map(|meta| match meta {
+            Meta::Link(dest) => href(vt, dest),
+            _ => None,
+        });
+
+        if let Some(link) = link {
+            let children = convert(vt, elem, styles, true)?;
+            self.push(HtmlNode::Element(
+                HtmlElement::new("a").with_attr("href", link).with_children(children),
+            ));
+            return Ok(());
+        }
+
+        self.accept(vt, elem, styles)
+    }
+
+    fn push(&mut self, node: HtmlNode) {
+        self.pieces.push(Piece::Node(node));
+    }
+
+    /// Group the pieces into lists and paragraphs.
+    ///
+    /// Inline content is wrapped in paragraphs if `top` is true or the
+    /// sequence contains paragraph breaks or blocks. List items are grouped
+    /// into lists if `lists` is true.
+    fn finish(self, top: bool, lists: bool) -> Vec<HtmlNode> {
+        let mut pieces = vec![];
+        let mut list: Option<(ElemFunc, Vec<HtmlNode>)> = None;
+        let mut staged = vec![];
+
+        for piece in self.pieces {
+            match piece {
+                Piece::Item(func, nodes) if lists => {
+                    if let Some((kind, items)) = &mut list {
+                        if *kind == func {
+                            // Spaces and breaks between items belong to the list.
+                            staged.clear();
+                            items.extend(nodes);
+                            continue;
+                        }
+                    }
+
+                    pieces.extend(list.take().map(finish_list));
+                    pieces.append(&mut staged);
+                    list = Some((func, nodes));
+                }
+                Piece::Item(_, nodes) => {
+                    pieces.extend(nodes.into_iter().map(Piece::Node));
+                }
+                Piece::Space | Piece::Parbreak if list.is_some() => staged.push(piece),
+                piece => {
+                    pieces.extend(list.take().map(finish_list));
+                    pieces.append(&mut staged);
+                    pieces.push(piece);
+                }
+            }
+        }
+
+        pieces.extend(list.take().map(finish_list));
+        pieces.append(&mut staged);
+
+        let wrap = top
+            || pieces.iter().any(|piece| match piece {
+                Piece::Node(HtmlNode::Element(elem)) => elem.is_block(),
+                Piece::Parbreak => true,
+                _ => false,
+            });
+
+        let mut nodes = vec![];
+        let mut par = ParCollector::default();
+        for piece in pieces {
+            match piece {
+                Piece::Node(HtmlNode::Element(elem)) if elem.is_block() => {
+                    par.finish(&mut nodes, wrap);
+                    nodes.push(HtmlNode::Element(elem));
+                }
+                Piece::Node(node) => par.push(node),
+                Piece::Space => par.space = !par.nodes.is_empty(),
+                Piece::Parbreak | Piece::Item(..) => par.finish(&mut nodes, wrap),
+            }
+        }
+
+        par.finish(&mut nodes, wrap);
+        nodes
+    }
+}
+
+/// Collects the inline nodes of a paragraph.
+#[derive(Default)]
+struct ParCollector {
+    /// The nodes collected so far.
+    nodes: Vec<HtmlNode>,
+    /// Whether a space is due before the next node.
+    space: bool,
+}
+
+impl ParCollector {
+    fn push(&mut self, node: HtmlNode) {
+        let space = std::mem::take(&mut self.space);
+        if let HtmlNode::Text(text) = &node {
+            if let Some(HtmlNode::Text(prev)) = self.nodes.last_mut() {
+                if space {
+                    prev.push(' ');
+                }
+                prev.push_str(text);
+                return;
+            }
+        }
+
+        if space {
+            self.nodes.push(HtmlNode::Text(' '.into()));
+        }
+
+        self.nodes.push(node);
+    }
+
+    /// Write the collected nodes into `out`, wrapped in a paragraph if `wrap`
+    /// is true.
+    fn finish(&mut self, out: &mut Vec<HtmlNode>, wrap: bool) {
+        self.space = false;
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let nodes = std::mem::take(&mut self.nodes);
+        if wrap {
+            out.push(HtmlNode::Element(HtmlElement::new("p").with_children(nodes)));
+        } else {
+            out.extend(nodes);
+        }
+    }
+}
+
+/// Convert content into the children of an HTML element.
+fn convert(
+    vt: &mut Vt,
+    content: &Content,
+    styles: StyleChain,
+    lists: bool,
+) -> SourceResult<Vec<HtmlNode>> {
+    let mut builder = HtmlBuilder::default();
+    builder.accept(vt, content, styles)?;
+    Ok(builder.finish(false, lists))
+}
+
+/// Wrap the items of a list in the list element that fits their kind.
+fn finish_list((func, items): (ElemFunc, Vec<HtmlNode>)) -> Piece {
+    let tag = if func == ListItem::func() {
+        "ul"
+    } else if func == EnumItem::func() {
+        "ol"
+    } else {
+        "dl"
+    };
+
+    Piece::Node(HtmlNode::Element(HtmlElement::new(tag).with_children(items)))
+}
+
+/// The image behind an image element if browsers can display it as is.
+///
+/// Other images, like PDF pages, are laid out and embedded as frames.
+fn web_image(
+    vt: &Vt,
+    content: &Content,
+    styles: StyleChain,
+) -> SourceResult<Option<Image>> {
+    let Some(elem) = content.to::<ImageElem>() else { return Ok(None) };
+    let image = elem.image(vt, styles)?;
+
+    // Compressed SVGs start with the magic bytes of gzip.
+    let supported = image.format() != ImageFormat::Vector(VectorFormat::Pdf)
+        && !image.data().starts_with(&[0x1f, 0x8b]);
+
+    Ok(supported.then_some(image))
+}
+
+/// The target of a link in HTML, if it can be represented.
+fn href(vt: &Vt, dest: Destination) -> Option<EcoString> {
+    match dest {
+        Destination::Url(url) => Some(url),
+        Destination::Position(_) => None,
+        Destination::Location(location) => {
+            let elem = vt.introspector.location(&location)?;
+            anchor(&elem).map(|anchor| eco_format!("#{anchor}"))
+        }
+    }
+}
@@ -1,4 +1,7 @@
+use typst::model::Guard;
+
 use super::VElem;
+use crate::html::{Html, HtmlElem};
 use crate::layout::Spacing;
 use crate::prelude::*;
 
@@ -21,7 +24,7 @@ use crate::prelude::*;
 ///
 /// Display: Box
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Html)]
 pub struct BoxElem {
     /// The width of the box.
     ///
@@ -98,6 +101,18 @@ pub struct BoxElem {
     pub body: Option<Content>,
 }
 
+impl Html for BoxElem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        // Boxes with a fixed size keep their appearance as a frame.
+        let sized = matches!(self.width(styles), Sizing::Rel(_))
+            || self.height(styles).is_custom();
+        Ok(match self.body(styles) {
+            Some(body) if !sized => HtmlElem::new("span".into()).with_body(body).pack(),
+            _ => self.clone().pack().guarded(Guard::Base(Self::func())),
+        })
+    }
+}
+
 impl Layout for BoxElem {
     fn layout(
         &self,
@@ -196,7 +211,7 @@ impl Layout for BoxElem {
 ///
 /// Display: Block
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Html)]
 pub struct BlockElem {
     /// The block's width.
     ///
@@ -325,6 +340,15 @@ pub struct BlockElem {
     pub sticky: bool,
 }
 
+impl Html for BlockElem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        Ok(match self.body(styles) {
+            Some(body) => HtmlElem::new("div".into()).with_body(body).pack(),
+            None => self.clone().pack().guarded(Guard::Base(Self::func())),
+        })
+    }
+}
+
 impl Layout for BlockElem {
     fn layout(
         &self,
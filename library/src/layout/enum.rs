@@ -1,11 +1,12 @@
 use std::str::FromStr;
 
+use crate::html::{Html, HtmlElem};
 use crate::layout::{BlockElem, ParElem, Sizing, Spacing};
 use crate::meta::{Numbering, NumberingPattern};
 use crate::prelude::*;
 use crate::text::TextElem;
 
-use super::GridLayouter;
+use super::{mark_structure, GridLayouter};
 
 /// A numbered list.
 ///
@@ -50,7 +51,7 @@ use super::GridLayouter;
 ///
 /// Display: Numbered List
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Structural, Html)]
 pub struct EnumElem {
     /// If this is `{false}`, the items are spaced apart with
     /// [enum spacing]($func/enum.spacing). If it is `{true}`, they use normal
@@ -153,6 +154,28 @@ pub struct EnumElem {
     parents: Parent,
 }
 
+impl Structural for EnumElem {
+    fn role(&self) -> Role {
+        Role::List
+    }
+}
+
+impl Html for EnumElem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let mut attrs = Dict::new();
+        let start = self.start(styles);
+        if start != 1 {
+            attrs.insert("start".into(), eco_format!("{start}").into());
+        }
+
+        let items = self.children().into_iter().map(|item| item.pack());
+        Ok(HtmlElem::new("ol".into())
+            .with_attrs(attrs)
+            .with_body(Content::sequence(items))
+            .pack())
+    }
+}
+
 impl Layout for EnumElem {
     fn layout(
         &self,
@@ -160,6 +183,8 @@ impl Layout for EnumElem {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment> {
+        let mark = mark_structure(vt, self.clone().pack());
+        let styles = styles.chain(&mark);
         let numbering = self.numbering(styles);
         let indent = self.indent(styles);
         let body_indent = self.body_indent(styles);
@@ -195,7 +220,11 @@ impl Layout for EnumElem {
             cells.push(Content::empty());
             cells.push(resolved);
             cells.push(Content::empty());
-            cells.push(item.body().styled(Self::set_parents(Parent(number))));
+            cells.push(
+                item.body()
+                    .styled(Self::set_parents(Parent(number)))
+                    .styled_with_map(mark_structure(vt, item.clone().pack())),
+            );
             number = number.saturating_add(1);
         }
 
@@ -221,7 +250,7 @@ impl Layout for EnumElem {
 ///
 /// Display: Numbered List Item
 /// Category: layout
-#[element]
+#[element(Structural, Html)]
 pub struct EnumItem {
     /// The item's number.
     #[positional]
@@ -232,6 +261,26 @@ pub struct EnumItem {
     pub body: Content,
 }
 
+impl Structural for EnumItem {
+    fn role(&self) -> Role {
+        Role::ListItem
+    }
+}
+
+impl Html for EnumItem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let mut attrs = Dict::new();
+        if let Some(number) = self.number(styles) {
+            attrs.insert("value".into(), eco_format!("{number}").into());
+        }
+
+        Ok(HtmlElem::new("li".into())
+            .with_attrs(attrs)
+            .with_body(self.body())
+            .pack())
+    }
+}
+
 cast_from_value! {
     EnumItem,
     array: Array => {
@@ -1,4 +1,7 @@
-use super::{AlignElem, BlockElem, ColbreakElem, ParElem, PlaceElem, Spacing, VElem};
+use super::{
+    mark_structure, AlignElem, BlockElem, ColbreakElem, ParElem, PlaceElem, Spacing,
+    VElem,
+};
 use crate::prelude::*;
 use crate::visualize::{
     CircleElem, EllipseElem, ImageElem, PathElem, PolygonElem, RectElem, SquareElem,
@@ -142,6 +145,8 @@ impl<'a> FlowLayouter<'a> {
         let aligns = AlignElem::alignment_in(styles).resolve(styles);
         let leading = ParElem::leading_in(styles);
         let consecutive = self.last_was_par;
+        let mark = mark_structure(vt, par.clone().pack());
+        let styles = styles.chain(&mark);
         let frames = par
             .layout(vt, styles, consecutive, self.regions.base(), self.regions.expand.x)?
             .into_frames();
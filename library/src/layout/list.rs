@@ -1,8 +1,9 @@
+use crate::html::{Html, HtmlElem};
 use crate::layout::{BlockElem, ParElem, Sizing, Spacing};
 use crate::prelude::*;
 use crate::text::TextElem;
 
-use super::GridLayouter;
+use super::{mark_structure, GridLayouter};
 
 /// A bullet list.
 ///
@@ -36,7 +37,7 @@ use super::GridLayouter;
 ///
 /// Display: Bullet List
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Structural, Html)]
 pub struct ListElem {
     /// If this is `{false}`, the items are spaced apart with [list
     /// spacing]($func/list.spacing). If it is `{true}`, they use normal
@@ -111,6 +112,19 @@ pub struct ListElem {
     depth: Depth,
 }
 
+impl Structural for ListElem {
+    fn role(&self) -> Role {
+        Role::List
+    }
+}
+
+impl Html for ListElem {
+    fn html(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        let items = self.children().into_iter().map(|item| item.pack());
+        Ok(HtmlElem::new("ul".into()).with_body(Content::sequence(items)).pack())
+    }
+}
+
 impl Layout for ListElem {
     fn layout(
         &self,
@@ -118,6 +132,8 @@ impl Layout for ListElem {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment> {
+        let mark = mark_structure(vt, self.clone().pack());
+        let styles = styles.chain(&mark);
         let indent = self.indent(styles);
         let body_indent = self.body_indent(styles);
         let gutter = if self.tight(styles) {
@@ -135,7 +151,11 @@ impl Layout for ListElem {
             cells.push(Content::empty());
             cells.push(marker.clone());
             cells.push(Content::empty());
-            cells.push(item.body().styled(Self::set_depth(Depth)));
+            cells.push(
+                item.body()
+                    .styled(Self::set_depth(Depth))
+                    .styled_with_map(mark_structure(vt, item.clone().pack())),
+            );
         }
 
         let layouter = GridLayouter::new(
@@ -160,13 +180,25 @@ impl Layout for ListElem {
 ///
 /// Display: Bullet List Item
 /// Category: layout
-#[element]
+#[element(Structural, Html)]
 pub struct ListItem {
     /// The item's body.
     #[required]
     pub body: Content,
 }
 
+impl Structural for ListItem {
+    fn role(&self) -> Role {
+        Role::ListItem
+    }
+}
+
+impl Html for ListItem {
+    fn html(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(HtmlElem::new("li".into()).with_body(self.body()).pack())
+    }
+}
+
 cast_from_value! {
     ListItem,
     v: Content => v.to::<Self>().cloned().unwrap_or_else(|| Self::new(v.clone())),
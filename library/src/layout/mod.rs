@@ -51,6 +51,7 @@ use typed_arena::Arena;
 use typst::diag::SourceResult;
 use typst::eval::Tracer;
 use typst::model::{applicable, realize, StyleVecBuilder};
+use typst::util::hash128;
 
 use crate::math::{EquationElem, LayoutMath};
 use crate::meta::DocumentElem;
@@ -163,6 +164,19 @@ impl Layout for Content {
     }
 }
 
+/// Mark everything laid out with the resulting style as produced by the given
+/// element, so that exporters can recover the logical structure of the
+/// document.
+///
+/// This is for elements like paragraphs and lists, which are laid out directly
+/// instead of being realized and thus don't get a location otherwise. The
+/// location only tells apart the parts of the structure, so the marked
+/// elements don't show up in queries.
+fn mark_structure(vt: &mut Vt, mut elem: Content) -> Styles {
+    elem.set_location(vt.provider.locate(hash128(&elem)));
+    MetaElem::set_data(vec![Meta::Structure(elem)]).into()
+}
+
 /// Realize into an element that is capable of root-level layout.
 fn realize_root<'a>(
     vt: &mut Vt,
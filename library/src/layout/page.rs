@@ -153,6 +153,10 @@ pub struct PageElem {
     ///
     /// If an explicit `footer` is given, the numbering is ignored.
     ///
+    /// In PDF export, the numbering also labels the pages in the page
+    /// navigation of PDF viewers. This way, front matter numbered with `{"i"}`
+    /// shows up as "iii" instead of "3".
+    ///
     /// ```example
     /// #set page(
     ///   height: 100pt,
@@ -329,9 +333,20 @@ impl PageElem {
             Size::zero(),
         );
 
+        // Label the pages for the page navigation of viewers.
+        let label = self
+            .numbering(styles)
+            .map(|numbering| PageLabelElem::new(numbering).pack());
+
         // Realize overlays.
         for frame in &mut fragment {
             frame.prepend(Point::zero(), numbering_meta.clone());
+            if let Some(label) = &label {
+                let pod = Regions::one(Size::zero(), Axes::splat(false));
+                let sub = label.layout(vt, styles, pod)?.into_frame();
+                frame.push_frame(Point::zero(), sub);
+            }
+
             let size = frame.size();
             let pad = padding.resolve(styles).relative_to(size);
             let pw = size.x - pad.left - pad.right;
@@ -377,6 +392,42 @@ impl PageElem {
     }
 }
 
+/// Labels the page it ends up on with the current value of the page counter.
+///
+/// Display: Page Label
+/// Category: special
+#[element(Locatable, Show)]
+struct PageLabelElem {
+    /// The numbering of the page.
+    #[required]
+    numbering: Numbering,
+}
+
+impl Show for PageLabelElem {
+    fn show(&self, vt: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        if !vt.introspector.init() {
+            return Ok(Content::empty());
+        }
+
+        let location = self.0.location().unwrap();
+        let number = Counter::new(CounterKey::Page).at(vt, location)?.first();
+        let label = match self.numbering() {
+            Numbering::Pattern(pattern) => pattern.page_label(number),
+            numbering => match numbering.apply_vt(vt, &[number])? {
+                Value::Str(text) => PageLabel {
+                    style: None,
+                    prefix: text.into(),
+                    number: NonZeroUsize::ONE,
+                },
+                _ => return Ok(Content::empty()),
+            },
+        };
+
+        let meta = Meta::PageLabel(label);
+        Ok(MetaElem::new().pack().styled(MetaElem::set_data(vec![meta])))
+    }
+}
+
 /// A manual page break.
 ///
 /// Must not be used inside any containers.
@@ -36,7 +36,7 @@ use crate::text::{
 ///
 /// Display: Paragraph
 /// Category: layout
-#[element(Construct)]
+#[element(Construct, Structural)]
 pub struct ParElem {
     /// The spacing between lines.
     ///
@@ -110,11 +110,11 @@ pub struct ParElem {
 }
 
 impl Construct for ParElem {
-    fn construct(_: &mut Vm, args: &mut Args) -> SourceResult<Content> {
+    fn construct(vm: &mut Vm, args: &mut Args) -> SourceResult<Content> {
         // The paragraph constructor is special: It doesn't create a paragraph
         // element. Instead, it just ensures that the passed content lives in a
         // separate paragraph and styles it.
-        let styles = Self::set(args)?;
+        let styles = Self::set(vm, args)?;
         let body = args.expect::<Content>("body")?;
         Ok(Content::sequence([
             ParbreakElem::new().pack(),
@@ -124,6 +124,12 @@ impl Construct for ParElem {
     }
 }
 
+impl Structural for ParElem {
+    fn role(&self) -> Role {
+        Role::Paragraph
+    }
+}
+
 impl ParElem {
     /// Layout the paragraph into a collection of lines.
     pub fn layout(
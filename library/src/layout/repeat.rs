@@ -1,3 +1,4 @@
+use crate::html::Html;
 use crate::prelude::*;
 
 use super::AlignElem;
@@ -26,13 +27,20 @@ use super::AlignElem;
 ///
 /// Display: Repeat
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Html)]
 pub struct RepeatElem {
     /// The content to repeat.
     #[required]
     pub body: Content,
 }
 
+impl Html for RepeatElem {
+    fn html(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        // Without a fixed width, there is no space to fill.
+        Ok(Content::empty())
+    }
+}
+
 impl Layout for RepeatElem {
     fn layout(
         &self,
@@ -1,4 +1,5 @@
-use crate::layout::{AlignElem, GridLayouter, TrackSizings};
+use crate::html::{anchor_attrs, Html, HtmlElem};
+use crate::layout::{mark_structure, AlignElem, GridLayouter, TrackSizings};
 use crate::meta::{Figurable, LocalName};
 use crate::prelude::*;
 
@@ -35,7 +36,7 @@ use crate::prelude::*;
 ///
 /// Display: Table
 /// Category: layout
-#[element(Layout, LocalName, Figurable)]
+#[element(Layout, LocalName, Figurable, Structural, Html)]
 pub struct TableElem {
     /// Defines the column sizes. See the [grid documentation]($func/grid) for
     /// more information on track sizing.
@@ -121,6 +122,24 @@ pub struct TableElem {
     pub children: Vec<Content>,
 }
 
+impl Html for TableElem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let cols = self.columns(styles).0.len().max(1);
+        let children = self.children();
+        let rows = children.chunks(cols).map(|row| {
+            let cells = row
+                .iter()
+                .map(|cell| HtmlElem::new("td".into()).with_body(cell.clone()).pack());
+            HtmlElem::new("tr".into()).with_body(Content::sequence(cells)).pack()
+        });
+
+        Ok(HtmlElem::new("table".into())
+            .with_attrs(anchor_attrs(&self.0))
+            .with_body(Content::sequence(rows))
+            .pack())
+    }
+}
+
 impl Layout for TableElem {
     fn layout(
         &self,
@@ -128,6 +147,8 @@ impl Layout for TableElem {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment> {
+        let mark = mark_structure(vt, self.clone().pack());
+        let styles = styles.chain(&mark);
         let inset = self.inset(styles);
         let align = self.align(styles);
 
@@ -147,7 +168,7 @@ impl Layout for TableElem {
                     child = child.styled(AlignElem::set_alignment(alignment));
                 }
 
-                Ok(child)
+                Ok(child.styled(MetaElem::set_data(vec![Meta::Cell(x, y)])))
             })
             .collect::<SourceResult<_>>()?;
 
@@ -196,6 +217,7 @@ impl Layout for TableElem {
             }
 
             // Render cell backgrounds.
+            let mut filled = false;
             let mut dx = Abs::zero();
             for (x, &col) in layout.cols.iter().enumerate() {
                 let mut dy = Abs::zero();
@@ -205,11 +227,19 @@ impl Layout for TableElem {
                         let size = Size::new(col, row.height);
                         let rect = Geometry::Rect(size).filled(fill);
                         frame.prepend(pos, FrameItem::Shape(rect, self.span()));
+                        filled = true;
                     }
                     dy += row.height;
                 }
                 dx += col;
             }
+
+            // The lines and backgrounds precede the cells and are decoration
+            // rather than part of the table's structure.
+            if stroke.is_some() || filled {
+                let size = frame.size();
+                frame.prepend(Point::zero(), FrameItem::Meta(Meta::Artifact, size));
+            }
         }
 
         Ok(layout.fragment)
@@ -283,6 +313,12 @@ impl<T: Into<Value>> From<Celled<T>> for Value {
     }
 }
 
+impl Structural for TableElem {
+    fn role(&self) -> Role {
+        Role::Table
+    }
+}
+
 impl LocalName for TableElem {
     fn local_name(&self, lang: Lang) -> &'static str {
         match lang {
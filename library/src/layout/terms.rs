@@ -1,4 +1,5 @@
 use super::{HElem, VElem};
+use crate::html::{Html, HtmlElem};
 use crate::layout::{BlockElem, ParElem, Spacing};
 use crate::prelude::*;
 
@@ -21,7 +22,7 @@ use crate::prelude::*;
 ///
 /// Display: Term List
 /// Category: layout
-#[element(Layout)]
+#[element(Layout, Html)]
 pub struct TermsElem {
     /// If this is `{false}`, the items are spaced apart with [term list
     /// spacing]($func/terms.spacing). If it is `{true}`, they use normal
@@ -91,6 +92,13 @@ pub struct TermsElem {
     pub children: Vec<TermItem>,
 }
 
+impl Html for TermsElem {
+    fn html(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        let items = self.children().into_iter().map(|item| item.pack());
+        Ok(HtmlElem::new("dl".into()).with_body(Content::sequence(items)).pack())
+    }
+}
+
 impl Layout for TermsElem {
     fn layout(
         &self,
@@ -131,7 +139,7 @@ impl Layout for TermsElem {
 ///
 /// Display: Term List Item
 /// Category: layout
-#[element]
+#[element(Html)]
 pub struct TermItem {
     /// The term described by the list item.
     #[required]
@@ -142,6 +150,13 @@ pub struct TermItem {
     pub description: Content,
 }
 
+impl Html for TermItem {
+    fn html(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(HtmlElem::new("dt".into()).with_body(self.term()).pack()
+            + HtmlElem::new("dd".into()).with_body(self.description()).pack())
+    }
+}
+
 cast_from_value! {
     TermItem,
     array: Array => {
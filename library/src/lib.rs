@@ -2,6 +2,7 @@
 //! Typst's standard library.
 
 pub mod compute;
+pub mod html;
 pub mod layout;
 pub mod math;
 pub mod meta;
@@ -12,7 +13,7 @@ pub mod text;
 pub mod visualize;
 
 use typst::diag::At;
-use typst::eval::{LangItems, Library, Module, Scope};
+use typst::eval::{Dict, LangItems, Library, Module, Scope};
 use typst::geom::{Align, Color, Dir, GenAlign, Smart};
 use typst::model::{Element, Styles};
 
@@ -20,14 +21,23 @@ use self::layout::LayoutRoot;
 
 /// Construct the standard library.
 pub fn build() -> Library {
+    build_with_inputs(Dict::new())
+}
+
+/// Construct the standard library with inputs that are accessible to
+/// documents through the `sys.inputs` dictionary.
+///
+/// This lets embedders parameterize a document from the outside.
+pub fn build_with_inputs(inputs: Dict) -> Library {
     let math = math::module();
     let calc = compute::calc::module();
-    let global = global(math.clone(), calc);
+    let sys = compute::sys::module(inputs);
+    let global = global(math.clone(), calc, sys);
     Library { global, math, styles: styles(), items: items() }
 }
 
 /// Construct the module with global definitions.
-fn global(math: Module, calc: Module) -> Module {
+fn global(math: Module, calc: Module, sys: Module) -> Module {
     let mut global = Scope::deduplicating();
 
     // Text.
@@ -86,6 +96,8 @@ fn global(math: Module, calc: Module) -> Module {
     global.define("circle", visualize::CircleElem::func());
     global.define("polygon", visualize::PolygonElem::func());
     global.define("path", visualize::PathElem::func());
+    global.define("gradient", visualize::gradient::module());
+    global.define("pattern", visualize::pattern);
 
     // Meta.
     global.define("document", meta::DocumentElem::func());
@@ -104,6 +116,9 @@ fn global(math: Module, calc: Module) -> Module {
     global.define("state", meta::state);
     global.define("query", meta::query);
     global.define("selector", meta::selector);
+    global.define("metadata", meta::MetadataElem::func());
+    global.define("pdf", meta::pdf::module());
+    global.define("form", meta::form::module());
 
     // Symbols.
     global.define("sym", symbols::sym());
@@ -134,6 +149,9 @@ fn global(math: Module, calc: Module) -> Module {
     // Calc.
     global.define("calc", calc);
 
+    // System.
+    global.define("sys", sys);
+
     // Colors.
     global.define("black", Color::BLACK);
     global.define("gray", Color::GRAY);
@@ -180,6 +198,7 @@ fn styles() -> Styles {
 fn items() -> LangItems {
     LangItems {
         layout: |world, content, styles| content.layout_root(world, styles),
+        html: html::html,
         em: text::TextElem::size_in,
         dir: text::TextElem::dir_in,
         space: || text::SpaceElem::new().pack(),
@@ -211,6 +230,7 @@ fn items() -> LangItems {
         },
         bibliography_keys: meta::BibliographyElem::keys,
         heading: |level, title| meta::HeadingElem::new(title).with_level(level).pack(),
+        heading_func: meta::HeadingElem::func(),
         list_item: |body| layout::ListItem::new(body).pack(),
         enum_item: |number, body| {
             let mut elem = layout::EnumItem::new(body);
@@ -38,6 +38,7 @@ use self::ctx::*;
 use self::fragment::*;
 use self::row::*;
 use self::spacing::*;
+use crate::html::{Html, HtmlElem};
 use crate::layout::{HElem, ParElem, Spacing};
 use crate::meta::Refable;
 use crate::meta::{Count, Counter, CounterUpdate, LocalName, Numbering};
@@ -136,7 +137,8 @@ pub fn module() -> Module {
 /// Display: Equation
 /// Category: math
 #[element(
-    Locatable, Synthesize, Show, Finalize, Layout, LayoutMath, Count, LocalName, Refable
+    Locatable, Synthesize, Show, Finalize, Layout, LayoutMath, Count, LocalName, Refable,
+    Html
 )]
 pub struct EquationElem {
     /// Whether the equation is displayed as a separate block.
@@ -189,6 +191,23 @@ impl Finalize for EquationElem {
     }
 }
 
+impl Html for EquationElem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        // Equations have no HTML equivalent and are embedded as frames.
+        let frame = self.clone().pack().guarded(Guard::Base(Self::func()));
+        let mut realized = self.finalize(frame, styles);
+        if self.block(styles) {
+            let mut attrs = Dict::new();
+            attrs.insert("class".into(), "equation".into());
+            realized = HtmlElem::new("div".into())
+                .with_attrs(attrs)
+                .with_body(realized)
+                .pack();
+        }
+        Ok(realized)
+    }
+}
+
 impl Layout for EquationElem {
     fn layout(
         &self,
@@ -1,5 +1,6 @@
 use crate::layout::{LayoutRoot, PageElem};
 use crate::prelude::*;
+use crate::text::TextElem;
 
 /// The root element of a document and its metadata.
 ///
@@ -29,6 +30,36 @@ pub struct DocumentElem {
     /// The document's authors.
     pub author: Author,
 
+    /// A short description of the document's contents. PDF viewers show it
+    /// as the document's subject.
+    pub description: Option<EcoString>,
+
+    /// The document's keywords. Search engines and document management
+    /// systems use them to index the document.
+    pub keywords: Keywords,
+
+    /// The document's creation date, in the form `{"YYYY-MM-DD"}`.
+    ///
+    /// When `{auto}`, the date of the export is used if one is given to the
+    /// compiler, for instance through the `SOURCE_DATE_EPOCH` environment
+    /// variable. When `{none}`, the document has no date.
+    pub date: Smart<Option<Date>>,
+
+    /// Custom metadata properties.
+    ///
+    /// The keys of the dictionary are the names of the properties and must
+    /// only consist of ASCII letters, digits, hyphens and underscores. Names
+    /// that PDF already defines, like `Title`, are not allowed. The values
+    /// must be strings.
+    ///
+    /// ```example
+    /// #set document(properties: (
+    ///   department: "Research",
+    ///   revision: "3",
+    /// ))
+    /// ```
+    pub properties: Properties,
+
     /// The page runs.
     #[internal]
     #[variadic]
@@ -66,6 +97,14 @@ impl LayoutRoot for DocumentElem {
             pages,
             title: self.title(styles),
             author: self.author(styles).0,
+            description: self.description(styles),
+            keywords: self.keywords(styles).0,
+            date: self.date(styles),
+            // Without an explicitly set language, exporters fall back to the
+            // most common language of the text.
+            lang: styles.properties(TextElem::func(), "lang", None).next(),
+            region: TextElem::region_in(styles),
+            properties: self.properties(styles).0,
         })
     }
 }
@@ -83,3 +122,60 @@ cast_from_value! {
 cast_to_value! {
     v: Author => v.0.into()
 }
+
+/// A list of keywords.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct Keywords(Vec<EcoString>);
+
+cast_from_value! {
+    Keywords,
+    v: EcoString => Self(vec![v]),
+    v: Array => Self(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
+}
+
+cast_to_value! {
+    v: Keywords => v.0.into()
+}
+
+/// Custom metadata properties.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct Properties(Vec<(EcoString, EcoString)>);
+
+/// Property names that the PDF document information already defines.
+const RESERVED: &[&str] = &[
+    "Title",
+    "Author",
+    "Subject",
+    "Keywords",
+    "Creator",
+    "Producer",
+    "CreationDate",
+    "ModDate",
+    "Trapped",
+];
+
+cast_from_value! {
+    Properties,
+    v: Dict => Self(v.into_iter().map(property).collect::<StrResult<_>>()?),
+}
+
+cast_to_value! {
+    v: Properties => Value::Dict(
+        v.0.into_iter().map(|(key, value)| (key.into(), value.into())).collect()
+    )
+}
+
+/// Check the name of a custom property and cast its value.
+fn property((key, value): (Str, Value)) -> StrResult<(EcoString, EcoString)> {
+    let valid = key.as_str().starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(eco_format!("invalid property name: {}", key));
+    }
+
+    if RESERVED.contains(&key.as_str()) {
+        return Err(eco_format!("property name is reserved: {}", key));
+    }
+
+    Ok((key.into(), value.cast()?))
+}
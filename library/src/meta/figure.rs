@@ -3,6 +3,7 @@ use std::str::FromStr;
 use super::{
     Count, Counter, CounterKey, CounterUpdate, LocalName, Numbering, NumberingPattern,
 };
+use crate::html::{anchor_attrs, Html, HtmlElem};
 use crate::layout::{BlockElem, VElem};
 use crate::meta::{Refable, Supplement};
 use crate::prelude::*;
@@ -72,7 +73,7 @@ use crate::visualize::ImageElem;
 ///
 /// Display: Figure
 /// Category: meta
-#[element(Locatable, Synthesize, Count, Show, Refable)]
+#[element(Locatable, Synthesize, Count, Show, Refable, Structural, Html)]
 pub struct FigureElem {
     /// The content of the figure. Often, an [image]($func/image).
     #[required]
@@ -81,6 +82,21 @@ pub struct FigureElem {
     /// The figure's caption.
     pub caption: Option<Content>,
 
+    /// A textual description of the figure for readers who can't see it, for
+    /// example because they use a screen reader.
+    ///
+    /// Tagged PDF files carry this description along with the figure. If it
+    /// is `{none}`, the text of the caption is used instead.
+    ///
+    /// ```example
+    /// #figure(
+    ///   image("molecular.jpg", width: 80%),
+    ///   caption: [A step in the molecular testing pipeline.],
+    ///   alt: "A pipette drips liquid into one of many small vials.",
+    /// )
+    /// ```
+    pub alt: Option<EcoString>,
+
     /// The kind of the figure this is.
     ///
     /// If set to `{auto}`, the figure will try to automatically determine its
@@ -224,6 +240,7 @@ impl Synthesize for FigureElem {
         )));
 
         self.push_caption(self.caption(styles));
+        self.push_alt(self.alt(styles));
         self.push_kind(Smart::Custom(kind));
         self.push_supplement(Smart::Custom(Supplement::Content(
             supplement.unwrap_or_default(),
@@ -256,6 +273,29 @@ impl Show for FigureElem {
     }
 }
 
+impl Html for FigureElem {
+    fn html(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let mut attrs = anchor_attrs(&self.0);
+        if let Some(alt) = self.alt(styles) {
+            // Describe the figure as a whole to assistive technology.
+            attrs.insert("role".into(), "img".into());
+            attrs.insert("aria-label".into(), alt.into());
+        }
+
+        let mut body = self.body();
+        if self.caption(styles).is_some() {
+            body += HtmlElem::new("figcaption".into())
+                .with_body(self.show_caption(vt)?)
+                .pack();
+        }
+
+        Ok(HtmlElem::new("figure".into())
+            .with_attrs(attrs)
+            .with_body(body)
+            .pack())
+    }
+}
+
 impl Count for FigureElem {
     fn update(&self) -> Option<CounterUpdate> {
         // If the figure is numbered, step the counter by one.
@@ -266,6 +306,16 @@ impl Count for FigureElem {
     }
 }
 
+impl Structural for FigureElem {
+    fn role(&self) -> Role {
+        let alt = self.alt(StyleChain::default()).or_else(|| {
+            self.caption(StyleChain::default())
+                .map(|caption| caption.plain_text())
+        });
+        Role::Figure(alt)
+    }
+}
+
 impl Refable for FigureElem {
     fn reference(
         &self,
@@ -0,0 +1,292 @@
+use typst::eval::{Module, Scope};
+
+use crate::layout::{BoxElem, Sizing};
+use crate::prelude::*;
+
+/// A module with interactive form fields.
+///
+/// In PDF export, the fields can be filled in with a PDF viewer. Other
+/// exports only show their outlines.
+pub fn module() -> Module {
+    let mut scope = Scope::new();
+    scope.define("text-field", TextFieldElem::func());
+    scope.define("checkbox", CheckboxElem::func());
+    scope.define("radio", RadioElem::func());
+    scope.define("dropdown", DropdownElem::func());
+    Module::new("form").with_scope(scope)
+}
+
+/// A text input that can be filled in a PDF viewer.
+///
+/// Like a [box]($func/box), the field is placed inline and has a fixed size.
+///
+/// ## Example
+/// ```example
+/// Name: #form.text-field("name")
+///
+/// Address:
+/// #form.text-field(
+///   "address",
+///   multiline: true,
+///   width: 100%,
+///   height: 3em,
+/// )
+/// ```
+///
+/// Display: Text Field
+/// Category: meta
+#[element(Locatable, Show)]
+pub struct TextFieldElem {
+    /// The name that identifies the field's value when the form is
+    /// submitted. Fields with the same name share their value and must be of
+    /// the same kind.
+    #[required]
+    pub name: EcoString,
+
+    /// The text the field initially contains.
+    #[default]
+    pub value: EcoString,
+
+    /// Whether the text can span multiple lines.
+    #[default(false)]
+    pub multiline: bool,
+
+    /// The width of the field.
+    #[default(Length::from(Em::new(10.0)).into())]
+    pub width: Rel<Length>,
+
+    /// The height of the field.
+    #[default(Length::from(Em::new(1.4)).into())]
+    pub height: Rel<Length>,
+}
+
+impl Show for TextFieldElem {
+    fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        check_name(vt, self.span(), self.name(), Self::func())?;
+        let kind = FormFieldKind::Text {
+            value: self.value(styles),
+            multiline: self.multiline(styles),
+        };
+        let size = Axes::new(self.width(styles), self.height(styles));
+        Ok(field(self.name(), kind, size, false))
+    }
+}
+
+/// A checkbox that can be checked in a PDF viewer.
+///
+/// ## Example
+/// ```example
+/// #form.checkbox("terms") I accept the terms. \
+/// #form.checkbox("news", checked: true) Send me news.
+/// ```
+///
+/// Display: Checkbox
+/// Category: meta
+#[element(Locatable, Show)]
+pub struct CheckboxElem {
+    /// The name that identifies the field's value when the form is
+    /// submitted.
+    #[required]
+    pub name: EcoString,
+
+    /// Whether the checkbox is initially checked.
+    #[default(false)]
+    pub checked: bool,
+
+    /// The width and height of the checkbox.
+    #[default(Em::new(0.8).into())]
+    pub size: Length,
+}
+
+impl Show for CheckboxElem {
+    fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        check_name(vt, self.span(), self.name(), Self::func())?;
+        let kind = FormFieldKind::Checkbox { checked: self.checked(styles) };
+        let size = Axes::splat(self.size(styles).into());
+        Ok(field(self.name(), kind, size, false))
+    }
+}
+
+/// A radio button, of which only one per group can be checked.
+///
+/// All radio buttons with the same name form a group. When the form is
+/// submitted, the value of the group is that of its checked button.
+///
+/// ## Example
+/// ```example
+/// Size:
+/// #form.radio("size", "s") S
+/// #form.radio("size", "m", checked: true) M
+/// #form.radio("size", "l") L
+/// ```
+///
+/// Display: Radio Button
+/// Category: meta
+#[element(Locatable, Show)]
+pub struct RadioElem {
+    /// The name of the group the button belongs to.
+    #[required]
+    pub name: EcoString,
+
+    /// The value that the button stands for.
+    ///
+    /// It must not be `{"Off"}`, which PDF viewers reserve for unchecked
+    /// buttons.
+    #[required]
+    pub value: EcoString,
+
+    /// Whether the button is initially checked.
+    #[default(false)]
+    pub checked: bool,
+
+    /// The width and height of the button.
+    #[default(Em::new(0.8).into())]
+    pub size: Length,
+}
+
+impl Show for RadioElem {
+    fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        check_name(vt, self.span(), self.name(), Self::func())?;
+        let value = self.value();
+        if value == "Off" {
+            bail!(self.span(), "radio button value must not be \"Off\"");
+        }
+
+        let kind = FormFieldKind::Radio { value, checked: self.checked(styles) };
+        let size = Axes::splat(self.size(styles).into());
+        Ok(field(self.name(), kind, size, true))
+    }
+}
+
+/// A dropdown for picking one of multiple options in a PDF viewer.
+///
+/// ## Example
+/// ```example
+/// Team: #form.dropdown(
+///   "team",
+///   ("Design", "Engineering", "People"),
+///   selected: "People",
+/// )
+/// ```
+///
+/// Display: Dropdown
+/// Category: meta
+#[element(Locatable, Show)]
+pub struct DropdownElem {
+    /// The name that identifies the field's value when the form is
+    /// submitted.
+    #[required]
+    pub name: EcoString,
+
+    /// The options to pick from.
+    #[required]
+    pub options: Vec<EcoString>,
+
+    /// The option that is initially picked.
+    pub selected: Option<EcoString>,
+
+    /// The width of the field.
+    #[default(Length::from(Em::new(10.0)).into())]
+    pub width: Rel<Length>,
+
+    /// The height of the field.
+    #[default(Length::from(Em::new(1.4)).into())]
+    pub height: Rel<Length>,
+}
+
+impl Show for DropdownElem {
+    fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        check_name(vt, self.span(), self.name(), Self::func())?;
+        let options = self.options();
+        let selected = self.selected(styles);
+        if let Some(selected) = &selected {
+            if !options.contains(selected) {
+                bail!(self.span(), "selected value must be one of the options");
+            }
+        }
+
+        let kind = FormFieldKind::Dropdown { options, selected };
+        let size = Axes::new(self.width(styles), self.height(styles));
+        Ok(field(self.name(), kind, size, false))
+    }
+}
+
+/// Ensure that no field of another kind has the same name, as the fields
+/// would share their value.
+fn check_name(vt: &Vt, span: Span, name: EcoString, func: ElemFunc) -> SourceResult<()> {
+    let kinds = [
+        TextFieldElem::func(),
+        CheckboxElem::func(),
+        RadioElem::func(),
+        DropdownElem::func(),
+    ];
+
+    let selector = Selector::Or(
+        kinds
+            .into_iter()
+            .filter(|&kind| kind != func)
+            .map(|kind| Selector::Elem(kind, Some(dict! { "name" => name.clone() })))
+            .collect(),
+    );
+
+    if let Some(other) = vt.introspector.query(&selector).first() {
+        bail!(
+            span,
+            "form field name \"{name}\" is already used by a {}",
+            other.func().info().display.to_lowercase(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Place a form field like a box with a thin border.
+fn field(
+    name: EcoString,
+    kind: FormFieldKind,
+    size: Axes<Rel<Length>>,
+    round: bool,
+) -> Content {
+    let stroke = PartialStroke {
+        paint: Smart::Custom(Color::GRAY.into()),
+        thickness: Smart::Custom(Abs::pt(0.5).into()),
+        ..Default::default()
+    };
+
+    let mut elem = BoxElem::new()
+        .with_width(Sizing::Rel(size.x))
+        .with_height(Smart::Custom(size.y))
+        .with_stroke(Sides::splat(Some(Some(stroke))))
+        .with_body(Some(WidgetElem::new(FormField { name, kind }).pack()));
+
+    if round {
+        elem = elem.with_radius(Corners::splat(Some(Ratio::new(0.5).into())));
+    }
+
+    elem.pack()
+}
+
+/// Marks the area of a form field.
+///
+/// Display: Widget
+/// Category: special
+#[element(Layout)]
+struct WidgetElem {
+    /// The field that covers the area.
+    #[required]
+    field: FormField,
+}
+
+impl Layout for WidgetElem {
+    fn layout(
+        &self,
+        _: &mut Vt,
+        _: StyleChain,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        let size = regions.base();
+        let mut frame = Frame::new(size);
+        frame.push(Point::zero(), FrameItem::Meta(Meta::FormField(self.field()), size));
+        Ok(Fragment::frame(frame))
+    }
+}
@@ -1,6 +1,7 @@
 use typst::font::FontWeight;
 
 use super::{Counter, CounterUpdate, LocalName, Numbering, Refable};
+use crate::html::{anchor_attrs, Html, HtmlElem};
 use crate::layout::{BlockElem, HElem, VElem};
 use crate::meta::{Count, Supplement};
 use crate::prelude::*;
@@ -41,7 +42,9 @@ use crate::text::{SpaceElem, TextElem, TextSize};
 ///
 /// Display: Heading
 /// Category: meta
-#[element(Locatable, Synthesize, Count, Show, Finalize, LocalName, Refable)]
+#[element(
+    Locatable, Synthesize, Count, Show, Finalize, LocalName, Refable, Structural, Html
+)]
 pub struct HeadingElem {
     /// The logical nesting depth of the heading, starting from one.
     #[default(NonZeroUsize::ONE)]
@@ -121,6 +124,25 @@ impl Show for HeadingElem {
     }
 }
 
+impl Html for HeadingElem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let mut body = self.body();
+        if let Some(numbering) = self.numbering(styles) {
+            body = Counter::of(Self::func())
+                .display(Some(numbering), false)
+                .spanned(self.span())
+                + SpaceElem::new().pack()
+                + body;
+        }
+
+        let level = self.level(styles).get().min(6);
+        Ok(HtmlElem::new(eco_format!("h{level}"))
+            .with_attrs(anchor_attrs(&self.0))
+            .with_body(body)
+            .pack())
+    }
+}
+
 impl Finalize for HeadingElem {
     fn finalize(&self, realized: Content, styles: StyleChain) -> Content {
         let level = self.level(styles).get();
@@ -152,6 +174,12 @@ impl Count for HeadingElem {
     }
 }
 
+impl Structural for HeadingElem {
+    fn role(&self) -> Role {
+        Role::Heading(self.level(StyleChain::default()))
+    }
+}
+
 cast_from_value! {
     HeadingElem,
     v: Content => v.to::<Self>().ok_or("expected heading")?.clone(),
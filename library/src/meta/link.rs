@@ -26,7 +26,7 @@ use crate::text::{Hyphenate, TextElem};
 ///
 /// Display: Link
 /// Category: meta
-#[element(Show)]
+#[element(Show, PlainText)]
 pub struct LinkElem {
     /// The destination the link points to.
     ///
@@ -79,6 +79,12 @@ pub struct LinkElem {
     pub body: Content,
 }
 
+impl PlainText for LinkElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl LinkElem {
     /// Create a link element from a URL with its bare text.
     pub fn from_url(url: EcoString) -> Self {
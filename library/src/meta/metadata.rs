@@ -0,0 +1,35 @@
+use crate::prelude::*;
+
+/// Expose a value to the query system without producing visible content.
+///
+/// This element can be retrieved with the [`query`]($func/query) function and
+/// from the command line with `typst query`. Its purpose is to expose an
+/// arbitrary value to the introspection system. To identify a metadata value
+/// among others, you can attach a [`label`]($func/label) to it and query for
+/// that label.
+///
+/// ## Example
+/// ```example
+/// // Put metadata somewhere.
+/// #metadata("This is a note") <note>
+///
+/// // And find it from anywhere else.
+/// #locate(loc => {
+///   query(<note>, loc).first().value
+/// })
+/// ```
+///
+/// Display: Metadata
+/// Category: meta
+#[element(Locatable, Show)]
+pub struct MetadataElem {
+    /// The value to embed into the document.
+    #[required]
+    pub value: Value,
+}
+
+impl Show for MetadataElem {
+    fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(Content::empty())
+    }
+}
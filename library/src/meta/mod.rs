@@ -5,10 +5,13 @@ mod context;
 mod counter;
 mod document;
 mod figure;
+pub mod form;
 mod heading;
 mod link;
+mod metadata;
 mod numbering;
 mod outline;
+pub mod pdf;
 mod query;
 mod reference;
 mod state;
@@ -20,6 +23,7 @@ pub use self::document::*;
 pub use self::figure::*;
 pub use self::heading::*;
 pub use self::link::*;
+pub use self::metadata::*;
 pub use self::numbering::*;
 pub use self::outline::*;
 pub use self::query::*;
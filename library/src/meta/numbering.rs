@@ -196,6 +196,40 @@ impl NumberingPattern {
         fmt
     }
 
+    /// How a page with the given number is labelled in the page navigation of
+    /// viewers when the page is numbered with this pattern.
+    ///
+    /// Only the first counting symbol refers to the page itself. When viewers
+    /// can't count in its style, the page gets a fixed label instead.
+    pub fn page_label(&self, number: usize) -> PageLabel {
+        let (prefix, kind, case) = &self.pieces[0];
+        let suffix = if self.pieces.len() == 1 { self.suffix.as_str() } else { "" };
+        let style = match (kind, case) {
+            (NumberingKind::Arabic, _) => Some(PageLabelStyle::Arabic),
+            (NumberingKind::Roman, Case::Lower) => Some(PageLabelStyle::LowerRoman),
+            (NumberingKind::Roman, Case::Upper) => Some(PageLabelStyle::UpperRoman),
+            // Viewers continue with "aa", "bb", and so on after "z".
+            (NumberingKind::Letter, Case::Lower) if number <= 26 => {
+                Some(PageLabelStyle::LowerAlpha)
+            }
+            (NumberingKind::Letter, Case::Upper) if number <= 26 => {
+                Some(PageLabelStyle::UpperAlpha)
+            }
+            _ => None,
+        };
+
+        match (style, NonZeroUsize::new(number)) {
+            (Some(style), Some(number)) if suffix.is_empty() => {
+                PageLabel { style: Some(style), prefix: prefix.clone(), number }
+            }
+            _ => PageLabel {
+                style: None,
+                prefix: eco_format!("{prefix}{}{suffix}", kind.apply(number, *case)),
+                number: NonZeroUsize::ONE,
+            },
+        }
+    }
+
     /// How many counting symbols this pattern has.
     pub fn pieces(&self) -> usize {
         self.pieces.len()
@@ -495,3 +529,58 @@ fn zeroless<const N_DIGITS: usize>(
     }
     cs.into_iter().rev().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn test(pattern: &str, number: usize, style: Option<PageLabelStyle>, prefix: &str) {
+        let pattern: NumberingPattern = pattern.parse().unwrap();
+        let label = pattern.page_label(number);
+        let expected = match style {
+            Some(_) => number,
+            None => 1,
+        };
+        assert_eq!(label.style, style);
+        assert_eq!(label.prefix, prefix);
+        assert_eq!(label.number.get(), expected);
+    }
+
+    #[test]
+    fn test_page_label_styles() {
+        test("1", 5, Some(PageLabelStyle::Arabic), "");
+        test("i", 4, Some(PageLabelStyle::LowerRoman), "");
+        test("I", 4, Some(PageLabelStyle::UpperRoman), "");
+        test("a", 26, Some(PageLabelStyle::LowerAlpha), "");
+        test("A", 3, Some(PageLabelStyle::UpperAlpha), "");
+    }
+
+    #[test]
+    fn test_page_label_letters_past_z() {
+        // Viewers would continue with "aa", "bb", so later pages get fixed
+        // labels.
+        test("a", 27, None, "aa");
+        test("A", 28, None, "AB");
+    }
+
+    #[test]
+    fn test_page_label_prefix_and_suffix() {
+        test("Page 1", 7, Some(PageLabelStyle::Arabic), "Page ");
+        test("(i)", 2, None, "(ii)");
+        test("- 1 -", 3, None, "- 3 -");
+    }
+
+    #[test]
+    fn test_page_label_multiple_pieces() {
+        // Only the first counting symbol refers to the page.
+        test("1.a", 3, Some(PageLabelStyle::Arabic), "");
+        test("A.1)", 2, Some(PageLabelStyle::UpperAlpha), "");
+        test("*", 2, None, "†");
+    }
+
+    #[test]
+    fn test_page_label_zero() {
+        test("1", 0, None, "0");
+    }
+}
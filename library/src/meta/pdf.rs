@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use typst::eval::{Module, Scope};
+
+use crate::prelude::*;
+
+/// A module with functions that only affect PDF export.
+pub fn module() -> Module {
+    let mut scope = Scope::new();
+    scope.define("attach", AttachElem::func());
+    Module::new("pdf").with_scope(scope)
+}
+
+/// Attach a file to the exported PDF.
+///
+/// PDF viewers list attached files alongside the document, so that, for
+/// instance, the raw data behind a chart can travel with the report. When a
+/// body is given, it is shown in place and clicking it opens the file. Other
+/// exports ignore attachments.
+///
+/// ## Example
+/// ```example
+/// #pdf.attach(
+///   "data.csv",
+///   description: "The measurements behind the chart",
+/// )
+///
+/// The #pdf.attach("data.csv")[raw data] is attached.
+/// ```
+///
+/// Display: Attach
+/// Category: meta
+#[element(Show)]
+pub struct AttachElem {
+    /// Path to the file to attach.
+    #[required]
+    #[parse(
+        let Spanned { v: path, span } =
+            args.expect::<Spanned<EcoString>>("path to file")?;
+        let path: EcoString = vm.locate(&path).at(span)?.to_string_lossy().into();
+        let _ = vm.world().file(Path::new(path.as_str())).at(span)?;
+        path
+    )]
+    pub path: EcoString,
+
+    /// The name under which the file is attached.
+    ///
+    /// When `{none}`, the file keeps the name it has on disk.
+    pub name: Option<EcoString>,
+
+    /// A description of the file that PDF viewers show next to it.
+    pub description: Option<EcoString>,
+
+    /// The [MIME type](https://en.wikipedia.org/wiki/Media_type) of the file,
+    /// for instance `{"text/csv"}`.
+    pub mime_type: Option<EcoString>,
+
+    /// Content that links to the attached file.
+    #[positional]
+    pub body: Option<Content>,
+}
+
+impl Show for AttachElem {
+    fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let path = self.path();
+        let path = Path::new(path.as_str());
+        let data = vt.world.file(path).at(self.span())?;
+        let name = self.name(styles).unwrap_or_else(|| {
+            path.file_name().unwrap_or_default().to_string_lossy().into()
+        });
+
+        let meta = Meta::Attachment(Attachment {
+            name,
+            data,
+            mime_type: self.mime_type(styles),
+            description: self.description(styles),
+        });
+
+        let body = self.body(styles).unwrap_or_else(|| MetaElem::new().pack());
+        Ok(body.styled(MetaElem::set_data(vec![meta])))
+    }
+}
@@ -10,7 +10,7 @@ pub use comemo::{Track, Tracked, TrackedMut};
 #[doc(no_inline)]
 pub use ecow::{eco_format, EcoString};
 #[doc(no_inline)]
-pub use typst::diag::{bail, error, At, SourceResult, StrResult};
+pub use typst::diag::{bail, error, warning, At, SourceResult, StrResult};
 #[doc(no_inline)]
 pub use typst::doc::*;
 #[doc(no_inline)]
@@ -23,9 +23,9 @@ pub use typst::geom::*;
 #[doc(no_inline)]
 pub use typst::model::{
     element, Behave, Behaviour, Construct, Content, ElemFunc, Element, Finalize, Fold,
-    Introspector, Label, Locatable, LocatableSelector, Location, MetaElem, Resolve,
-    Selector, Set, Show, StabilityProvider, StyleChain, StyleVec, Styles, Synthesize,
-    Unlabellable, Vt,
+    Introspector, Label, Locatable, LocatableSelector, Location, MetaElem, PlainText,
+    Resolve, Selector, Set, Show, StabilityProvider, Structural, StyleChain, StyleVec,
+    Styles, Synthesize, Unlabellable, Vt,
 };
 #[doc(no_inline)]
 pub use typst::syntax::{Span, Spanned};
@@ -13,7 +13,7 @@ use crate::prelude::*;
 ///
 /// Display: Underline
 /// Category: text
-#[element(Show)]
+#[element(Show, PlainText)]
 pub struct UnderlineElem {
     /// How to stroke the line. The text color and thickness are read from the
     /// font tables if `{auto}`.
@@ -65,6 +65,12 @@ pub struct UnderlineElem {
     pub body: Content,
 }
 
+impl PlainText for UnderlineElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl Show for UnderlineElem {
     fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
         Ok(self.body().styled(TextElem::set_deco(Decoration {
@@ -86,7 +92,7 @@ impl Show for UnderlineElem {
 ///
 /// Display: Overline
 /// Category: text
-#[element(Show)]
+#[element(Show, PlainText)]
 pub struct OverlineElem {
     /// How to stroke the line. The text color and thickness are read from the
     /// font tables if `{auto}`.
@@ -144,6 +150,12 @@ pub struct OverlineElem {
     pub body: Content,
 }
 
+impl PlainText for OverlineElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl Show for OverlineElem {
     fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
         Ok(self.body().styled(TextElem::set_deco(Decoration {
@@ -165,7 +177,7 @@ impl Show for OverlineElem {
 ///
 /// Display: Strikethrough
 /// Category: text
-#[element(Show)]
+#[element(Show, PlainText)]
 pub struct StrikeElem {
     /// How to stroke the line. The text color and thickness are read from the
     /// font tables if `{auto}`.
@@ -208,6 +220,12 @@ pub struct StrikeElem {
     pub body: Content,
 }
 
+impl PlainText for StrikeElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl Show for StrikeElem {
     fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
         Ok(self.body().styled(TextElem::set_deco(Decoration {
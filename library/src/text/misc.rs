@@ -1,11 +1,12 @@
 use super::TextElem;
+use crate::html::{Html, HtmlElem};
 use crate::prelude::*;
 
 /// A text space.
 ///
 /// Display: Space
 /// Category: text
-#[element(Unlabellable, Behave)]
+#[element(Unlabellable, Behave, PlainText)]
 pub struct SpaceElem {}
 
 impl Behave for SpaceElem {
@@ -14,6 +15,12 @@ impl Behave for SpaceElem {
     }
 }
 
+impl PlainText for SpaceElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push(' ');
+    }
+}
+
 impl Unlabellable for SpaceElem {}
 
 /// Inserts a line break.
@@ -36,7 +43,7 @@ impl Unlabellable for SpaceElem {}
 ///
 /// Display: Line Break
 /// Category: text
-#[element(Behave)]
+#[element(Behave, PlainText)]
 pub struct LinebreakElem {
     /// Whether to justify the line before the break.
     ///
@@ -55,6 +62,12 @@ pub struct LinebreakElem {
     pub justify: bool,
 }
 
+impl PlainText for LinebreakElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push('\n');
+    }
+}
+
 impl Behave for LinebreakElem {
     fn behaviour(&self) -> Behaviour {
         Behaviour::Destructive
@@ -82,7 +95,7 @@ impl Behave for LinebreakElem {
 ///
 /// Display: Strong Emphasis
 /// Category: text
-#[element(Show)]
+#[element(Show, PlainText, Html)]
 pub struct StrongElem {
     /// The delta to apply on the font weight.
     ///
@@ -98,12 +111,24 @@ pub struct StrongElem {
     pub body: Content,
 }
 
+impl PlainText for StrongElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl Show for StrongElem {
     fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
         Ok(self.body().styled(TextElem::set_delta(Delta(self.delta(styles)))))
     }
 }
 
+impl Html for StrongElem {
+    fn html(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(HtmlElem::new("strong".into()).with_body(self.body()).pack())
+    }
+}
+
 /// A delta that is summed up when folded.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Delta(pub i64);
@@ -151,19 +176,31 @@ impl Fold for Delta {
 ///
 /// Display: Emphasis
 /// Category: text
-#[element(Show)]
+#[element(Show, PlainText, Html)]
 pub struct EmphElem {
     /// The content to emphasize.
     #[required]
     pub body: Content,
 }
 
+impl PlainText for EmphElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl Show for EmphElem {
     fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
         Ok(self.body().styled(TextElem::set_emph(Toggle)))
     }
 }
 
+impl Html for EmphElem {
+    fn html(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(HtmlElem::new("em".into()).with_body(self.body()).pack())
+    }
+}
+
 /// A toggle that turns on and off alternatingly if folded.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Toggle;
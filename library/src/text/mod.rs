@@ -40,7 +40,7 @@ use crate::prelude::*;
 ///
 /// Display: Text
 /// Category: text
-#[element(Construct)]
+#[element(Construct, PlainText)]
 pub struct TextElem {
     /// A prioritized sequence of font families.
     ///
@@ -59,6 +59,32 @@ pub struct TextElem {
     /// هذا عربي.
     ///
     /// ```
+    #[parse({
+        let font_list: Option<Spanned<Value>> = args.named("font")?;
+        match font_list {
+            Some(Spanned { v, span }) => {
+                // Families are matched in lowercase, but reported as written.
+                let names: Vec<EcoString> = match &v {
+                    Value::Array(array) => {
+                        array.iter().filter_map(|v| v.clone().cast().ok()).collect()
+                    }
+                    v => v.clone().cast().ok().into_iter().collect(),
+                };
+                let list: FontList = v.cast().at(span)?;
+                for name in names {
+                    if !vm.world().book().contains_family(&name.to_lowercase()) {
+                        vm.vt.tracer.warn(warning!(
+                            span,
+                            "unknown font family: {}",
+                            name,
+                        ));
+                    }
+                }
+                Some(list)
+            }
+            None => None,
+        }
+    })]
     #[default(FontList(vec![FontFamily::new("Linux Libertine")]))]
     pub font: FontList,
 
@@ -443,6 +469,20 @@ pub struct TextElem {
     #[fold]
     pub features: FontFeatures,
 
+    /// Coordinates for the variation axes of variable fonts.
+    ///
+    /// Given as a dictionary that maps four-letter axis tags to numbers. The
+    /// text's weight and stretch are mapped onto the standard `wght` and `wdth`
+    /// axes automatically, but coordinates given here take precedence. Axes
+    /// that the font doesn't have are ignored.
+    ///
+    /// ```example
+    /// #set text(variations: (wght: 550, opsz: 12))
+    /// Semibold text at an optical size for small print.
+    /// ```
+    #[fold]
+    pub variations: FontVariations,
+
     /// Content in which all text is styled according to the other arguments.
     #[external]
     #[required]
@@ -479,6 +519,12 @@ pub struct TextElem {
     pub smallcaps: bool,
 }
 
+impl PlainText for TextElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.text());
+    }
+}
+
 impl TextElem {
     /// Create a new packed text element.
     pub fn packed(text: impl Into<EcoString>) -> Content {
@@ -487,11 +533,11 @@ impl TextElem {
 }
 
 impl Construct for TextElem {
-    fn construct(_: &mut Vm, args: &mut Args) -> SourceResult<Content> {
+    fn construct(vm: &mut Vm, args: &mut Args) -> SourceResult<Content> {
         // The text constructor is special: It doesn't create a text element.
         // Instead, it leaves the passed argument structurally unchanged, but
         // styles all text in it.
-        let styles = Self::set(args)?;
+        let styles = Self::set(vm, args)?;
         let body = args.expect::<Content>("body")?;
         Ok(body.styled_with_map(styles))
     }
@@ -748,3 +794,47 @@ impl Fold for FontFeatures {
         self
     }
 }
+
+/// Coordinates for the variation axes of variable fonts.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct FontVariations(pub Vec<(Tag, Scalar)>);
+
+cast_from_value! {
+    FontVariations,
+    values: Dict => Self(values
+        .into_iter()
+        .map(|(k, v)| {
+            let tag = match *k.as_bytes() {
+                [a, b, c, d] if k.is_ascii() => Tag::from_bytes(&[a, b, c, d]),
+                _ => return Err("variation axes must be four-letter tags".into()),
+            };
+            let value = v.cast::<f64>()?;
+            if !value.is_finite() {
+                return Err("variation coordinates must be finite".into());
+            }
+            Ok((tag, Scalar(value)))
+        })
+        .collect::<StrResult<_>>()?),
+}
+
+cast_to_value! {
+    v: FontVariations => Value::Dict(
+        v.0.into_iter()
+            .map(|(tag, value)| {
+                let bytes = tag.to_bytes();
+                let key = std::str::from_utf8(&bytes).unwrap_or_default();
+                (key.into(), value.0.into())
+            })
+            .collect(),
+    )
+}
+
+impl Fold for FontVariations {
+    type Output = Self;
+
+    fn fold(self, mut outer: Self::Output) -> Self::Output {
+        // Inner coordinates come last so that they take precedence.
+        outer.0.extend(self.0);
+        outer
+    }
+}
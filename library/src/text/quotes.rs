@@ -24,7 +24,7 @@ use crate::prelude::*;
 ///
 /// Display: Smart Quote
 /// Category: text
-#[element]
+#[element(PlainText)]
 pub struct SmartQuoteElem {
     /// Whether this should be a double quote.
     #[default(true)]
@@ -44,6 +44,12 @@ pub struct SmartQuoteElem {
     pub enabled: bool,
 }
 
+impl PlainText for SmartQuoteElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push(if self.double(StyleChain::default()) { '"' } else { '\'' });
+    }
+}
+
 /// State machine for smart quote substitution.
 #[derive(Debug, Clone)]
 pub struct Quoter {
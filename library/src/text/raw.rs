@@ -5,6 +5,7 @@ use typst::syntax::{self, LinkedNode};
 use super::{
     FontFamily, FontList, Hyphenate, LinebreakElem, SmartQuoteElem, TextElem, TextSize,
 };
+use crate::html::{Html, HtmlElem};
 use crate::layout::BlockElem;
 use crate::meta::{Figurable, LocalName};
 use crate::prelude::*;
@@ -36,7 +37,7 @@ use crate::prelude::*;
 ///
 /// Display: Raw Text / Code
 /// Category: text
-#[element(Synthesize, Show, Finalize, LocalName, Figurable)]
+#[element(Synthesize, Show, Finalize, LocalName, Figurable, PlainText, Html)]
 pub struct RawElem {
     /// The raw text.
     ///
@@ -104,6 +105,12 @@ pub struct RawElem {
     pub lang: Option<EcoString>,
 }
 
+impl PlainText for RawElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.text());
+    }
+}
+
 impl RawElem {
     /// The supported language names and tags.
     pub fn languages() -> Vec<(&'static str, Vec<&'static str>)> {
@@ -186,6 +193,25 @@ impl Show for RawElem {
     }
 }
 
+impl Html for RawElem {
+    fn html(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let body = TextElem::packed(self.text());
+        let mut code = HtmlElem::new("code".into()).with_body(body);
+        if let Some(lang) = self.lang(styles) {
+            let mut attrs = Dict::new();
+            attrs.insert("class".into(), eco_format!("language-{lang}").into());
+            code = code.with_attrs(attrs);
+        }
+
+        let mut realized = code.pack();
+        if self.block(styles) {
+            realized = HtmlElem::new("pre".into()).with_body(realized).pack();
+        }
+
+        Ok(realized)
+    }
+}
+
 impl Finalize for RawElem {
     fn finalize(&self, realized: Content, _: StyleChain) -> Content {
         let mut styles = Styles::new();
@@ -139,13 +139,24 @@ impl<'a> ShapedText<'a> {
         let decos = TextElem::deco_in(self.styles);
         let fill = TextElem::fill_in(self.styles);
 
+        let clusters = self.cluster_ranges();
+        let mut index = 0;
+
         for ((font, y_offset), group) in
             self.glyphs.as_ref().group_by_key(|g| (g.font.clone(), g.y_offset))
         {
             let pos = Point::new(offset, top + shift - y_offset.at(self.size));
+
+            // The glyphs' clusters are stored relative to the run's text.
+            let ranges = &clusters[index..index + group.len()];
+            let start = ranges.iter().map(|r| r.start).min().unwrap_or_default();
+            let end = ranges.iter().map(|r| r.end).max().unwrap_or_default();
+            index += group.len();
+
             let glyphs = group
                 .iter()
-                .map(|glyph| {
+                .zip(ranges)
+                .map(|(glyph, range)| {
                     let mut justification = Em::zero();
                     if justification_ratio < 0.0 {
                         justification += glyph.shrinkability() * justification_ratio
@@ -161,6 +172,7 @@ impl<'a> ShapedText<'a> {
                         x_advance: glyph.x_advance + justification,
                         x_offset: glyph.x_offset,
                         c: glyph.c,
+                        range: range.start - start..range.end - start,
                         span: glyph.span,
                         offset: glyph.offset,
                     }
@@ -172,6 +184,7 @@ impl<'a> ShapedText<'a> {
                 size: self.size,
                 lang,
                 fill: fill.clone(),
+                text: self.text.get(start..end).unwrap_or_default().into(),
                 glyphs,
             };
 
@@ -217,6 +230,7 @@ impl<'a> ShapedText<'a> {
                     .book()
                     .select(family.as_str(), self.variant)
                     .and_then(|id| world.font(id))
+                    .map(|font| font.with_variations(&variations(self.styles)))
                 {
                     expand(&font);
                     break;
@@ -302,7 +316,8 @@ impl<'a> ShapedText<'a> {
             let font = world
                 .book()
                 .select(family.as_str(), self.variant)
-                .and_then(|id| world.font(id))?;
+                .and_then(|id| world.font(id))?
+                .with_variations(&variations(self.styles));
             let ttf = font.ttf();
             let glyph_id = ttf.glyph_index('-')?;
             let x_advance = font.to_em(ttf.glyph_hor_advance(glyph_id)?);
@@ -324,6 +339,27 @@ impl<'a> ShapedText<'a> {
         });
     }
 
+    /// The byte ranges of the glyphs' clusters in the text.
+    fn cluster_ranges(&self) -> Vec<Range<usize>> {
+        // Glyphs reused from shaping a larger text have clusters relative to
+        // that text. The smallest cluster is where this text starts in it.
+        let base = self.glyphs.iter().map(|g| g.cluster).min().unwrap_or_default();
+        let mut starts: Vec<_> = self.glyphs.iter().map(|g| g.cluster - base).collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let len = self.text.len();
+        self.glyphs
+            .iter()
+            .map(|glyph| {
+                let start = (glyph.cluster - base).min(len);
+                let next = starts.partition_point(|&s| s <= start);
+                let end = starts.get(next).map_or(len, |&s| s.min(len));
+                start..end
+            })
+            .collect()
+    }
+
     /// Find the subslice of glyphs that represent the given text range if both
     /// sides are safe to break.
     fn slice_safe_to_break(&self, text_range: Range<usize>) -> Option<&[ShapedGlyph]> {
@@ -399,6 +435,7 @@ struct ShapingContext<'a> {
     styles: StyleChain<'a>,
     size: Abs,
     variant: FontVariant,
+    variations: Vec<(ttf_parser::Tag, f64)>,
     tags: Vec<rustybuzz::Feature>,
     fallback: bool,
     dir: Dir,
@@ -423,6 +460,7 @@ pub fn shape<'a>(
         used: vec![],
         styles,
         variant: variant(styles),
+        variations: variations(styles),
         tags: tags(styles),
         fallback: TextElem::fallback_in(styles),
         dir,
@@ -461,9 +499,11 @@ fn shape_segment(
     // Find the next available family.
     let world = ctx.vt.world;
     let book = world.book();
+    let instance = |font: Font| font.with_variations(&ctx.variations);
     let mut selection = families.find_map(|family| {
         book.select(family.as_str(), ctx.variant)
             .and_then(|id| world.font(id))
+            .map(instance)
             .filter(|font| !ctx.used.contains(font))
     });
 
@@ -473,6 +513,7 @@ fn shape_segment(
         selection = book
             .select_fallback(first, ctx.variant, text)
             .and_then(|id| world.font(id))
+            .map(instance)
             .filter(|font| !ctx.used.contains(font));
     }
 
@@ -653,6 +694,24 @@ pub fn variant(styles: StyleChain) -> FontVariant {
     variant
 }
 
+/// Resolve the coordinates for the variation axes of variable fonts.
+///
+/// The weight and stretch of the font variant map onto the `wght` and `wdth`
+/// axes. Explicitly given coordinates come last and thus take precedence.
+pub fn variations(styles: StyleChain) -> Vec<(ttf_parser::Tag, f64)> {
+    let variant = variant(styles);
+    let mut variations = vec![
+        (ttf_parser::Tag::from_bytes(b"wght"), f64::from(variant.weight.to_number())),
+        (ttf_parser::Tag::from_bytes(b"wdth"), variant.stretch.to_ratio().get() * 100.0),
+    ];
+
+    for (tag, value) in TextElem::variations_in(styles).0 {
+        variations.push((ttf_parser::Tag(tag.0), value.0));
+    }
+
+    variations
+}
+
 /// Resolve a prioritized iterator over the font families.
 pub fn families(styles: StyleChain) -> impl Iterator<Item = FontFamily> + Clone {
     const FALLBACKS: &[&str] = &[
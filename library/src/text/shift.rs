@@ -12,7 +12,7 @@ use crate::prelude::*;
 ///
 /// Display: Subscript
 /// Category: text
-#[element(Show)]
+#[element(Show, PlainText)]
 pub struct SubElem {
     /// Whether to prefer the dedicated subscript characters of the font.
     ///
@@ -44,6 +44,12 @@ pub struct SubElem {
     pub body: Content,
 }
 
+impl PlainText for SubElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl Show for SubElem {
     fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
         let body = self.body();
@@ -74,7 +80,7 @@ impl Show for SubElem {
 ///
 /// Display: Superscript
 /// Category: text
-#[element(Show)]
+#[element(Show, PlainText)]
 pub struct SuperElem {
     /// Whether to prefer the dedicated superscript characters of the font.
     ///
@@ -106,6 +112,12 @@ pub struct SuperElem {
     pub body: Content,
 }
 
+impl PlainText for SuperElem {
+    fn plain_text(&self, text: &mut EcoString) {
+        text.push_str(&self.body().plain_text());
+    }
+}
+
 impl Show for SuperElem {
     fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
         let body = self.body();
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use typst::eval::{Module, Scope};
+
+use crate::prelude::*;
+
+/// A module with functions that create color gradients.
+pub fn module() -> Module {
+    let mut scope = Scope::new();
+    scope.define("linear", linear);
+    scope.define("radial", radial);
+    Module::new("gradient").with_scope(scope)
+}
+
+/// Create a gradient that blends colors along a straight line.
+///
+/// A gradient can be used wherever a color is accepted as a fill or stroke,
+/// for instance for the `fill` of a [rectangle]($func/rect) or of
+/// [text]($func/text).
+///
+/// Each stop is either a color or an array of a color and the offset at which
+/// it is reached. If no offsets are given, the stops are spread out evenly.
+/// Colors between the stops are interpolated in the RGB color space.
+///
+/// ## Example
+/// ```example
+/// #rect(width: 100%, fill: gradient.linear(red, yellow, blue))
+/// #rect(
+///   width: 100%,
+///   fill: gradient.linear(
+///     (eastern, 0%), (teal, 20%), (navy, 100%),
+///     angle: 45deg,
+///   ),
+/// )
+/// ```
+///
+/// Display: Linear Gradient
+/// Category: visualize
+/// Returns: gradient
+#[func]
+pub fn linear(
+    /// The color stops of the gradient.
+    #[variadic]
+    stops: Vec<Spanned<Stop>>,
+    /// The direction of the gradient, measured clockwise from left-to-right.
+    #[named]
+    #[default]
+    angle: Angle,
+    /// What the gradient stretches over.
+    ///
+    /// - `{"shape"}`: The bounding box of the shape or text that is filled or
+    ///   stroked.
+    /// - `{"page"}`: The page the shape or text is on. This way, multiple
+    ///   shapes can share one continuous gradient.
+    ///
+    /// When `{auto}`, shapes use their own bounding box and text uses the
+    /// page.
+    #[named]
+    #[default]
+    relative: Smart<RelativeTo>,
+) -> Value {
+    let stops = resolve_stops(args.span, stops)?;
+    Gradient::Linear(Arc::new(LinearGradient { stops, angle, relative })).into()
+}
+
+/// Create a gradient that blends colors outwards from a center point.
+///
+/// The stops work just like those of a linear gradient, with offset zero at
+/// the center and offset one on the circle around it.
+///
+/// ## Example
+/// ```example
+/// #circle(radius: 20pt, fill: gradient.radial(white, aqua, navy))
+/// #square(
+///   size: 40pt,
+///   fill: gradient.radial(
+///     yellow, orange,
+///     center: (30%, 30%),
+///     radius: 70%,
+///   ),
+/// )
+/// ```
+///
+/// Display: Radial Gradient
+/// Category: visualize
+/// Returns: gradient
+#[func]
+pub fn radial(
+    /// The color stops of the gradient.
+    #[variadic]
+    stops: Vec<Spanned<Stop>>,
+    /// The center of the gradient, relative to the box it stretches over.
+    #[named]
+    #[default(Axes::splat(Ratio::new(0.5)))]
+    center: Axes<Ratio>,
+    /// The radius of the gradient, relative to the box it stretches over.
+    ///
+    /// In a box that isn't square, the gradient is stretched into an
+    /// ellipse.
+    #[named]
+    #[default(Ratio::new(0.5))]
+    radius: Ratio,
+    /// What the gradient stretches over.
+    ///
+    /// Works just like for linear gradients.
+    #[named]
+    #[default]
+    relative: Smart<RelativeTo>,
+) -> Value {
+    if radius.get() <= 0.0 {
+        bail!(args.span, "radius must be positive");
+    }
+
+    let stops = resolve_stops(args.span, stops)?;
+    Gradient::Radial(Arc::new(RadialGradient { stops, center, radius, relative })).into()
+}
+
+/// Check the stops of a gradient and fill in missing offsets.
+fn resolve_stops(
+    span: Span,
+    stops: Vec<Spanned<Stop>>,
+) -> SourceResult<Vec<(Color, Ratio)>> {
+    if stops.len() < 2 {
+        bail!(span, "a gradient must have at least two stops");
+    }
+
+    if stops.iter().all(|stop| stop.v.1.is_none()) {
+        let last = (stops.len() - 1) as f64;
+        return Ok(stops
+            .into_iter()
+            .enumerate()
+            .map(|(i, stop)| (stop.v.0, Ratio::new(i as f64 / last)))
+            .collect());
+    }
+
+    let mut prev = Ratio::zero();
+    let mut resolved = Vec::with_capacity(stops.len());
+    for Spanned { v: Stop(color, offset), span } in stops {
+        let Some(offset) = offset else {
+            bail!(span, "either all or none of the stops must have an offset");
+        };
+
+        if offset.get() < 0.0 || offset.get() > 1.0 {
+            bail!(span, "offset must be between 0% and 100%");
+        }
+
+        if offset < prev {
+            bail!(span, "offsets must be in ascending order");
+        }
+
+        prev = offset;
+        resolved.push((color, offset));
+    }
+
+    Ok(resolved)
+}
+
+/// A color stop of a gradient, with an optional offset.
+struct Stop(Color, Option<Ratio>);
+
+cast_from_value! {
+    Stop,
+    color: Color => Self(color, None),
+    array: Array => {
+        let mut iter = array.into_iter();
+        match (iter.next(), iter.next(), iter.next()) {
+            (Some(a), Some(b), None) => Self(a.cast()?, Some(b.cast()?)),
+            _ => Err("a stop must be a color or an array of a color and an offset")?,
+        }
+    },
+}
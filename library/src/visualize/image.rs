@@ -10,7 +10,9 @@ use crate::{
 
 /// A raster or vector graphic.
 ///
-/// Supported formats are PNG, JPEG, GIF and SVG.
+/// Supported formats are PNG, JPEG, GIF, SVG and PDF. Of a PDF file, a single
+/// page is shown, with its vector graphics and fonts intact in PDF export.
+/// Other exports show a placeholder instead.
 ///
 /// ## Example
 /// ```example
@@ -33,11 +35,19 @@ pub struct ImageElem {
         let Spanned { v: path, span } =
             args.expect::<Spanned<EcoString>>("path to image file")?;
         let path: EcoString = vm.locate(&path).at(span)?.to_string_lossy().into();
-        let _ = load(vm.world(), &path).at(span)?;
+        let _ = load(vm.world(), &path, NonZeroUsize::ONE).at(span)?;
         path
     )]
     pub path: EcoString,
 
+    /// Which page of a PDF file to show, starting at one.
+    ///
+    /// ```example
+    /// #image("diagrams.pdf", page: 2, width: 60%)
+    /// ```
+    #[default(NonZeroUsize::ONE)]
+    pub page: NonZeroUsize,
+
     /// The width of the image.
     pub width: Smart<Rel<Length>>,
 
@@ -49,6 +59,13 @@ pub struct ImageElem {
     pub fit: ImageFit,
 }
 
+impl ImageElem {
+    /// Load the image, or the selected page of it.
+    pub fn image(&self, vt: &Vt, styles: StyleChain) -> SourceResult<Image> {
+        load(vt.world, &self.path(), self.page(styles)).at(self.span())
+    }
+}
+
 impl Layout for ImageElem {
     fn layout(
         &self,
@@ -56,7 +73,7 @@ impl Layout for ImageElem {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment> {
-        let image = load(vt.world, &self.path()).unwrap();
+        let image = self.image(vt, styles)?;
         let sizing = Axes::new(self.width(styles), self.height(styles));
         let region = sizing
             .zip(regions.base())
@@ -156,9 +173,9 @@ pub enum ImageFit {
     Stretch,
 }
 
-/// Load an image from a path.
+/// Load an image, or a page of it, from a path.
 #[comemo::memoize]
-fn load(world: Tracked<dyn World>, full: &str) -> StrResult<Image> {
+fn load(world: Tracked<dyn World>, full: &str, page: NonZeroUsize) -> StrResult<Image> {
     let full = Path::new(full);
     let buffer = world.file(full)?;
     let ext = full.extension().and_then(OsStr::to_str).unwrap_or_default();
@@ -167,7 +184,8 @@ fn load(world: Tracked<dyn World>, full: &str) -> StrResult<Image> {
         "jpg" | "jpeg" => ImageFormat::Raster(RasterFormat::Jpg),
         "gif" => ImageFormat::Raster(RasterFormat::Gif),
         "svg" | "svgz" => ImageFormat::Vector(VectorFormat::Svg),
+        "pdf" => ImageFormat::Vector(VectorFormat::Pdf),
         _ => return Err("unknown image format".into()),
     };
-    Image::new(buffer, format)
+    Image::with_page(buffer, format, page)
 }
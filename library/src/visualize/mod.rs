@@ -1,13 +1,16 @@
 //! Drawing and visualization.
 
+pub mod gradient;
 mod image;
 mod line;
 mod path;
+mod pattern;
 mod polygon;
 mod shape;
 
 pub use self::image::*;
 pub use self::line::*;
 pub use self::path::*;
+pub use self::pattern::*;
 pub use self::polygon::*;
 pub use self::shape::*;
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+
+/// Create a pattern that repeats a tile of content.
+///
+/// A pattern can be used wherever a color is accepted as a fill or stroke,
+/// for instance for the `fill` of a [rectangle]($func/rect) or of
+/// [text]($func/text). The content is laid out once and then repeated in
+/// both directions to cover the painted area.
+///
+/// Since a pattern is created before it is placed into the document, its tile
+/// is laid out with the default styles. To make it match its surroundings
+/// instead, retrieve the active styles with the [`style`]($func/style)
+/// function and pass them to the pattern.
+///
+/// ## Example
+/// ```example
+/// #let dots = pattern(size: (8pt, 8pt), circle(radius: 2pt, fill: teal))
+/// #rect(width: 100%, height: 40pt, fill: dots)
+///
+/// #let hatch = pattern(
+///   size: (6pt, 6pt),
+///   line(start: (0%, 100%), end: (100%, 0%), stroke: 0.5pt),
+/// )
+/// #rect(width: 100%, fill: hatch)[Hatched]
+///
+/// #set text(fill: eastern)
+/// #style(styles => rect(
+///   width: 100%,
+///   fill: pattern(size: (12pt, 12pt), styles: styles)[\*],
+/// ))
+/// ```
+///
+/// Display: Pattern
+/// Category: visualize
+/// Returns: pattern
+#[func]
+pub fn pattern(
+    /// The content of a single tile.
+    body: Content,
+    /// The size of a single tile.
+    ///
+    /// When `{auto}`, the tile is as large as its content.
+    #[named]
+    #[default]
+    size: Smart<Axes<Length>>,
+    /// The gap between neighbouring tiles.
+    #[named]
+    #[default(Axes::splat(Length::zero()))]
+    spacing: Axes<Length>,
+    /// What the tiling is aligned to.
+    ///
+    /// - `{"shape"}`: The tiles start in the top-left corner of the bounding
+    ///   box of the shape or text that is filled or stroked.
+    /// - `{"page"}`: The tiles start in the top-left corner of the page. This
+    ///   way, the tiles of neighbouring shapes line up.
+    ///
+    /// When `{auto}`, shapes use their own bounding box and text uses the
+    /// page.
+    #[named]
+    #[default]
+    relative: Smart<RelativeTo>,
+    /// The styles with which to lay out the tile.
+    ///
+    /// When `{none}`, the default styles are used.
+    #[named]
+    #[default]
+    styles: Option<Styles>,
+) -> Value {
+    let world = vm.world();
+    let styles = match &styles {
+        Some(styles) => StyleChain::new(styles),
+        None => StyleChain::new(&world.library().styles),
+    };
+    let pod = match size {
+        Smart::Auto => Regions::one(Axes::splat(Abs::inf()), Axes::splat(false)),
+        Smart::Custom(size) => Regions::one(size.resolve(styles), Axes::splat(true)),
+    };
+
+    let frame = body.measure(&mut vm.vt, styles, pod)?.into_frame();
+    let Size { x, y } = frame.size();
+    if !x.is_finite() || !y.is_finite() || x <= Abs::zero() || y <= Abs::zero() {
+        bail!(args.span, "pattern tile must have a positive size");
+    }
+
+    let spacing = spacing.resolve(styles);
+    if spacing.x < Abs::zero() || spacing.y < Abs::zero() {
+        bail!(args.span, "spacing must not be negative");
+    }
+
+    Pattern::new(frame, spacing, relative).into()
+}
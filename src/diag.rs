@@ -44,6 +44,23 @@ macro_rules! __error {
 
 #[doc(inline)]
 pub use crate::__error as error;
+
+/// Construct a [`SourceError`] with [warning](Severity::Warning) severity.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __warning {
+    ($span:expr, $message:expr $(,)?) => {
+        $crate::diag::SourceError::new($span, $message)
+            .with_severity($crate::diag::Severity::Warning)
+    };
+
+    ($span:expr, $fmt:expr, $($arg:expr),+ $(,)?) => {
+        $crate::diag::warning!($span, $crate::diag::eco_format!($fmt, $($arg),+))
+    };
+}
+
+#[doc(inline)]
+pub use crate::__warning as warning;
 #[doc(hidden)]
 pub use ecow::eco_format;
 
@@ -56,6 +73,8 @@ pub type SourceResult<T> = Result<T, Box<Vec<SourceError>>>;
 /// were detached.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct SourceError {
+    /// Whether the diagnostic is an error or a warning.
+    pub severity: Severity,
     /// The span of the erroneous node in the source code.
     pub span: Span,
     /// The position in the node where the error should be annotated.
@@ -71,6 +90,7 @@ impl SourceError {
     #[track_caller]
     pub fn new(span: Span, message: impl Into<EcoString>) -> Self {
         Self {
+            severity: Severity::Error,
             span,
             pos: ErrorPos::Full,
             trace: vec![],
@@ -84,6 +104,12 @@ impl SourceError {
         self
     }
 
+    /// Adjust the severity of the diagnostic.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
     /// The range in the source file identified by
     /// [`self.span.source()`](Span::source) where the error should be
     /// annotated.
@@ -97,6 +123,16 @@ impl SourceError {
     }
 }
 
+/// How severe a [`SourceError`] is.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    /// A fatal problem that aborts compilation.
+    Error,
+    /// A problem that does not prevent compilation, but that the user should
+    /// be made aware of.
+    Warning,
+}
+
 /// A part of an error's [trace](SourceError::trace).
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Tracepoint {
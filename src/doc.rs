@@ -2,20 +2,22 @@
 
 use std::fmt::{self, Debug, Formatter, Write};
 use std::num::NonZeroUsize;
+use std::ops::Range;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 
 use crate::eval::{cast_from_value, cast_to_value, dict, Dict, Value};
 use crate::font::Font;
 use crate::geom::{
     self, rounded_rect, Abs, Align, Axes, Color, Corners, Dir, Em, Geometry, Length,
-    Numeric, Paint, Point, Rel, RgbaColor, Shape, Sides, Size, Stroke, Transform,
+    Numeric, Paint, Point, Rel, RgbaColor, Shape, Sides, Size, Smart, Stroke, Transform,
 };
 use crate::image::Image;
 use crate::model::{Content, Location, MetaElem, StyleChain};
 use crate::syntax::Span;
+use crate::util::Buffer;
 
 /// A finished document with metadata and page frames.
 #[derive(Debug, Default, Clone, Hash)]
@@ -26,6 +28,19 @@ pub struct Document {
     pub title: Option<EcoString>,
     /// The document's author.
     pub author: Vec<EcoString>,
+    /// A short description of the document's contents.
+    pub description: Option<EcoString>,
+    /// Keywords that help to find the document.
+    pub keywords: Vec<EcoString>,
+    /// The document's creation date. When `Auto`, the date of the export is
+    /// used, if any.
+    pub date: Smart<Option<Date>>,
+    /// The document's main language, if it was set explicitly.
+    pub lang: Option<Lang>,
+    /// The region of the document's main language.
+    pub region: Option<Region>,
+    /// Custom metadata properties as pairs of names and values.
+    pub properties: Vec<(EcoString, EcoString)>,
 }
 
 /// A finished layout with items at fixed positions.
@@ -471,6 +486,8 @@ pub struct TextItem {
     pub fill: Paint,
     /// The natural language of the text.
     pub lang: Lang,
+    /// The text that was shaped into the glyphs.
+    pub text: EcoString,
     /// The glyphs.
     pub glyphs: Vec<Glyph>,
 }
@@ -480,6 +497,11 @@ impl TextItem {
     pub fn width(&self) -> Abs {
         self.glyphs.iter().map(|g| g.x_advance).sum::<Em>().at(self.size)
     }
+
+    /// The text of a glyph's cluster.
+    pub fn cluster(&self, glyph: &Glyph) -> &str {
+        self.text.get(glyph.range.clone()).unwrap_or_default()
+    }
 }
 
 impl Debug for TextItem {
@@ -496,7 +518,7 @@ impl Debug for TextItem {
 }
 
 /// A glyph in a run of shaped text.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Glyph {
     /// The glyph's index in the font.
     pub id: u16,
@@ -506,6 +528,8 @@ pub struct Glyph {
     pub x_offset: Em,
     /// The first character of the glyph's cluster.
     pub c: char,
+    /// The byte range of the glyph's cluster in the run's text.
+    pub range: Range<usize>,
     /// The source code location of the text.
     pub span: Span,
     /// The offset within the spanned text.
@@ -610,6 +634,61 @@ cast_to_value! {
     v: Region => v.as_str().into()
 }
 
+/// A calendar date.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Date {
+    /// The year, e.g. 2023.
+    pub year: u16,
+    /// The month, from 1 to 12.
+    pub month: u8,
+    /// The day of the month, from 1 to 31.
+    pub day: u8,
+}
+
+impl FromStr for Date {
+    type Err = &'static str;
+
+    /// Construct a date from its ISO 8601 form `YYYY-MM-DD`.
+    fn from_str(iso: &str) -> Result<Self, Self::Err> {
+        const ERR: &str = "expected date in the form YYYY-MM-DD";
+        let parts: Vec<_> = iso.split('-').collect();
+        let [year, month, day] = parts.as_slice() else { return Err(ERR) };
+
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(ERR);
+        }
+
+        let year = year.parse().map_err(|_| ERR)?;
+        let month = month.parse().map_err(|_| ERR)?;
+        let day = day.parse().map_err(|_| ERR)?;
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return Err("date is out of range");
+        }
+
+        Ok(Self { year, month, day })
+    }
+}
+
+/// The number of days in a month of the Gregorian calendar.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+cast_from_value! {
+    Date,
+    string: EcoString => Self::from_str(&string)?,
+}
+
+cast_to_value! {
+    v: Date => eco_format!("{:04}-{:02}-{:02}", v.year, v.month, v.day).into()
+}
+
 /// Meta information that isn't visible or renderable.
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub enum Meta {
@@ -618,8 +697,28 @@ pub enum Meta {
     /// An identifiable element that produces something within the area this
     /// metadata is attached to.
     Elem(Content),
+    /// An element like a paragraph or a list that is part of the logical
+    /// structure of the document and produces something within the area this
+    /// metadata is attached to. Unlike with `Elem`, the element is not known
+    /// to the introspector.
+    Structure(Content),
+    /// A table cell at the given column and row that produces something
+    /// within the area this metadata is attached to.
+    Cell(usize, usize),
+    /// Marks what is produced within the area this metadata is attached to as
+    /// decoration, like table lines, that is not part of the logical
+    /// structure of the document.
+    Artifact,
     /// The numbering of the current page.
     PageNumbering(Value),
+    /// How the current page is labelled in the page navigation of viewers.
+    PageLabel(PageLabel),
+    /// A file that is attached to the document. If the metadata has a size,
+    /// its area also links to the file.
+    Attachment(Attachment),
+    /// An interactive form field that covers the area this metadata is
+    /// attached to.
+    FormField(FormField),
     /// Indicates that content should be hidden. This variant doesn't appear
     /// in the final frames as it is removed alongside the content that should
     /// be hidden.
@@ -630,6 +729,105 @@ cast_from_value! {
     Meta: "meta",
 }
 
+/// How a page is labelled in the page navigation of viewers.
+///
+/// A label consists of a fixed prefix followed by the page number in the
+/// given style. Without a style, the label is just the prefix.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PageLabel {
+    /// How the page number is displayed, if at all.
+    pub style: Option<PageLabelStyle>,
+    /// Text that is shown in front of the page number.
+    pub prefix: EcoString,
+    /// The page number.
+    pub number: NonZeroUsize,
+}
+
+impl PageLabel {
+    /// Whether this label continues the numbering of the given label on the
+    /// previous page.
+    pub fn continues(&self, prev: &Self) -> bool {
+        self.style.is_some()
+            && self.style == prev.style
+            && self.prefix == prev.prefix
+            && self.number.get() == prev.number.get() + 1
+    }
+}
+
+/// How the number in a page label is displayed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PageLabelStyle {
+    /// Decimal arabic numerals.
+    Arabic,
+    /// Lowercase roman numerals.
+    LowerRoman,
+    /// Uppercase roman numerals.
+    UpperRoman,
+    /// Lowercase letters, from a to z.
+    LowerAlpha,
+    /// Uppercase letters, from A to Z.
+    UpperAlpha,
+}
+
+/// A file that is embedded into an exported document.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Attachment {
+    /// The name of the file.
+    pub name: EcoString,
+    /// The raw data of the file.
+    pub data: Buffer,
+    /// The MIME type of the file, if known.
+    pub mime_type: Option<EcoString>,
+    /// A description of the file.
+    pub description: Option<EcoString>,
+}
+
+/// An interactive form field.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FormField {
+    /// The name that identifies the field's value when the form is submitted.
+    ///
+    /// Fields with the same name share their value.
+    pub name: EcoString,
+    /// What kind of field this is.
+    pub kind: FormFieldKind,
+}
+
+/// The different kinds of form fields.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum FormFieldKind {
+    /// A text input with an initial value.
+    Text { value: EcoString, multiline: bool },
+    /// A checkbox that can be checked or unchecked.
+    Checkbox { checked: bool },
+    /// One button of a radio group. The buttons of a group share the field's
+    /// name and each stands for its own value.
+    Radio { value: EcoString, checked: bool },
+    /// A dropdown for picking one of multiple options.
+    Dropdown { options: Vec<EcoString>, selected: Option<EcoString> },
+}
+
+cast_from_value! {
+    FormField: "form field",
+}
+
+/// The role of an element in the logical structure of a document.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Role {
+    /// A paragraph.
+    Paragraph,
+    /// A heading of the given level.
+    Heading(NonZeroUsize),
+    /// A bullet or numbered list.
+    List,
+    /// An item of a list.
+    ListItem,
+    /// A table.
+    Table,
+    /// A figure with an optional alternative description.
+    Figure(Option<EcoString>),
+}
+
 /// A link destination.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Destination {
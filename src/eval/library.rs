@@ -9,6 +9,7 @@ use once_cell::sync::OnceCell;
 use super::{Args, Dynamic, Module, Value, Vm};
 use crate::diag::SourceResult;
 use crate::doc::Document;
+use crate::export::HtmlNode;
 use crate::geom::{Abs, Dir};
 use crate::model::{Content, ElemFunc, Introspector, Label, StyleChain, Styles, Vt};
 use crate::syntax::Span;
@@ -34,6 +35,12 @@ pub struct LangItems {
     /// The root layout function.
     pub layout:
         fn(vt: &mut Vt, content: &Content, styles: StyleChain) -> SourceResult<Document>,
+    /// Converts content into the nodes of a semantic HTML document.
+    pub html: fn(
+        vt: &mut Vt,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<Vec<HtmlNode>>,
     /// Access the em size.
     pub em: fn(StyleChain) -> Abs,
     /// Access the text direction.
@@ -72,6 +79,8 @@ pub struct LangItems {
     ) -> Vec<(EcoString, Option<EcoString>)>,
     /// A section heading: `= Introduction`.
     pub heading: fn(level: NonZeroUsize, body: Content) -> Content,
+    /// The heading function.
+    pub heading_func: ElemFunc,
     /// An item in a bullet list: `- ...`.
     pub list_item: fn(body: Content) -> Content,
     /// An item in an enumeration (numbered list): `+ ...` or `1. ...`.
@@ -110,6 +119,7 @@ impl Debug for LangItems {
 impl Hash for LangItems {
     fn hash<H: Hasher>(&self, state: &mut H) {
         (self.layout as usize).hash(state);
+        (self.html as usize).hash(state);
         (self.em as usize).hash(state);
         (self.dir as usize).hash(state);
         self.space.hash(state);
@@ -125,6 +135,7 @@ impl Hash for LangItems {
         self.link.hash(state);
         self.reference.hash(state);
         self.heading.hash(state);
+        self.heading_func.hash(state);
         self.list_item.hash(state);
         self.enum_item.hash(state);
         self.term_item.hash(state);
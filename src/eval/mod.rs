@@ -290,11 +290,13 @@ impl Route {
     }
 }
 
-/// Traces which values existed for the expression at a span.
+/// Traces which values existed for the expression at a span and collects the
+/// warnings that were emitted during compilation.
 #[derive(Default, Clone)]
 pub struct Tracer {
     span: Option<Span>,
     values: Vec<Value>,
+    warnings: Vec<SourceError>,
 }
 
 impl Tracer {
@@ -303,13 +305,18 @@ impl Tracer {
 
     /// Create a new tracer, possibly with a span under inspection.
     pub fn new(span: Option<Span>) -> Self {
-        Self { span, values: vec![] }
+        Self { span, values: vec![], warnings: vec![] }
     }
 
     /// Get the traced values.
     pub fn finish(self) -> Vec<Value> {
         self.values
     }
+
+    /// Get the warnings that were emitted so far.
+    pub fn warnings(&self) -> &[SourceError] {
+        &self.warnings
+    }
 }
 
 #[comemo::track]
@@ -329,6 +336,16 @@ impl Tracer {
             self.values.push(v);
         }
     }
+
+    /// Emit a warning.
+    ///
+    /// Since content may be laid out multiple times, duplicate warnings are
+    /// ignored.
+    pub fn warn(&mut self, warning: SourceError) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
 }
 
 /// Evaluate an expression.
@@ -1307,7 +1324,7 @@ impl Eval for ast::SetRule {
             })
             .at(target.span())?;
         let args = self.args().eval(vm)?;
-        Ok(target.set(args)?.spanned(self.span()))
+        Ok(target.set(vm, args)?.spanned(self.span()))
     }
 }
 
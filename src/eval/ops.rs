@@ -6,7 +6,9 @@ use ecow::eco_format;
 
 use super::{format_str, Regex, Value};
 use crate::diag::StrResult;
-use crate::geom::{Axes, Axis, GenAlign, Length, Numeric, PartialStroke, Rel, Smart};
+use crate::geom::{
+    Axes, Axis, GenAlign, Gradient, Length, Numeric, Paint, PartialStroke, Rel, Smart,
+};
 use Value::*;
 
 /// Bail with a type mismatch error.
@@ -112,6 +114,18 @@ pub fn add(lhs: Value, rhs: Value) -> StrResult<Value> {
             })
         }
 
+        (Dyn(a), Length(thickness)) | (Length(thickness), Dyn(a))
+            if a.is::<Gradient>() =>
+        {
+            Value::dynamic(PartialStroke {
+                paint: Smart::Custom(Paint::Gradient(
+                    a.downcast::<Gradient>().unwrap().clone(),
+                )),
+                thickness: Smart::Custom(thickness),
+                ..PartialStroke::default()
+            })
+        }
+
         (Dyn(a), Dyn(b)) => {
             // 1D alignments can be summed into 2D alignments.
             if let (Some(&a), Some(&b)) =
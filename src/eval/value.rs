@@ -5,6 +5,7 @@ use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use ecow::eco_format;
+use serde::{Serialize, Serializer};
 use siphasher::sip128::{Hasher128, SipHasher};
 
 use super::{
@@ -240,6 +241,28 @@ impl Hash for Value {
     }
 }
 
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::None => serializer.serialize_none(),
+            Self::Bool(v) => serializer.serialize_bool(*v),
+            Self::Int(v) => serializer.serialize_i64(*v),
+            Self::Float(v) => serializer.serialize_f64(*v),
+            Self::Symbol(v) => serializer.serialize_char(v.get()),
+            Self::Str(v) => serializer.serialize_str(v.as_str()),
+            Self::Content(v) => v.serialize(serializer),
+            Self::Array(v) => serializer.collect_seq(v.iter()),
+            Self::Dict(v) => {
+                serializer.collect_map(v.iter().map(|(k, v)| (k.as_str(), v)))
+            }
+            _ => serializer.serialize_str(self.repr().as_str()),
+        }
+    }
+}
+
 /// A dynamic value.
 #[allow(clippy::derived_hash_with_manual_eq)]
 #[derive(Clone, Hash)]
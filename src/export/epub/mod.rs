@@ -0,0 +1,674 @@
+//! Exporting into EPUB e-books.
+
+mod zip;
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use ecow::{eco_format, EcoString};
+use ttf_parser::Tag;
+
+use self::zip::ZipWriter;
+use super::html::{Embed, Escaped, HtmlWriter};
+use super::svg::svg;
+use super::text::raw;
+use super::{HtmlDocument, HtmlElement, HtmlNode, PdfTimestamp};
+use crate::diag::StrResult;
+use crate::doc::Frame;
+use crate::font::{Font, FontFlags, FontStyle};
+use crate::geom::Smart;
+use crate::image::{Image, ImageFormat, RasterFormat, VectorFormat};
+use crate::util::hash128;
+
+/// Export a document into an EPUB 3 e-book.
+///
+/// The document is split into one XHTML chapter per top-level heading and
+/// its headings make up the table of contents. Images and the fonts used by
+/// the document are packaged with the chapters.
+///
+/// Returns the raw bytes making up the EPUB file or an error if the e-book
+/// exceeds the limits of the ZIP format.
+pub fn epub(document: &HtmlDocument, options: &EpubOptions) -> StrResult<Vec<u8>> {
+    let mut ctx = EpubContext::new(document);
+    let chapters = split_chapters(&document.body);
+    for (i, chapter) in chapters.iter().enumerate() {
+        collect_anchors(chapter, i, &mut ctx.anchors);
+    }
+
+    let files: Vec<_> =
+        chapters.iter().map(|chapter| ctx.write_chapter(chapter)).collect();
+
+    let nav = ctx.write_nav(&chapters);
+    let style = ctx.write_style();
+    let package = ctx.write_package(chapters.len(), options);
+
+    let mut zip = ZipWriter::default();
+    zip.stored("mimetype", b"application/epub+zip")?;
+    zip.deflated("META-INF/container.xml", CONTAINER.as_bytes())?;
+    zip.deflated("OEBPS/content.opf", package.as_bytes())?;
+    zip.deflated("OEBPS/nav.xhtml", nav.as_bytes())?;
+    zip.deflated("OEBPS/style.css", style.as_bytes())?;
+    for (i, file) in files.iter().enumerate() {
+        zip.deflated(&format!("OEBPS/{}", chapter_path(i)), file.as_bytes())?;
+    }
+    for resource in &ctx.resources {
+        zip.deflated(&format!("OEBPS/{}", resource.path), &resource.data)?;
+    }
+
+    zip.finish()
+}
+
+/// Options for EPUB export.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct EpubOptions {
+    /// The modification date of the e-book.
+    ///
+    /// EPUB readers require one. Without a timestamp, the document's date is
+    /// used if it has one and the Unix epoch otherwise.
+    pub timestamp: Option<PdfTimestamp>,
+}
+
+/// Tells reading systems where to find the package document.
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles>
+<rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+</rootfiles>
+</container>
+"#;
+
+/// Context for exporting a whole e-book.
+struct EpubContext<'a> {
+    /// The document that is exported.
+    document: &'a HtmlDocument,
+    /// The chapters in which elements with ids are placed.
+    anchors: HashMap<EcoString, usize>,
+    /// Images, frames and fonts that are packaged with the chapters.
+    resources: Vec<Resource>,
+    /// Maps the hashes of images to their index in `resources`, so that each
+    /// image is packaged only once.
+    images: HashMap<u128, usize>,
+}
+
+/// A file that is packaged with the chapters.
+struct Resource {
+    /// The path of the file, relative to the package document.
+    path: String,
+    /// The file's media type.
+    media_type: &'static str,
+    /// The file's contents.
+    data: Vec<u8>,
+}
+
+/// A heading in the table of contents.
+struct NavItem {
+    title: String,
+    level: usize,
+    href: String,
+    children: Vec<NavItem>,
+}
+
+impl NavItem {
+    /// Insert a heading below this one if its level is higher.
+    fn try_insert(&mut self, child: Self) -> Option<Self> {
+        if child.level <= self.level {
+            return Some(child);
+        }
+
+        let child = match self.children.last_mut() {
+            Some(last) => last.try_insert(child)?,
+            None => child,
+        };
+
+        self.children.push(child);
+        None
+    }
+}
+
+impl<'a> EpubContext<'a> {
+    fn new(document: &'a HtmlDocument) -> Self {
+        Self {
+            document,
+            anchors: HashMap::new(),
+            resources: vec![],
+            images: HashMap::new(),
+        }
+    }
+
+    /// Write the XHTML file of a chapter.
+    fn write_chapter(&mut self, nodes: &[HtmlNode]) -> String {
+        let title = nodes
+            .iter()
+            .find_map(|node| match node {
+                HtmlNode::Element(elem) if heading_level(elem).is_some() => {
+                    Some(plain_text(&elem.children))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| self.title());
+
+        let mut buf = self.xhtml_head(&title);
+        buf.push_str("<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/>\n");
+        buf.push_str("</head>\n<body>\n");
+        let mut w = HtmlWriter::new(true, self);
+        for node in nodes {
+            w.write_node(node);
+        }
+        buf.push_str(&w.finish());
+        buf.push_str("</body>\n</html>\n");
+        buf
+    }
+
+    /// Write the navigation document, which holds the table of contents.
+    fn write_nav(&self, chapters: &[&[HtmlNode]]) -> String {
+        let mut items: Vec<NavItem> = vec![];
+        for (i, chapter) in chapters.iter().enumerate() {
+            let mut headings = vec![];
+            collect_headings(chapter, i, &mut headings);
+            for item in headings {
+                let item = match items.last_mut() {
+                    Some(last) => last.try_insert(item),
+                    None => Some(item),
+                };
+                items.extend(item);
+            }
+        }
+
+        // The table of contents must not be empty.
+        if items.is_empty() {
+            items.push(NavItem {
+                title: self.title(),
+                level: 1,
+                href: chapter_path(0),
+                children: vec![],
+            });
+        }
+
+        let title = self.title();
+        let mut buf = self.xhtml_head(&title);
+        buf.push_str("</head>\n<body>\n<nav epub:type=\"toc\" id=\"toc\">\n");
+        write_nav_items(&mut buf, &items);
+        buf.push_str("</nav>\n</body>\n</html>\n");
+        buf
+    }
+
+    /// Package the used fonts and write the style sheet that makes them
+    /// available.
+    fn write_style(&mut self) -> String {
+        let mut buf = String::new();
+        let mut body = vec![];
+        let mut mono = vec![];
+        let document = self.document;
+
+        let mut chars = BTreeSet::new();
+        collect_chars(&document.body, &mut chars);
+
+        for font in &document.fonts {
+            let info = font.info();
+            let Some((ext, media_type)) = font_type(font) else { continue };
+            let path = format!("fonts/font-{}.{ext}", self.resources.len() + 1);
+            let style = match info.variant.style {
+                FontStyle::Normal => "normal",
+                FontStyle::Italic => "italic",
+                FontStyle::Oblique => "oblique",
+            };
+
+            writeln!(
+                buf,
+                "@font-face {{ font-family: \"{}\"; font-style: {style}; \
+                 font-weight: {}; src: url(\"{path}\"); }}",
+                info.family,
+                info.variant.weight.to_number(),
+            )
+            .unwrap();
+
+            let monospace = info.flags.contains(FontFlags::MONOSPACE);
+            let families = if monospace { &mut mono } else { &mut body };
+            if !families.contains(&info.family) {
+                families.push(info.family.clone());
+            }
+
+            let data = embedded_font(font, &chars);
+            self.resources.push(Resource { path, media_type, data });
+        }
+
+        let list = |families: &[String], generic: &str| {
+            let mut list: Vec<_> =
+                families.iter().map(|family| format!("\"{family}\"")).collect();
+            list.push(generic.into());
+            list.join(", ")
+        };
+
+        writeln!(buf, "body {{ font-family: {}; }}", list(&body, "serif")).unwrap();
+        writeln!(buf, "pre, code {{ font-family: {}; }}", list(&mono, "monospace"))
+            .unwrap();
+        buf.push_str("img { max-width: 100%; }\n");
+        buf.push_str(".equation { text-align: center; }\n");
+        buf
+    }
+
+    /// Write the package document, which holds the metadata and lists all
+    /// files in reading order.
+    fn write_package(&self, chapters: usize, options: &EpubOptions) -> String {
+        let document = self.document;
+        let lang = self.lang();
+        let id = hash128(&(document, options));
+
+        let mut buf = String::new();
+        buf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            buf,
+            "<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+             unique-identifier=\"id\" xml:lang=\"{lang}\">"
+        )
+        .unwrap();
+
+        buf.push_str("<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+        writeln!(buf, "<dc:identifier id=\"id\">urn:uuid:{}</dc:identifier>", uuid(id))
+            .unwrap();
+        writeln!(buf, "<dc:title>{}</dc:title>", Escaped(&self.title())).unwrap();
+        writeln!(buf, "<dc:language>{lang}</dc:language>").unwrap();
+        for author in &document.author {
+            writeln!(buf, "<dc:creator>{}</dc:creator>", Escaped(author)).unwrap();
+        }
+        if let Some(description) = &document.description {
+            writeln!(buf, "<dc:description>{}</dc:description>", Escaped(description))
+                .unwrap();
+        }
+        for keyword in &document.keywords {
+            writeln!(buf, "<dc:subject>{}</dc:subject>", Escaped(keyword)).unwrap();
+        }
+
+        let date = match document.date {
+            Smart::Custom(Some(date)) => Some((date.year, date.month, date.day)),
+            Smart::Auto => options.timestamp.map(|ts| (ts.year, ts.month, ts.day)),
+            Smart::Custom(None) => None,
+        };
+        if let Some((year, month, day)) = date {
+            writeln!(buf, "<dc:date>{year:04}-{month:02}-{day:02}</dc:date>").unwrap();
+        }
+
+        // EPUB requires exactly one modification date. Falling back to a fixed
+        // one keeps the export reproducible.
+        let (year, month, day, hour, minute, second) = match options.timestamp {
+            Some(ts) => (ts.year, ts.month, ts.day, ts.hour, ts.minute, ts.second),
+            None => date.map_or((1970, 1, 1, 0, 0, 0), |(year, month, day)| {
+                (year, month, day, 0, 0, 0)
+            }),
+        };
+        writeln!(
+            buf,
+            "<meta property=\"dcterms:modified\">\
+             {year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z</meta>"
+        )
+        .unwrap();
+        buf.push_str("</metadata>\n");
+
+        buf.push_str("<manifest>\n");
+        buf.push_str(
+            "<item id=\"nav\" href=\"nav.xhtml\" \
+             media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+        );
+        buf.push_str("<item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n");
+        for i in 0..chapters {
+            writeln!(
+                buf,
+                "<item id=\"chapter-{}\" href=\"{}\" \
+                 media-type=\"application/xhtml+xml\"/>",
+                i + 1,
+                chapter_path(i),
+            )
+            .unwrap();
+        }
+        for (i, resource) in self.resources.iter().enumerate() {
+            writeln!(
+                buf,
+                "<item id=\"resource-{}\" href=\"{}\" media-type=\"{}\"/>",
+                i + 1,
+                resource.path,
+                resource.media_type,
+            )
+            .unwrap();
+        }
+        buf.push_str("</manifest>\n");
+
+        buf.push_str("<spine>\n");
+        for i in 0..chapters {
+            writeln!(buf, "<itemref idref=\"chapter-{}\"/>", i + 1).unwrap();
+        }
+        buf.push_str("</spine>\n</package>\n");
+        buf
+    }
+
+    /// Package an image once and return its path.
+    fn push_image(&mut self, image: &Image) -> String {
+        let hash = hash128(image);
+        if let Some(&index) = self.images.get(&hash) {
+            return self.resources[index].path.clone();
+        }
+
+        let (ext, media_type) = match image.format() {
+            ImageFormat::Raster(RasterFormat::Png) => ("png", "image/png"),
+            ImageFormat::Raster(RasterFormat::Jpg) => ("jpg", "image/jpeg"),
+            ImageFormat::Raster(RasterFormat::Gif) => ("gif", "image/gif"),
+            ImageFormat::Vector(VectorFormat::Svg) => ("svg", "image/svg+xml"),
+            ImageFormat::Vector(VectorFormat::Pdf) => ("pdf", "application/pdf"),
+        };
+
+        self.images.insert(hash, self.resources.len());
+        self.push_resource("image", ext, media_type, image.data().to_vec())
+    }
+
+    /// Add an image file to the package and return its path.
+    fn push_resource(
+        &mut self,
+        kind: &str,
+        ext: &str,
+        media_type: &'static str,
+        data: Vec<u8>,
+    ) -> String {
+        let path = format!("images/{kind}-{}.{ext}", self.resources.len() + 1);
+        self.resources.push(Resource { path: path.clone(), media_type, data });
+        path
+    }
+
+    /// Start an XHTML file up to the end of its head's title.
+    fn xhtml_head(&self, title: &str) -> String {
+        let lang = self.lang();
+        let mut buf = String::new();
+        buf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n");
+        writeln!(
+            buf,
+            "<html xmlns=\"http://www.w3.org/1999/xhtml\" \
+             xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{lang}\" \
+             xml:lang=\"{lang}\">"
+        )
+        .unwrap();
+        buf.push_str("<head>\n<meta charset=\"utf-8\"/>\n");
+        writeln!(buf, "<title>{}</title>", Escaped(title)).unwrap();
+        buf
+    }
+
+    /// The title of the e-book, which EPUB requires.
+    fn title(&self) -> String {
+        self.document.title.as_deref().unwrap_or("Untitled").into()
+    }
+
+    /// The language tag of the e-book, which EPUB requires.
+    fn lang(&self) -> String {
+        match (self.document.lang, self.document.region) {
+            (Some(lang), Some(region)) => {
+                format!("{}-{}", lang.as_str(), region.as_str())
+            }
+            (Some(lang), None) => lang.as_str().into(),
+            _ => "und".into(),
+        }
+    }
+}
+
+impl Embed for EpubContext<'_> {
+    fn frame(&mut self, frame: &Frame) -> EcoString {
+        let data = svg(frame).into_bytes();
+        self.push_resource("frame", "svg", "image/svg+xml", data).into()
+    }
+
+    fn image(&mut self, image: &Image) -> EcoString {
+        self.push_image(image).into()
+    }
+
+    fn href(&self, href: &EcoString) -> EcoString {
+        // Links to elements point into the chapters that hold them.
+        match href.strip_prefix('#').and_then(|anchor| self.anchors.get(anchor)) {
+            Some(&chapter) => eco_format!("{}{href}", chapter_path(chapter)),
+            None => href.clone(),
+        }
+    }
+}
+
+/// Split the nodes of a document's body into chapters, each of which starts
+/// at a top-level heading.
+fn split_chapters(body: &[HtmlNode]) -> Vec<&[HtmlNode]> {
+    let mut chapters = vec![];
+    let mut start = 0;
+    for (i, node) in body.iter().enumerate() {
+        if matches!(node, HtmlNode::Element(elem) if elem.tag == "h1") && i > start {
+            chapters.push(&body[start..i]);
+            start = i;
+        }
+    }
+
+    chapters.push(&body[start..]);
+    chapters
+}
+
+/// Record the chapter of each element with an id.
+fn collect_anchors(
+    nodes: &[HtmlNode],
+    chapter: usize,
+    anchors: &mut HashMap<EcoString, usize>,
+) {
+    for node in nodes {
+        if let HtmlNode::Element(elem) = node {
+            if let Some(id) = elem.attr("id") {
+                anchors.insert(id.clone(), chapter);
+            }
+            collect_anchors(&elem.children, chapter, anchors);
+        }
+    }
+}
+
+/// Collect the headings of a chapter for the table of contents.
+fn collect_headings(nodes: &[HtmlNode], chapter: usize, items: &mut Vec<NavItem>) {
+    for node in nodes {
+        let HtmlNode::Element(elem) = node else { continue };
+        let Some(level) = heading_level(elem) else {
+            collect_headings(&elem.children, chapter, items);
+            continue;
+        };
+
+        let mut href = chapter_path(chapter);
+        if let Some(id) = elem.attr("id") {
+            write!(href, "#{id}").unwrap();
+        }
+
+        items.push(NavItem {
+            title: plain_text(&elem.children),
+            level,
+            href,
+            children: vec![],
+        });
+    }
+}
+
+/// Write the items of the table of contents as nested lists.
+fn write_nav_items(buf: &mut String, items: &[NavItem]) {
+    buf.push_str("<ol>\n");
+    for item in items {
+        write!(
+            buf,
+            "<li><a href=\"{}\">{}</a>",
+            Escaped(&item.href),
+            Escaped(&item.title)
+        )
+        .unwrap();
+        if !item.children.is_empty() {
+            buf.push('\n');
+            write_nav_items(buf, &item.children);
+        }
+        buf.push_str("</li>\n");
+    }
+    buf.push_str("</ol>\n");
+}
+
+/// The level of a heading element.
+fn heading_level(elem: &HtmlElement) -> Option<usize> {
+    match elem.tag.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => Some(usize::from(level - b'0')),
+        _ => None,
+    }
+}
+
+/// Collect the characters of the text in nodes.
+fn collect_chars(nodes: &[HtmlNode], chars: &mut BTreeSet<char>) {
+    for node in nodes {
+        match node {
+            HtmlNode::Text(text) => chars.extend(text.chars()),
+            HtmlNode::Element(elem) => collect_chars(&elem.children, chars),
+            HtmlNode::Frame(_) | HtmlNode::Image(_) => {}
+        }
+    }
+}
+
+/// The text of nodes on a single line.
+fn plain_text(nodes: &[HtmlNode]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        raw(node, &mut text);
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The path of a chapter's file, relative to the package document.
+fn chapter_path(chapter: usize) -> String {
+    format!("chapter-{}.xhtml", chapter + 1)
+}
+
+/// The file extension and media type of a font, if it can be packaged.
+///
+/// Fonts from collections are left out because style sheets cannot refer to
+/// a single font in a collection.
+fn font_type(font: &Font) -> Option<(&'static str, &'static str)> {
+    let data = font.data();
+    if font.index() != 0 || data.starts_with(b"ttcf") {
+        return None;
+    }
+
+    Some(if data.starts_with(b"OTTO") {
+        ("otf", "font/otf")
+    } else {
+        ("ttf", "font/ttf")
+    })
+}
+
+/// The data of a font as it is packaged, subset to the given characters if
+/// possible.
+///
+/// Reading systems shape the text themselves, but a subset has neither the
+/// layout tables that scripts like Arabic need for shaping nor the tables with
+/// color glyphs. Fonts that have any of them are packaged whole.
+fn embedded_font(font: &Font, chars: &BTreeSet<char>) -> Vec<u8> {
+    let face = font.ttf().raw_face();
+    if [b"GSUB", b"GPOS", b"morx", b"COLR", b"SVG ", b"sbix", b"CBDT"]
+        .into_iter()
+        .any(|tag| face.table(Tag::from_bytes(tag)).is_some())
+    {
+        return font.data().to_vec();
+    }
+
+    let glyphs: Vec<_> = chars
+        .iter()
+        .filter_map(|&c| font.ttf().glyph_index(c))
+        .map(|id| id.0)
+        .collect();
+    let profile = subsetter::Profile::pdf(&glyphs);
+    subsetter::subset(font.data(), font.index(), profile)
+        .unwrap_or_else(|_| font.data().to_vec())
+}
+
+/// Format a hash as a UUID.
+fn uuid(hash: u128) -> String {
+    // Mark the UUID as random (version 4, variant 1).
+    let hash = (hash & !(0xf << 76) & !(0x3 << 62)) | (0x4 << 76) | (0x2 << 62);
+    let hex = format!("{hash:032x}");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Buffer;
+
+    fn node(tag: &str) -> HtmlNode {
+        HtmlNode::Element(HtmlElement::new(tag))
+    }
+
+    fn link(href: &str) -> HtmlNode {
+        HtmlNode::Element(HtmlElement::new("a").with_attr("href", href))
+    }
+
+    #[test]
+    fn test_epub_split_chapters() {
+        let lens = |body: &[HtmlNode]| -> Vec<usize> {
+            split_chapters(body).iter().map(|chapter| chapter.len()).collect()
+        };
+
+        assert_eq!(lens(&[]), [0]);
+        assert_eq!(lens(&[node("h1"), node("p"), node("h2"), node("p")]), [4]);
+        assert_eq!(
+            lens(&[node("p"), node("h1"), node("p"), node("h2"), node("h1")]),
+            [1, 3, 1]
+        );
+
+        // Only top-level headings start chapters.
+        let section = HtmlElement::new("section").with_children(vec![node("h1")]);
+        assert_eq!(lens(&[node("h1"), HtmlNode::Element(section)]), [2]);
+    }
+
+    #[test]
+    fn test_epub_chapter_markup() {
+        let target = HtmlElement::new("h1").with_attr("id", "b");
+        let body = vec![
+            node("h1"),
+            link("#b"),
+            link("#missing"),
+            link("https://typst.app"),
+            node("br"),
+            HtmlNode::Element(target),
+        ];
+
+        let document = HtmlDocument { body, ..Default::default() };
+        let mut ctx = EpubContext::new(&document);
+        let chapters = split_chapters(&document.body);
+        for (i, chapter) in chapters.iter().enumerate() {
+            collect_anchors(chapter, i, &mut ctx.anchors);
+        }
+
+        let first = ctx.write_chapter(chapters[0]);
+        assert!(first.contains("<a href=\"chapter-2.xhtml#b\"></a>"));
+        assert!(first.contains("<a href=\"#missing\"></a>"));
+        assert!(first.contains("<a href=\"https://typst.app\"></a>"));
+        assert!(first.contains("<br/>"));
+    }
+
+    #[test]
+    fn test_epub_embedded_font() {
+        // The Arabic font needs its layout tables to be shaped.
+        let data = include_bytes!("../../../assets/fonts/NotoSansArabic-Regular.ttf");
+        let font = Font::new(Buffer::from(&data[..]), 0).unwrap();
+        let chars = "سلام".chars().collect();
+        assert_eq!(embedded_font(&font, &chars), &data[..]);
+    }
+
+    #[test]
+    fn test_epub_modified_date() {
+        let document = HtmlDocument::default();
+        let ctx = EpubContext::new(&document);
+        let modified = |options: &EpubOptions| {
+            let package = ctx.write_package(1, options);
+            let start = package.find("<meta property=\"dcterms:modified\">").unwrap();
+            let end = start + package[start..].find("</meta>").unwrap();
+            package[start + 34..end].to_string()
+        };
+
+        assert_eq!(modified(&EpubOptions::default()), "1970-01-01T00:00:00Z");
+        let timestamp = PdfTimestamp::from_unix(86400 + 3661);
+        assert_eq!(modified(&EpubOptions { timestamp }), "1970-01-02T01:01:01Z");
+    }
+}
@@ -0,0 +1,192 @@
+use crate::diag::StrResult;
+
+/// The compression level for compressed files.
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// The version of the ZIP specification that is needed to extract the files.
+const VERSION: u16 = 20;
+
+/// General purpose flag that marks file names as UTF-8.
+const UTF8: u16 = 1 << 11;
+
+/// The DOS date of all files, which is the first of January 1980.
+///
+/// A fixed date keeps exporting the same document reproducible.
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// Writes a ZIP archive.
+#[derive(Default)]
+pub struct ZipWriter {
+    /// The bytes of the local files written so far.
+    buf: Vec<u8>,
+    /// The central directory entries of the files written so far.
+    central: Vec<u8>,
+    /// The number of files written so far.
+    len: u16,
+}
+
+impl ZipWriter {
+    /// Add a file as is.
+    pub fn stored(&mut self, name: &str, data: &[u8]) -> StrResult<()> {
+        self.write(name, data, data, 0)
+    }
+
+    /// Add a file compressed with deflate.
+    pub fn deflated(&mut self, name: &str, data: &[u8]) -> StrResult<()> {
+        let compressed = miniz_oxide::deflate::compress_to_vec(data, COMPRESSION_LEVEL);
+        self.write(name, data, &compressed, 8)
+    }
+
+    /// Write the central directory and return the finished archive.
+    pub fn finish(mut self) -> StrResult<Vec<u8>> {
+        let offset = size(self.buf.len())?;
+        let central = size(self.central.len())?;
+        self.buf.extend(&self.central);
+
+        // End of central directory record.
+        put_u32(&mut self.buf, 0x06054b50);
+        put_u16(&mut self.buf, 0);
+        put_u16(&mut self.buf, 0);
+        put_u16(&mut self.buf, self.len);
+        put_u16(&mut self.buf, self.len);
+        put_u32(&mut self.buf, central);
+        put_u32(&mut self.buf, offset);
+        put_u16(&mut self.buf, 0);
+        Ok(self.buf)
+    }
+
+    /// Write a local file header with the file's contents and remember its
+    /// central directory entry.
+    fn write(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        compressed: &[u8],
+        method: u16,
+    ) -> StrResult<()> {
+        // The highest count marks archives in the extended ZIP64 format.
+        let len = self
+            .len
+            .checked_add(1)
+            .filter(|&len| len < u16::MAX)
+            .ok_or("archive has too many files")?;
+        let offset = size(self.buf.len())?;
+        let compressed_size = size(compressed.len())?;
+        let data_size = size(data.len())?;
+        let name = name.as_bytes();
+        let name_len = u16::try_from(name.len()).map_err(|_| "file name is too long")?;
+
+        let mut crc = flate2::Crc::new();
+        crc.update(data);
+
+        // Local file header.
+        let buf = &mut self.buf;
+        put_u32(buf, 0x04034b50);
+        put_u16(buf, VERSION);
+        put_u16(buf, UTF8);
+        put_u16(buf, method);
+        put_u16(buf, 0);
+        put_u16(buf, DOS_DATE);
+        put_u32(buf, crc.sum());
+        put_u32(buf, compressed_size);
+        put_u32(buf, data_size);
+        put_u16(buf, name_len);
+        put_u16(buf, 0);
+        buf.extend(name);
+        buf.extend(compressed);
+
+        // Central directory file header.
+        let central = &mut self.central;
+        put_u32(central, 0x02014b50);
+        put_u16(central, VERSION);
+        put_u16(central, VERSION);
+        put_u16(central, UTF8);
+        put_u16(central, method);
+        put_u16(central, 0);
+        put_u16(central, DOS_DATE);
+        put_u32(central, crc.sum());
+        put_u32(central, compressed_size);
+        put_u32(central, data_size);
+        put_u16(central, name_len);
+        put_u16(central, 0);
+        put_u16(central, 0);
+        put_u16(central, 0);
+        put_u16(central, 0);
+        put_u32(central, 0);
+        put_u32(central, offset);
+        central.extend(name);
+
+        self.len = len;
+        Ok(())
+    }
+}
+
+/// Convert a size or offset into the 32 bits that the format allows.
+fn size(len: usize) -> StrResult<u32> {
+    u32::try_from(len).map_err(|_| "archive is too large".into())
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend(value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend(value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(data: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([data[at], data[at + 1]])
+    }
+
+    fn u32_at(data: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+    }
+
+    #[test]
+    fn test_zip_layout() {
+        let mut zip = ZipWriter::default();
+        zip.stored("mimetype", b"application/epub+zip").unwrap();
+        zip.deflated("a.txt", &[b'a'; 100]).unwrap();
+        let data = zip.finish().unwrap();
+
+        // Reading systems look for the media type right after the first
+        // local file header.
+        assert_eq!(&data[..4], b"PK\x03\x04");
+        assert_eq!(&data[30..38], b"mimetype");
+        assert_eq!(&data[38..58], b"application/epub+zip");
+
+        // The second file inflates back to its contents.
+        assert_eq!(u32_at(&data, 58), 0x04034b50);
+        assert_eq!(u16_at(&data, 66), 8);
+        assert_eq!(u32_at(&data, 80), 100);
+        let start = 58 + 30 + 5;
+        let end = start + u32_at(&data, 76) as usize;
+        let inflated =
+            miniz_oxide::inflate::decompress_to_vec(&data[start..end]).unwrap();
+        assert_eq!(inflated, [b'a'; 100]);
+
+        // The end of central directory record points to both entries.
+        let record = data.len() - 22;
+        assert_eq!(u32_at(&data, record), 0x06054b50);
+        assert_eq!(u16_at(&data, record + 10), 2);
+        assert_eq!(u32_at(&data, record + 16) as usize, end);
+        assert_eq!(u32_at(&data, end), 0x02014b50);
+    }
+
+    #[test]
+    fn test_zip_limits() {
+        let mut zip = ZipWriter::default();
+        let name = "a".repeat(usize::from(u16::MAX) + 1);
+        assert_eq!(zip.stored(&name, b""), Err("file name is too long".into()));
+
+        for _ in 0..u16::MAX - 1 {
+            zip.stored("a", b"").unwrap();
+        }
+        assert_eq!(zip.stored("a", b""), Err("archive has too many files".into()));
+        assert!(zip.finish().is_ok());
+    }
+}
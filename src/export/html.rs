@@ -0,0 +1,362 @@
+//! Exporting into HTML documents.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use ecow::{eco_format, EcoString};
+
+use super::svg::{data_url, svg};
+use crate::doc::{Date, Frame, Lang, Region};
+use crate::font::Font;
+use crate::geom::Smart;
+use crate::image::Image;
+
+/// A document in the form of a tree of semantic HTML elements.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct HtmlDocument {
+    /// The document's title.
+    pub title: Option<EcoString>,
+    /// The document's author.
+    pub author: Vec<EcoString>,
+    /// A short description of the document's contents.
+    pub description: Option<EcoString>,
+    /// Keywords that help to find the document.
+    pub keywords: Vec<EcoString>,
+    /// The document's date, which is derived from the time of export if
+    /// `Auto`.
+    pub date: Smart<Option<Date>>,
+    /// The document's main language.
+    pub lang: Option<Lang>,
+    /// The region of the document's main language.
+    pub region: Option<Region>,
+    /// The fonts used by the document's text, in order of first use.
+    pub fonts: Vec<Font>,
+    /// The contents of the document's body.
+    pub body: Vec<HtmlNode>,
+}
+
+/// A node in an HTML tree.
+#[derive(Debug, Clone, Hash)]
+pub enum HtmlNode {
+    /// Plain text.
+    Text(EcoString),
+    /// An element with attributes and children.
+    Element(HtmlElement),
+    /// Laid-out content without an HTML equivalent, like an equation or a
+    /// shape. It is embedded as an SVG image.
+    Frame(Frame),
+    /// An image in a format that browsers can display.
+    Image(Image),
+}
+
+/// An HTML element.
+#[derive(Debug, Clone, Hash)]
+pub struct HtmlElement {
+    /// The element's tag name, like `p`.
+    pub tag: EcoString,
+    /// The element's attributes as pairs of names and values.
+    pub attrs: Vec<(EcoString, EcoString)>,
+    /// The element's children.
+    pub children: Vec<HtmlNode>,
+}
+
+impl HtmlElement {
+    /// Create an element without attributes and children.
+    pub fn new(tag: impl Into<EcoString>) -> Self {
+        Self { tag: tag.into(), attrs: vec![], children: vec![] }
+    }
+
+    /// Add an attribute to the element.
+    pub fn with_attr(
+        mut self,
+        name: impl Into<EcoString>,
+        value: impl Into<EcoString>,
+    ) -> Self {
+        self.attrs.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the element's children.
+    pub fn with_children(mut self, children: Vec<HtmlNode>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// The value of one of the element's attributes.
+    pub fn attr(&self, name: &str) -> Option<&EcoString> {
+        self.attrs.iter().find(|(key, _)| key == name).map(|(_, value)| value)
+    }
+
+    /// Whether the element starts a new block instead of flowing within a
+    /// line of text.
+    pub fn is_block(&self) -> bool {
+        matches!(
+            self.tag.as_str(),
+            "address"
+                | "article"
+                | "aside"
+                | "blockquote"
+                | "dd"
+                | "div"
+                | "dl"
+                | "dt"
+                | "figcaption"
+                | "figure"
+                | "footer"
+                | "h1"
+                | "h2"
+                | "h3"
+                | "h4"
+                | "h5"
+                | "h6"
+                | "header"
+                | "hr"
+                | "li"
+                | "main"
+                | "nav"
+                | "ol"
+                | "p"
+                | "pre"
+                | "section"
+                | "table"
+                | "tbody"
+                | "td"
+                | "th"
+                | "thead"
+                | "tr"
+                | "ul"
+        )
+    }
+
+    /// Whether the element never has children and thus no closing tag.
+    pub fn is_void(&self) -> bool {
+        matches!(
+            self.tag.as_str(),
+            "area"
+                | "base"
+                | "br"
+                | "col"
+                | "embed"
+                | "hr"
+                | "img"
+                | "input"
+                | "link"
+                | "meta"
+                | "source"
+                | "track"
+                | "wbr"
+        )
+    }
+}
+
+/// Export a document into an HTML file.
+///
+/// Returns the markup of a complete, standalone page. Content without an HTML
+/// equivalent is embedded as SVG images.
+pub fn html(document: &HtmlDocument) -> String {
+    let mut buf = String::new();
+    buf.push_str("<!DOCTYPE html>\n");
+    match (document.lang, document.region) {
+        (Some(lang), Some(region)) => {
+            writeln!(buf, "<html lang=\"{}-{}\">", lang.as_str(), region.as_str())
+                .unwrap();
+        }
+        (Some(lang), None) => {
+            writeln!(buf, "<html lang=\"{}\">", lang.as_str()).unwrap();
+        }
+        _ => buf.push_str("<html>\n"),
+    }
+
+    buf.push_str("<head>\n");
+    buf.push_str("<meta charset=\"utf-8\">\n");
+    buf.push_str(
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+    );
+
+    if let Some(title) = &document.title {
+        writeln!(buf, "<title>{}</title>", Escaped(title)).unwrap();
+    }
+
+    let mut meta = |name: &str, content: &str| {
+        writeln!(buf, "<meta name=\"{name}\" content=\"{}\">", Escaped(content)).unwrap();
+    };
+
+    if !document.author.is_empty() {
+        meta("author", &document.author.join(", "));
+    }
+    if let Some(description) = &document.description {
+        meta("description", description);
+    }
+    if !document.keywords.is_empty() {
+        meta("keywords", &document.keywords.join(", "));
+    }
+    meta("generator", "Typst");
+
+    buf.push_str("</head>\n<body>\n");
+    let mut embed = DataUrls;
+    let mut w = HtmlWriter::new(false, &mut embed);
+    for node in &document.body {
+        w.write_node(node);
+    }
+    buf.push_str(&w.finish());
+    buf.push_str("</body>\n</html>\n");
+    buf
+}
+
+/// Decides where the frames, images and links of written markup point to.
+pub(super) trait Embed {
+    /// The source of the SVG image that shows a frame.
+    fn frame(&mut self, frame: &Frame) -> EcoString;
+
+    /// The source of an image.
+    fn image(&mut self, image: &Image) -> EcoString;
+
+    /// The target of a link.
+    fn href(&self, href: &EcoString) -> EcoString {
+        href.clone()
+    }
+}
+
+/// Embeds frames and images into standalone pages as data URLs.
+struct DataUrls;
+
+impl Embed for DataUrls {
+    fn frame(&mut self, frame: &Frame) -> EcoString {
+        eco_format!("data:image/svg+xml;base64,{}", base64::encode(svg(frame)))
+    }
+
+    fn image(&mut self, image: &Image) -> EcoString {
+        data_url(image).into()
+    }
+}
+
+/// Writes HTML nodes into a buffer.
+pub(super) struct HtmlWriter<'a> {
+    /// The markup written so far.
+    buf: String,
+    /// Whether to write XHTML, where void elements must be closed.
+    xhtml: bool,
+    /// Whether the writer is inside of preformatted text, where whitespace
+    /// matters.
+    pre: bool,
+    /// Decides where frames, images and links point to.
+    embed: &'a mut dyn Embed,
+}
+
+impl<'a> HtmlWriter<'a> {
+    /// Create a writer for HTML or XHTML markup.
+    pub fn new(xhtml: bool, embed: &'a mut dyn Embed) -> Self {
+        Self { buf: String::new(), xhtml, pre: false, embed }
+    }
+
+    /// Return the markup written so far.
+    pub fn finish(self) -> String {
+        self.buf
+    }
+
+    /// Write a node and, if it is a block, a newline after it.
+    pub fn write_node(&mut self, node: &HtmlNode) {
+        match node {
+            HtmlNode::Text(text) => write!(self.buf, "{}", Escaped(text)).unwrap(),
+            HtmlNode::Element(elem) => self.write_element(elem),
+            HtmlNode::Frame(frame) => self.write_frame(frame),
+            HtmlNode::Image(image) => self.write_image(image),
+        }
+    }
+
+    /// Write an element with its children.
+    fn write_element(&mut self, elem: &HtmlElement) {
+        write!(self.buf, "<{}", elem.tag).unwrap();
+        for (name, value) in &elem.attrs {
+            let value =
+                if name == "href" { self.embed.href(value) } else { value.clone() };
+            write!(self.buf, " {name}=\"{}\"", Escaped(&value)).unwrap();
+        }
+
+        if elem.is_void() {
+            let end = self.end();
+            self.buf.push_str(end);
+            self.newline(elem);
+            return;
+        }
+
+        self.buf.push('>');
+
+        // Put block children on their own lines to keep the markup readable.
+        let pre = self.pre;
+        self.pre |= elem.tag == "pre";
+        let blocks = elem.children.iter().any(|child| match child {
+            HtmlNode::Element(child) => child.is_block(),
+            _ => false,
+        });
+
+        if blocks && !self.pre {
+            self.buf.push('\n');
+        }
+
+        for child in &elem.children {
+            self.write_node(child);
+        }
+
+        self.pre = pre;
+        write!(self.buf, "</{}>", elem.tag).unwrap();
+        self.newline(elem);
+    }
+
+    /// Write laid-out content as an inline SVG image that sits on the
+    /// baseline.
+    fn write_frame(&mut self, frame: &Frame) {
+        let depth = frame.height() - frame.baseline();
+        let src = self.embed.frame(frame);
+        let end = self.end();
+        write!(
+            self.buf,
+            "<img src=\"{}\" style=\"vertical-align: -{:.2}pt\"{}",
+            Escaped(&src),
+            depth.to_pt(),
+            end,
+        )
+        .unwrap();
+    }
+
+    /// Write an image that is at most as wide as the page.
+    fn write_image(&mut self, image: &Image) {
+        let src = self.embed.image(image);
+        let end = self.end();
+        write!(self.buf, "<img src=\"{}\" style=\"max-width: 100%\"{end}", Escaped(&src))
+            .unwrap();
+    }
+
+    /// The end of a void element's tag.
+    fn end(&self) -> &'static str {
+        if self.xhtml {
+            "/>"
+        } else {
+            ">"
+        }
+    }
+
+    /// Write a newline after a block element outside of preformatted text.
+    fn newline(&mut self, elem: &HtmlElement) {
+        if elem.is_block() && !self.pre {
+            self.buf.push('\n');
+        }
+    }
+}
+
+/// Escapes text for use in HTML text and attribute values.
+pub(super) struct Escaped<'a>(pub &'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
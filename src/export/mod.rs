@@ -1,7 +1,15 @@
 //! Exporting into external formats.
 
+mod epub;
+mod html;
 mod pdf;
 mod render;
+mod svg;
+mod text;
 
-pub use self::pdf::pdf;
+pub use self::epub::{epub, EpubOptions};
+pub use self::html::{html, HtmlDocument, HtmlElement, HtmlNode};
+pub use self::pdf::{pdf, pdf_with_options, PdfOptions, PdfStandard, PdfTimestamp};
 pub use self::render::render;
+pub use self::svg::{svg, svg_with_links};
+pub use self::text::{markdown, text};
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use ecow::{eco_format, EcoString};
+use pdf_writer::writers::Catalog;
+use pdf_writer::{Filter, Finish, Name, Ref, Str, TextStr};
+
+use super::{deflate, PdfContext, RefExt};
+
+/// Embed all attached files into the PDF.
+///
+/// Different files with the same name are numbered to tell them apart.
+pub fn write_attachments(ctx: &mut PdfContext) {
+    let mut used = HashSet::new();
+    for attachment in ctx.attachment_map.items() {
+        let file_ref = ctx.alloc.bump();
+        let spec_ref = ctx.alloc.bump();
+        let name = unique_name(&attachment.name, &mut used);
+        ctx.attachment_refs.push(spec_ref);
+        ctx.attachment_names.push(name.clone());
+
+        let data = deflate(&attachment.data);
+        let mut file = ctx.writer.stream(file_ref, &data);
+        file.filter(Filter::FlateDecode);
+        file.pair(Name(b"Type"), Name(b"EmbeddedFile"));
+        if let Some(mime_type) = &attachment.mime_type {
+            file.pair(Name(b"Subtype"), Name(mime_type.as_bytes()));
+        }
+
+        let size = i32::try_from(attachment.data.len()).unwrap_or(i32::MAX);
+        file.insert(Name(b"Params")).dict().pair(Name(b"Size"), size);
+        file.finish();
+
+        let mut spec = ctx.writer.indirect(spec_ref).dict();
+        spec.pair(Name(b"Type"), Name(b"Filespec"));
+        spec.pair(Name(b"F"), Str(name.as_bytes()));
+        spec.pair(Name(b"UF"), TextStr(&name));
+        if let Some(description) = &attachment.description {
+            spec.pair(Name(b"Desc"), TextStr(description));
+        }
+
+        let mut files = spec.insert(Name(b"EF")).dict();
+        files.pair(Name(b"F"), file_ref);
+        files.pair(Name(b"UF"), file_ref);
+    }
+}
+
+/// Write the name tree that lists the attached files in the catalog.
+///
+/// The names in the tree must be unique and sorted by the bytes of the
+/// strings they are encoded as.
+pub fn write_embedded_files(
+    catalog: &mut Catalog,
+    file_names: &[EcoString],
+    refs: &[Ref],
+) {
+    if refs.is_empty() {
+        return;
+    }
+
+    let mut entries: Vec<_> = file_names.iter().zip(refs.iter().copied()).collect();
+    entries.sort_by_cached_key(|(name, _)| text_str_bytes(name));
+
+    let mut names = catalog.insert(Name(b"Names")).dict();
+    let mut tree = names.insert(Name(b"EmbeddedFiles")).dict();
+    let mut items = tree.insert(Name(b"Names")).array();
+    for (name, spec_ref) in entries {
+        items.item(TextStr(name));
+        items.item(spec_ref);
+    }
+}
+
+/// Make the name of a file unique among the already used names by numbering
+/// it in front of its extension.
+fn unique_name(name: &str, used: &mut HashSet<EcoString>) -> EcoString {
+    let (stem, extension) = match name.rfind('.') {
+        Some(i) if i > 0 => name.split_at(i),
+        _ => (name, ""),
+    };
+
+    let mut unique = EcoString::from(name);
+    let mut number = 2;
+    while !used.insert(unique.clone()) {
+        unique = eco_format!("{stem} ({number}){extension}");
+        number += 1;
+    }
+
+    unique
+}
+
+/// The bytes of a text string as they are encoded in the file.
+///
+/// Printable ASCII is written as is and everything else as UTF-16BE with a
+/// byte order mark.
+fn text_str_bytes(text: &str) -> Vec<u8> {
+    if text.bytes().all(|b| matches!(b, 32..=126)) {
+        return text.as_bytes().to_vec();
+    }
+
+    [0xfe, 0xff]
+        .into_iter()
+        .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_unique_name() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_name("data.csv", &mut used), "data.csv");
+        assert_eq!(unique_name("data.csv", &mut used), "data (2).csv");
+        assert_eq!(unique_name("data.csv", &mut used), "data (3).csv");
+        assert_eq!(unique_name("data (2).csv", &mut used), "data (2) (2).csv");
+        assert_eq!(unique_name(".hidden", &mut used), ".hidden");
+        assert_eq!(unique_name(".hidden", &mut used), ".hidden (2)");
+        assert_eq!(unique_name("notes", &mut used), "notes");
+        assert_eq!(unique_name("notes", &mut used), "notes (2)");
+    }
+
+    #[test]
+    fn test_pdf_text_str_bytes() {
+        assert_eq!(text_str_bytes("a.txt"), b"a.txt");
+        assert_eq!(text_str_bytes("ä"), [0xfe, 0xff, 0x00, 0xe4]);
+
+        // Encoded non-ASCII names sort behind ASCII ones even though they
+        // would sort in between as strings.
+        let mut names = vec!["b.txt", "ä.txt", "a.txt", "z.txt"];
+        names.sort_by_key(|name| text_str_bytes(name));
+        assert_eq!(names, ["a.txt", "b.txt", "z.txt", "ä.txt"]);
+    }
+}
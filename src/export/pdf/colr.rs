@@ -0,0 +1,696 @@
+//! Reading color glyphs from the `COLR` and `CPAL` tables.
+//!
+//! The layers of a color glyph are flattened into outlines that are filled
+//! with a color or gradient each. Linear and radial gradients become
+//! shadings, while sweep gradients are split into thin wedges of a single
+//! color. Gradients are always padded and variations are ignored.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use ttf_parser::Tag;
+
+use super::font::glyph_outline;
+use crate::font::Font;
+use crate::geom::{
+    self, Abs, Angle, Axes, Color, Gradient, LinearGradient, PathItem, Point,
+    RadialGradient, Ratio, RelativeTo, RgbaColor, Size, Smart, Transform,
+};
+
+/// The palette index that stands for the text's fill.
+const FOREGROUND: u16 = 0xFFFF;
+
+/// How deeply paints may be nested before we give up on a glyph.
+const MAX_DEPTH: usize = 64;
+
+/// How many wedges a sweep gradient is split into.
+const SWEEP_WEDGES: usize = 360;
+
+/// The stops of a gradient, sorted by their offsets.
+type Stops = Vec<(Color, Ratio)>;
+
+/// A layer of a color glyph.
+#[derive(Debug, Clone, Hash)]
+pub struct ColorLayer {
+    /// The layer's outline in font units with the y-axis pointing upwards.
+    pub path: geom::Path,
+    /// How to fill the outline.
+    pub fill: LayerFill,
+}
+
+/// How a layer of a color glyph is filled.
+#[derive(Debug, Clone, Hash)]
+pub enum LayerFill {
+    /// A single color or `None` if the layer should be filled with the text's
+    /// fill.
+    Solid(Option<RgbaColor>),
+    /// A gradient that stretches over a box of the given size. The transform
+    /// maps the box's coordinate system to font units.
+    Gradient(Gradient, Size, Transform),
+    /// Wedges in font units, each filled with one color, that approximate a
+    /// sweep gradient. They must be clipped to the layer's outline.
+    Wedges(Vec<(geom::Path, RgbaColor)>),
+}
+
+/// The layers of a glyph from the font's `COLR` table, bottom-most first.
+///
+/// Returns `None` if the glyph is not a color glyph.
+pub fn colr_layers(font: &Font, id: u16) -> Option<Vec<ColorLayer>> {
+    let face = font.ttf().raw_face();
+    let colr = Colr {
+        font,
+        data: face.table(Tag::from_bytes(b"COLR"))?,
+        palette: face.table(Tag::from_bytes(b"CPAL")).and_then(palette),
+    };
+
+    let mut layers = vec![];
+    match colr.base_paint(id) {
+        Some(paint) => colr.paint(paint, Transform::identity(), 0, &mut layers)?,
+        None => colr.v0_layers(id, &mut layers)?,
+    }
+
+    Some(layers)
+}
+
+/// A font's `COLR` table together with its first palette.
+struct Colr<'a> {
+    font: &'a Font,
+    data: &'a [u8],
+    palette: Option<Vec<RgbaColor>>,
+}
+
+impl Colr<'_> {
+    /// Collect the layers of a glyph from the version 0 records.
+    fn v0_layers(&self, id: u16, layers: &mut Vec<ColorLayer>) -> Option<()> {
+        let data = self.data;
+        let count = u16_at(data, 2)? as usize;
+        let base_records = u32_at(data, 4)? as usize;
+        let layer_records = u32_at(data, 8)? as usize;
+
+        let (first, len) = (0..count).find_map(|i| {
+            let record = base_records + 6 * i;
+            if u16_at(data, record)? != id {
+                return None;
+            }
+            Some((u16_at(data, record + 2)?, u16_at(data, record + 4)?))
+        })?;
+
+        for i in first..first.checked_add(len)? {
+            let offset = layer_records + 4 * i as usize;
+            let glyph = u16_at(data, offset)?;
+            let fill = LayerFill::Solid(self.color(u16_at(data, offset + 2)?, 1.0)?);
+            let path = glyph_outline(self.font, glyph, Transform::identity());
+            layers.push(ColorLayer { path, fill });
+        }
+
+        Some(())
+    }
+
+    /// The offset of a glyph's root paint in the version 1 base glyph list.
+    fn base_paint(&self, id: u16) -> Option<usize> {
+        let data = self.data;
+        if u16_at(data, 0)? < 1 {
+            return None;
+        }
+
+        let list = u32_at(data, 14)? as usize;
+        if list == 0 {
+            return None;
+        }
+
+        let count = u32_at(data, list)? as usize;
+        (0..count).find_map(|i| {
+            let record = list + 4 + 6 * i;
+            if u16_at(data, record)? != id {
+                return None;
+            }
+            Some(list + u32_at(data, record + 2)? as usize)
+        })
+    }
+
+    /// Collect the layers of a paint table under the given transform.
+    fn paint(
+        &self,
+        offset: usize,
+        ts: Transform,
+        depth: usize,
+        layers: &mut Vec<ColorLayer>,
+    ) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let data = self.data;
+        let depth = depth + 1;
+        let child = || Some(offset + u24_at(data, offset + 1)? as usize);
+        match u8_at(data, offset)? {
+            // PaintColrLayers
+            1 => {
+                let len = u8_at(data, offset + 1)? as usize;
+                let first = u32_at(data, offset + 2)? as usize;
+                let list = u32_at(data, 18)? as usize;
+                for i in first..first + len {
+                    let paint = list + u32_at(data, list + 4 + 4 * i)? as usize;
+                    self.paint(paint, ts, depth, layers)?;
+                }
+                Some(())
+            }
+            // PaintGlyph
+            10 => {
+                let glyph = u16_at(data, offset + 4)?;
+                let path = glyph_outline(self.font, glyph, ts);
+                let fill = self.fill(child()?, ts, &path, depth)?;
+                layers.push(ColorLayer { path, fill });
+                Some(())
+            }
+            // PaintColrGlyph
+            11 => {
+                let paint = self.base_paint(u16_at(data, offset + 1)?)?;
+                self.paint(paint, ts, depth, layers)
+            }
+            // The transforms.
+            12..=31 => {
+                let local = self.transform(offset)?;
+                self.paint(child()?, ts.pre_concat(local), depth, layers)
+            }
+            // PaintComposite, which we approximate by drawing the source over
+            // the backdrop.
+            32 => {
+                let backdrop = offset + u24_at(data, offset + 5)? as usize;
+                self.paint(backdrop, ts, depth, layers)?;
+                self.paint(child()?, ts, depth, layers)
+            }
+            // Fills without a glyph to clip them have no extent we could
+            // draw.
+            _ => None,
+        }
+    }
+
+    /// The transform of one of the transform paint tables.
+    fn transform(&self, offset: usize) -> Option<Transform> {
+        let data = self.data;
+        let fword = |at| Some(Abs::pt(i16_at(data, offset + at)?.into()));
+        let f2dot14 = |at| f2dot14_at(data, offset + at);
+        let around = |at, local: Transform| {
+            let (cx, cy) = (fword(at)?, fword(at + 2)?);
+            Some(
+                Transform::translate(cx, cy)
+                    .pre_concat(local)
+                    .pre_concat(Transform::translate(-cx, -cy)),
+            )
+        };
+
+        Some(match u8_at(data, offset)? {
+            // PaintTransform and PaintVarTransform
+            12 | 13 => {
+                let affine = offset + u24_at(data, offset + 4)? as usize;
+                let fixed = |i: usize| fixed_at(data, affine + 4 * i);
+                Transform {
+                    sx: Ratio::new(fixed(0)?),
+                    ky: Ratio::new(fixed(1)?),
+                    kx: Ratio::new(fixed(2)?),
+                    sy: Ratio::new(fixed(3)?),
+                    tx: Abs::pt(fixed(4)?),
+                    ty: Abs::pt(fixed(5)?),
+                }
+            }
+            // PaintTranslate and PaintVarTranslate
+            14 | 15 => Transform::translate(fword(4)?, fword(6)?),
+            // PaintScale and PaintVarScale
+            16 | 17 => Transform::scale(Ratio::new(f2dot14(4)?), Ratio::new(f2dot14(6)?)),
+            // PaintScaleAroundCenter and PaintVarScaleAroundCenter
+            18 | 19 => around(
+                8,
+                Transform::scale(Ratio::new(f2dot14(4)?), Ratio::new(f2dot14(6)?)),
+            )?,
+            // PaintScaleUniform and PaintVarScaleUniform
+            20 | 21 => Transform::scale(Ratio::new(f2dot14(4)?), Ratio::new(f2dot14(4)?)),
+            // PaintScaleUniformAroundCenter and PaintVarScaleUniformAroundCenter
+            22 | 23 => around(
+                6,
+                Transform::scale(Ratio::new(f2dot14(4)?), Ratio::new(f2dot14(4)?)),
+            )?,
+            // PaintRotate and PaintVarRotate
+            24 | 25 => Transform::rotate(Angle::rad(f2dot14(4)? * PI)),
+            // PaintRotateAroundCenter and PaintVarRotateAroundCenter
+            26 | 27 => around(6, Transform::rotate(Angle::rad(f2dot14(4)? * PI)))?,
+            // PaintSkew and PaintVarSkew
+            28 | 29 => skew(f2dot14(4)?, f2dot14(6)?),
+            // PaintSkewAroundCenter and PaintVarSkewAroundCenter
+            30 | 31 => around(8, skew(f2dot14(4)?, f2dot14(6)?))?,
+            _ => return None,
+        })
+    }
+
+    /// Resolve the paint that fills a glyph's outline, which lies in font
+    /// units, under the given transform.
+    fn fill(
+        &self,
+        offset: usize,
+        ts: Transform,
+        path: &geom::Path,
+        depth: usize,
+    ) -> Option<LayerFill> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let data = self.data;
+        let format = u8_at(data, offset)?;
+        let fword = |at| Some(f64::from(i16_at(data, offset + at)?));
+        let point = |at| Some((fword(at)?, fword(at + 2)?));
+        let line = || {
+            let line = offset + u24_at(data, offset + 1)? as usize;
+            self.color_line(line, format % 2 == 1)
+        };
+
+        match format {
+            // PaintSolid and PaintVarSolid
+            2 | 3 => Some(LayerFill::Solid(
+                self.color(u16_at(data, offset + 1)?, f2dot14_at(data, offset + 3)?)?,
+            )),
+            // PaintLinearGradient and PaintVarLinearGradient
+            4 | 5 => {
+                let (stops, bounds) = line()?;
+                let (p0, p1, p2) = (point(4)?, point(8)?, point(12)?);
+                Some(linear(stops, bounds, p0, p1, p2, ts))
+            }
+            // PaintRadialGradient and PaintVarRadialGradient
+            6 | 7 => {
+                let (stops, bounds) = line()?;
+                let (r0, r1) = (u16_at(data, offset + 8)?, u16_at(data, offset + 14)?);
+                let (r0, r1) = (f64::from(r0), f64::from(r1));
+                Some(radial(stops, bounds, point(10)?, r0, r1, ts))
+            }
+            // PaintSweepGradient and PaintVarSweepGradient
+            8 | 9 => {
+                let (stops, bounds) = line()?;
+                let angle = |at| Some((f2dot14_at(data, offset + at)? + 1.0) * PI);
+                let angles = (angle(8)?, angle(10)?);
+                Some(sweep(stops, bounds, point(4)?, angles, ts, path))
+            }
+            // The transforms move the fill around.
+            12..=31 => {
+                let local = self.transform(offset)?;
+                let child = offset + u24_at(data, offset + 1)? as usize;
+                self.fill(child, ts.pre_concat(local), path, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Read a color line into stops with offsets normalized to the range from
+    /// zero to one and the original offsets at which that range starts and
+    /// ends.
+    ///
+    /// The text's fill can't be part of a gradient, so it is replaced by
+    /// black.
+    fn color_line(&self, offset: usize, var: bool) -> Option<(Stops, (f64, f64))> {
+        let data = self.data;
+        let stride = if var { 10 } else { 6 };
+        let count = u16_at(data, offset + 1)? as usize;
+        let mut stops = (0..count)
+            .map(|i| {
+                let stop = offset + 3 + stride * i;
+                let t = f2dot14_at(data, stop)?;
+                let alpha = f2dot14_at(data, stop + 4)?;
+                let black = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+                let color = self
+                    .color(u16_at(data, stop + 2)?, alpha)?
+                    .unwrap_or(RgbaColor::new(0, 0, 0, black));
+                Some((Color::Rgba(color), t))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        stops.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let min = stops.first().map_or(0.0, |&(_, t)| t);
+        let max = stops.last().map_or(0.0, |&(_, t)| t);
+        let span = max - min;
+        let stops = stops
+            .into_iter()
+            .map(|(color, t)| {
+                let t = if span > 0.0 { (t - min) / span } else { 0.0 };
+                (color, Ratio::new(t))
+            })
+            .collect();
+
+        Some((stops, (min, max)))
+    }
+
+    /// Look up a color in the palette and scale its alpha.
+    ///
+    /// Returns `Some(None)` for the text's fill.
+    fn color(&self, index: u16, alpha: f64) -> Option<Option<RgbaColor>> {
+        if index == FOREGROUND {
+            return Some(None);
+        }
+
+        let mut color = *self.palette.as_ref()?.get(index as usize)?;
+        color.a = (f64::from(color.a) * alpha.clamp(0.0, 1.0)).round() as u8;
+        Some(Some(color))
+    }
+}
+
+/// Read the colors of the first palette from a `CPAL` table.
+fn palette(data: &[u8]) -> Option<Vec<RgbaColor>> {
+    let len = u16_at(data, 2)? as usize;
+    let records = u32_at(data, 8)? as usize;
+    let first = u16_at(data, 12)? as usize;
+    (0..len)
+        .map(|i| {
+            let record = records + 4 * (first + i);
+            let [b, g, r, a] = data.get(record..record + 4)?.try_into().ok()?;
+            Some(RgbaColor::new(r, g, b, a))
+        })
+        .collect()
+}
+
+/// A linear gradient from `p0` to `p1`, whose lines of equal color are
+/// parallel to the line from `p0` to `p2`, under the given transform.
+fn linear(
+    stops: Stops,
+    (min, max): (f64, f64),
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    ts: Transform,
+) -> LayerFill {
+    // Project the end point onto the normal of the lines of equal color.
+    let (mut dx, mut dy) = (p1.0 - p0.0, p1.1 - p0.1);
+    let (vx, vy) = (p2.0 - p0.0, p2.1 - p0.1);
+    let norm = vx * vx + vy * vy;
+    if norm > 0.0 {
+        let t = (dx * vx + dy * vy) / norm;
+        dx -= t * vx;
+        dy -= t * vy;
+    }
+
+    let len = dx.hypot(dy) * (max - min);
+    if stops.len() < 2 || len <= 0.0 {
+        return solid(&stops);
+    }
+
+    // The gradient's box is a square whose horizontal center line runs along
+    // the gradient's axis.
+    let (x, y) = (p0.0 + dx * min, p0.1 + dy * min);
+    let transform = ts
+        .pre_concat(Transform::translate(Abs::pt(x), Abs::pt(y)))
+        .pre_concat(Transform::rotate(Angle::rad(dy.atan2(dx))))
+        .pre_concat(Transform::translate(Abs::zero(), Abs::pt(-len / 2.0)));
+
+    let gradient = Gradient::Linear(Arc::new(LinearGradient {
+        stops,
+        angle: Angle::zero(),
+        relative: Smart::Custom(RelativeTo::Shape),
+    }));
+
+    LayerFill::Gradient(gradient, Size::splat(Abs::pt(len)), transform)
+}
+
+/// A radial gradient between two circles under the given transform.
+///
+/// Shadings can only be concentric here, so the circles are both centered
+/// on the second one's center. Their radii are kept.
+fn radial(
+    stops: Stops,
+    (min, max): (f64, f64),
+    center: (f64, f64),
+    r0: f64,
+    r1: f64,
+    ts: Transform,
+) -> LayerFill {
+    let mut stops: Stops = stops
+        .into_iter()
+        .map(|(color, s)| {
+            let t = min + s.get() * (max - min);
+            (color, Ratio::new((r0 + t * (r1 - r0)).max(0.0)))
+        })
+        .collect();
+
+    let radius = stops.iter().map(|&(_, r)| r.get()).fold(0.0, f64::max);
+    if stops.len() < 2 || radius <= 0.0 {
+        return solid(&stops);
+    }
+
+    for (_, r) in &mut stops {
+        *r /= radius;
+    }
+    stops.sort_by(|a, b| a.1.get().total_cmp(&b.1.get()));
+
+    let transform = ts.pre_concat(Transform::translate(
+        Abs::pt(center.0 - radius),
+        Abs::pt(center.1 - radius),
+    ));
+
+    let gradient = Gradient::Radial(Arc::new(RadialGradient {
+        stops,
+        center: Axes::splat(Ratio::new(0.5)),
+        radius: Ratio::new(0.5),
+        relative: Smart::Custom(RelativeTo::Shape),
+    }));
+
+    LayerFill::Gradient(gradient, Size::splat(Abs::pt(2.0 * radius)), transform)
+}
+
+/// A sweep gradient around a center between two counter-clockwise angles
+/// under the given transform.
+///
+/// The wedges reach just beyond the outline, which lies in font units.
+fn sweep(
+    stops: Stops,
+    (min, max): (f64, f64),
+    center: (f64, f64),
+    (start, end): (f64, f64),
+    ts: Transform,
+    path: &geom::Path,
+) -> LayerFill {
+    let Some(inverse) = ts.invert() else { return solid(&stops) };
+    if stops.len() < 2 || start == end {
+        return solid(&stops);
+    }
+
+    let radius = path
+        .0
+        .iter()
+        .flat_map(|item| match *item {
+            PathItem::MoveTo(p) | PathItem::LineTo(p) => vec![p],
+            PathItem::CubicTo(p1, p2, p3) => vec![p1, p2, p3],
+            PathItem::ClosePath => vec![],
+        })
+        .map(|p| {
+            let p = p.transform(inverse);
+            (p.x.to_pt() - center.0).hypot(p.y.to_pt() - center.1)
+        })
+        .fold(0.0, f64::max)
+        * 1.01
+        + 1.0;
+
+    let gradient = Gradient::Linear(Arc::new(LinearGradient {
+        stops,
+        angle: Angle::zero(),
+        relative: Smart::Auto,
+    }));
+
+    // Neighbouring wedges overlap slightly so that no seams show between
+    // them.
+    let step = 2.0 * PI / SWEEP_WEDGES as f64;
+    let at = |angle: f64| {
+        Point::new(
+            Abs::pt(center.0 + radius * angle.cos()),
+            Abs::pt(center.1 + radius * angle.sin()),
+        )
+        .transform(ts)
+    };
+
+    let wedges = (0..SWEEP_WEDGES)
+        .map(|i| {
+            let from = i as f64 * step;
+            let t = (from + step / 2.0 - start) / (end - start);
+            let s = if max > min { (t - min) / (max - min) } else { 0.0 };
+            let color = gradient.sample(Ratio::new(s)).to_rgba();
+
+            let mut wedge = geom::Path::new();
+            wedge.move_to(Point::new(Abs::pt(center.0), Abs::pt(center.1)).transform(ts));
+            wedge.line_to(at(from));
+            wedge.line_to(at(from + step * 1.5));
+            wedge.close_path();
+            (wedge, color)
+        })
+        .collect();
+
+    LayerFill::Wedges(wedges)
+}
+
+/// Fill with the last stop of a gradient that can't be drawn as such.
+fn solid(stops: &[(Color, Ratio)]) -> LayerFill {
+    let transparent = RgbaColor::new(0, 0, 0, 0);
+    LayerFill::Solid(Some(stops.last().map_or(transparent, |&(c, _)| c.to_rgba())))
+}
+
+/// A skew transform with angles in half turns.
+fn skew(x: f64, y: f64) -> Transform {
+    Transform {
+        ky: Ratio::new((y * PI).tan()),
+        kx: Ratio::new(-(x * PI).tan()),
+        ..Transform::identity()
+    }
+}
+
+fn u8_at(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn i16_at(data: &[u8], offset: usize) -> Option<i16> {
+    Some(i16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u24_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 3)?;
+    Some(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+/// Read a signed 2.14 fixed-point number.
+fn f2dot14_at(data: &[u8], offset: usize) -> Option<f64> {
+    Some(f64::from(i16_at(data, offset)?) / 16384.0)
+}
+
+/// Read a signed 16.16 fixed-point number.
+fn fixed_at(data: &[u8], offset: usize) -> Option<f64> {
+    let bits = u32_at(data, offset)? as i32;
+    Some(f64::from(bits) / 65536.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Buffer;
+
+    const RED: RgbaColor = RgbaColor::new(255, 0, 0, 255);
+    const BLUE: RgbaColor = RgbaColor::new(0, 0, 255, 255);
+
+    fn f2dot14(v: f64) -> i16 {
+        (v * 16384.0) as i16
+    }
+
+    /// A color line with the given offsets and palette indices.
+    fn line(var: bool, stops: &[(f64, u16)]) -> Vec<u8> {
+        let mut data = vec![0];
+        data.extend((stops.len() as u16).to_be_bytes());
+        for &(t, index) in stops {
+            data.extend(f2dot14(t).to_be_bytes());
+            data.extend(index.to_be_bytes());
+            data.extend(f2dot14(1.0).to_be_bytes());
+            if var {
+                data.extend([0; 4]);
+            }
+        }
+        data
+    }
+
+    /// A gradient paint with the given arguments, directly followed by its
+    /// color line.
+    fn paint(format: u8, args: &[i16], line: Vec<u8>) -> Vec<u8> {
+        let offset = 4 + 2 * args.len() as u32 + 4;
+        let mut data = vec![format];
+        data.extend(&offset.to_be_bytes()[1..]);
+        data.extend(args.iter().flat_map(|arg| arg.to_be_bytes()));
+        data.extend([0; 4]);
+        data.extend(line);
+        data
+    }
+
+    fn fill(data: &[u8]) -> LayerFill {
+        let font = include_bytes!("../../../assets/fonts/PTSans-Regular.ttf");
+        let font = Font::new(Buffer::from(&font[..]), 0).unwrap();
+        let colr = Colr { font: &font, data, palette: Some(vec![RED, BLUE]) };
+        let path = geom::Path::rect(Size::splat(Abs::pt(100.0)));
+        colr.fill(0, Transform::identity(), &path, 0).unwrap()
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_colr_linear_gradient() {
+        let stops = line(false, &[(0.0, 0), (1.0, 1)]);
+        let LayerFill::Gradient(Gradient::Linear(linear), size, ts) =
+            fill(&paint(4, &[0, 0, 100, 0, 0, 100], stops))
+        else {
+            panic!("expected a linear gradient")
+        };
+
+        assert_eq!(linear.stops[0], (Color::Rgba(RED), Ratio::zero()));
+        assert_eq!(linear.stops[1], (Color::Rgba(BLUE), Ratio::one()));
+        assert_eq!(size, Size::splat(Abs::pt(100.0)));
+        assert!(approx(ts.ty.to_pt(), -50.0));
+
+        // Lines of equal color along the diagonal make the gradient run
+        // perpendicular to it, which shortens it.
+        let stops = line(true, &[(0.0, 0), (1.0, 1)]);
+        let LayerFill::Gradient(_, size, ts) =
+            fill(&paint(5, &[0, 0, 100, 0, 100, 100], stops))
+        else {
+            panic!("expected a linear gradient")
+        };
+
+        assert!(approx(size.x.to_pt(), 50.0 * 2.0_f64.sqrt()));
+        assert!(approx(ts.ky.get(), -(0.5_f64).sqrt()));
+    }
+
+    #[test]
+    fn test_colr_radial_gradient() {
+        let stops = line(false, &[(0.0, 0), (0.5, 1)]);
+        let LayerFill::Gradient(Gradient::Radial(radial), size, ts) =
+            fill(&paint(6, &[0, 0, 0, 10, 20, 100], stops))
+        else {
+            panic!("expected a radial gradient")
+        };
+
+        assert_eq!(radial.stops[0], (Color::Rgba(RED), Ratio::zero()));
+        assert_eq!(radial.stops[1], (Color::Rgba(BLUE), Ratio::one()));
+        assert_eq!(size, Size::splat(Abs::pt(100.0)));
+        assert!(approx(ts.tx.to_pt(), -40.0));
+        assert!(approx(ts.ty.to_pt(), -30.0));
+    }
+
+    #[test]
+    fn test_colr_sweep_gradient() {
+        let stops = line(false, &[(0.0, 0), (1.0, 0xFFFF)]);
+        let LayerFill::Wedges(wedges) =
+            fill(&paint(8, &[50, 50, f2dot14(-1.0), f2dot14(1.0)], stops))
+        else {
+            panic!("expected wedges")
+        };
+
+        // The sweep starts red and ends in the text's fill, which becomes
+        // black.
+        assert_eq!(wedges.len(), SWEEP_WEDGES);
+        assert!(wedges[0].1.r > 250 && wedges[0].1.b == 0);
+        assert_eq!(wedges[SWEEP_WEDGES - 1].1.a, 255);
+        assert!(wedges[SWEEP_WEDGES - 1].1.r < 5);
+    }
+
+    #[test]
+    fn test_colr_degenerate_gradients() {
+        let stops = line(false, &[(0.5, 1)]);
+        assert!(matches!(
+            fill(&paint(4, &[0, 0, 100, 0, 0, 100], stops)),
+            LayerFill::Solid(Some(BLUE)),
+        ));
+
+        let stops = line(false, &[(0.0, 0), (1.0, 1)]);
+        assert!(matches!(
+            fill(&paint(6, &[0, 0, 0, 0, 0, 0], stops)),
+            LayerFill::Solid(Some(BLUE)),
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+use pdf_writer::Name;
+
+use super::{PdfContext, RefExt};
+
+/// An external graphics state that sets the opacity of strokes and fills.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ExtGState {
+    /// The opacity of strokes, from 0 (transparent) to 255 (opaque).
+    pub stroke_opacity: u8,
+    /// The opacity of fills, from 0 (transparent) to 255 (opaque).
+    pub fill_opacity: u8,
+    /// The soft mask that carries the opacity of a gradient with translucent
+    /// stops, if any.
+    pub soft_mask: Option<usize>,
+}
+
+impl Default for ExtGState {
+    fn default() -> Self {
+        Self {
+            stroke_opacity: 255,
+            fill_opacity: 255,
+            soft_mask: None,
+        }
+    }
+}
+
+/// Embed all used external graphics states into the PDF.
+///
+/// Must be called after the soft masks were written.
+pub fn write_external_graphics_states(ctx: &mut PdfContext) {
+    for external_gs in ctx.ext_gs_map.items() {
+        let gs_ref = ctx.alloc.bump();
+        ctx.ext_gs_refs.push(gs_ref);
+
+        let mut gs = ctx.writer.ext_graphics(gs_ref);
+        gs.non_stroking_alpha(external_gs.fill_opacity as f32 / 255.0)
+            .stroking_alpha(external_gs.stroke_opacity as f32 / 255.0);
+
+        // A graphics state without a mask must explicitly remove the mask of
+        // the previous one.
+        match external_gs.soft_mask {
+            Some(index) => {
+                let mut mask = gs.insert(Name(b"SMask")).dict();
+                mask.pair(Name(b"Type"), Name(b"Mask"));
+                mask.pair(Name(b"S"), Name(b"Luminosity"));
+                mask.pair(Name(b"G"), ctx.soft_mask_refs[index]);
+            }
+            None => {
+                gs.pair(Name(b"SMask"), Name(b"None"));
+            }
+        }
+    }
+}
@@ -1,12 +1,19 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
 
-use ecow::eco_format;
+use ecow::{eco_format, EcoString};
 use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
-use pdf_writer::{Filter, Finish, Name, Rect, Str};
-use ttf_parser::{name_id, GlyphId, Tag};
+use pdf_writer::{Content, Filter, Finish, Name, Rect, Str};
+use ttf_parser::{name_id, GlyphId, OutlineBuilder, Tag};
 
-use super::{deflate, EmExt, PdfContext, RefExt};
-use crate::util::SliceExt;
+use super::colr::{colr_layers, ColorLayer};
+use super::{deflate, AbsExt, EmExt, PdfContext, RefExt};
+use crate::export::render::render_image_glyph;
+use crate::export::svg::svg_glyph_document;
+use crate::font::Font;
+use crate::geom::{self, Abs, PathItem, Point, Ratio, Size, Transform};
+use crate::image::{Image, ImageFormat, RasterFormat, VectorFormat};
+use crate::util::{hash128, SliceExt};
 
 /// Embed all used fonts into the PDF.
 pub fn write_fonts(ctx: &mut PdfContext) {
@@ -19,6 +26,12 @@ pub fn write_fonts(ctx: &mut PdfContext) {
         ctx.font_refs.push(type0_ref);
 
         let glyphs = &ctx.glyph_sets[font];
+
+        // Sort the glyphs so that the subset doesn't depend on the order in
+        // which they were used.
+        let mut subset: Vec<u16> = glyphs.iter().copied().collect();
+        subset.sort_unstable();
+
         let metrics = font.metrics();
         let ttf = font.ttf();
 
@@ -26,7 +39,7 @@ pub fn write_fonts(ctx: &mut PdfContext) {
             .find_name(name_id::POST_SCRIPT_NAME)
             .unwrap_or_else(|| "unknown".to_string());
 
-        let base_font = eco_format!("ABCDEF+{}", postscript_name);
+        let base_font = eco_format!("{}+{}", subset_tag(font, &subset), postscript_name);
         let base_font = Name(base_font.as_bytes());
         let cmap_name = Name(b"Custom");
         let system_info = SystemInfo {
@@ -96,13 +109,7 @@ pub fn write_fonts(ctx: &mut PdfContext) {
         flags.insert(FontFlags::SYMBOLIC);
         flags.insert(FontFlags::SMALL_CAP);
 
-        let global_bbox = ttf.global_bounding_box();
-        let bbox = Rect::new(
-            font.to_em(global_bbox.x_min).to_font_units(),
-            font.to_em(global_bbox.y_min).to_font_units(),
-            font.to_em(global_bbox.x_max).to_font_units(),
-            font.to_em(global_bbox.y_max).to_font_units(),
-        );
+        let bbox = font_bbox(font);
 
         let italic_angle = ttf.italic_angle().unwrap_or(0.0);
         let ascender = metrics.ascender.to_font_units();
@@ -130,30 +137,10 @@ pub fn write_fonts(ctx: &mut PdfContext) {
         font_descriptor.finish();
 
         // Compute a reverse mapping from glyphs to unicode.
-        let cmap = {
-            let mut mapping = BTreeMap::new();
-            for subtable in
-                ttf.tables().cmap.into_iter().flat_map(|table| table.subtables)
-            {
-                if subtable.is_unicode() {
-                    subtable.codepoints(|n| {
-                        if let Some(c) = std::char::from_u32(n) {
-                            if let Some(GlyphId(g)) = ttf.glyph_index(c) {
-                                if glyphs.contains(&g) {
-                                    mapping.insert(g, c);
-                                }
-                            }
-                        }
-                    });
-                }
-            }
-
-            let mut cmap = UnicodeCmap::new(cmap_name, system_info);
-            for (g, c) in mapping {
-                cmap.pair(g, c);
-            }
-            cmap
-        };
+        let mut cmap = UnicodeCmap::new(cmap_name, system_info);
+        for (g, c) in glyph_chars(font, glyphs) {
+            cmap.pair(g, c);
+        }
 
         // Write the /ToUnicode character map, which maps glyph ids back to
         // unicode codepoints to enable copying out of the PDF.
@@ -163,11 +150,8 @@ pub fn write_fonts(ctx: &mut PdfContext) {
 
         // Subset and write the font's bytes.
         let data = font.data();
-        let subsetted = {
-            let glyphs: Vec<_> = glyphs.iter().copied().collect();
-            let profile = subsetter::Profile::pdf(&glyphs);
-            subsetter::subset(data, font.index(), profile)
-        };
+        let profile = subsetter::Profile::pdf(&subset);
+        let subsetted = subsetter::subset(data, font.index(), profile);
 
         // Compress and write the font's bytes.
         let data = subsetted.as_deref().unwrap_or(data);
@@ -182,3 +166,308 @@ pub fn write_fonts(ctx: &mut PdfContext) {
         stream.finish();
     }
 }
+
+/// Embed the glyphs of variable font instances as Type3 fonts.
+///
+/// The glyphs are drawn from the instanced outlines, so that they look like
+/// they did during layout.
+pub fn write_type3_fonts(ctx: &mut PdfContext) {
+    for (font, glyphs) in &ctx.type3_map.fonts {
+        let type3_ref = ctx.alloc.bump();
+        let cmap_ref = ctx.alloc.bump();
+        ctx.type3_refs.push(type3_ref);
+
+        // Glyph space has 1000 units per em.
+        let scale = 1000.0 / font.units_per_em();
+        let ts = Transform::scale(Ratio::new(scale), Ratio::new(scale));
+
+        // Write a content stream that draws each glyph.
+        let mut widths = vec![];
+        let mut procs = vec![];
+        for &g in glyphs {
+            let width = font.advance(g).unwrap_or_default().to_font_units();
+            let bbox = font.ttf().glyph_bounding_box(GlyphId(g));
+            let [x_min, y_min, x_max, y_max] = bbox.map_or([0.0; 4], |bbox| {
+                [bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max]
+                    .map(|v| (f64::from(v) * scale) as f32)
+            });
+
+            let mut content = Content::new();
+            content.start_shape_glyph(width, x_min, y_min, x_max, y_max);
+            let path = glyph_outline(font, g, ts);
+            if !path.0.is_empty() {
+                write_path(&mut content, &path);
+                content.fill_nonzero();
+            }
+
+            let proc_ref = ctx.alloc.bump();
+            ctx.writer
+                .stream(proc_ref, &deflate(&content.finish()))
+                .filter(Filter::FlateDecode);
+
+            widths.push(width);
+            procs.push(proc_ref);
+        }
+
+        let glyph_names: Vec<_> =
+            (0..glyphs.len()).map(|code| eco_format!("g{code}")).collect();
+        let names = || glyph_names.iter().map(|name| Name(name.as_bytes()));
+
+        let mut type3 = ctx.writer.type3_font(type3_ref);
+        type3.bbox(font_bbox(font));
+        type3.matrix([0.001, 0.0, 0.0, 0.001, 0.0, 0.0]);
+        type3.first_char(0);
+        type3.last_char((glyphs.len() - 1) as u8);
+        type3.widths(widths);
+        type3.encoding_custom().differences().consecutive(0, names());
+        type3.char_procs().pairs(names().zip(procs));
+        type3.to_unicode(cmap_ref);
+        type3.finish();
+
+        // Write the /ToUnicode character map. Type3 fonts have single-byte
+        // codes, which the CID-keyed character maps of `pdf-writer` don't
+        // support, so we write it by hand.
+        let chars = glyph_chars(font, &glyphs.iter().copied().collect());
+        let mapped: Vec<_> = glyphs
+            .iter()
+            .enumerate()
+            .filter_map(|(code, g)| Some((code, *chars.get(g)?)))
+            .collect();
+
+        let mut cmap = String::from(TYPE3_CMAP_HEADER);
+        for chunk in mapped.chunks(100) {
+            writeln!(cmap, "{} beginbfchar", chunk.len()).unwrap();
+            for (code, c) in chunk {
+                write!(cmap, "<{code:02X}> <").unwrap();
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(cmap, "{unit:04X}").unwrap();
+                }
+                cmap.push_str(">\n");
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str(TYPE3_CMAP_FOOTER);
+
+        ctx.writer
+            .cmap(cmap_ref, &deflate(cmap.as_bytes()))
+            .filter(Filter::FlateDecode);
+    }
+}
+
+/// The start of a /ToUnicode character map for single-byte codes.
+const TYPE3_CMAP_HEADER: &str = "\
+/CIDInit /ProcSet findresource begin
+12 dict begin
+begincmap
+/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def
+/CMapName /Adobe-Identity-UCS def
+/CMapType 2 def
+1 begincodespacerange
+<00> <FF>
+endcodespacerange
+";
+
+/// The end of a /ToUnicode character map.
+const TYPE3_CMAP_FOOTER: &str = "\
+endcmap
+CMapName currentdict /CMap defineresource pop
+end
+end
+";
+
+/// Assigns the glyphs of variable font instances to Type3 fonts.
+///
+/// PDF can only embed a variable font's default instance, so other instances
+/// are drawn through Type3 fonts instead. As these have single-byte codes,
+/// each holds at most 256 glyphs and an instance's glyphs are spread over as
+/// many of them as needed.
+#[derive(Default)]
+pub struct Type3Map {
+    /// The instance and the glyphs of each Type3 font.
+    fonts: Vec<(Font, Vec<u16>)>,
+    /// The Type3 font and code of each glyph used so far.
+    codes: HashMap<(Font, u16), (usize, u8)>,
+}
+
+impl Type3Map {
+    /// The index of the Type3 font that holds a glyph and the glyph's code
+    /// in it, assigning both if the glyph is new.
+    pub fn code(&mut self, font: &Font, glyph: u16) -> (usize, u8) {
+        let key = (font.clone(), glyph);
+        if let Some(&code) = self.codes.get(&key) {
+            return code;
+        }
+
+        let index = match self.fonts.iter().rposition(|(f, _)| f == font) {
+            Some(index) if self.fonts[index].1.len() < 256 => index,
+            _ => {
+                self.fonts.push((font.clone(), vec![]));
+                self.fonts.len() - 1
+            }
+        };
+
+        let glyphs = &mut self.fonts[index].1;
+        let code = (index, glyphs.len() as u8);
+        glyphs.push(glyph);
+        self.codes.insert(key, code);
+        code
+    }
+}
+
+/// Compute a reverse mapping from some of a font's glyphs to unicode.
+fn glyph_chars(font: &Font, glyphs: &HashSet<u16>) -> BTreeMap<u16, char> {
+    let ttf = font.ttf();
+    let mut mapping = BTreeMap::new();
+    for subtable in ttf.tables().cmap.into_iter().flat_map(|table| table.subtables) {
+        if subtable.is_unicode() {
+            subtable.codepoints(|n| {
+                if let Some(c) = std::char::from_u32(n) {
+                    if let Some(GlyphId(g)) = ttf.glyph_index(c) {
+                        if glyphs.contains(&g) {
+                            mapping.insert(g, c);
+                        }
+                    }
+                }
+            });
+        }
+    }
+    mapping
+}
+
+/// The bounding box of all of a font's glyphs in PDF font units.
+fn font_bbox(font: &Font) -> Rect {
+    let global_bbox = font.ttf().global_bounding_box();
+    Rect::new(
+        font.to_em(global_bbox.x_min).to_font_units(),
+        font.to_em(global_bbox.y_min).to_font_units(),
+        font.to_em(global_bbox.x_max).to_font_units(),
+        font.to_em(global_bbox.y_max).to_font_units(),
+    )
+}
+
+/// Encode a bezier path into a glyph description.
+fn write_path(content: &mut Content, path: &geom::Path) {
+    for elem in &path.0 {
+        match elem {
+            PathItem::MoveTo(p) => content.move_to(p.x.to_f32(), p.y.to_f32()),
+            PathItem::LineTo(p) => content.line_to(p.x.to_f32(), p.y.to_f32()),
+            PathItem::CubicTo(p1, p2, p3) => content.cubic_to(
+                p1.x.to_f32(),
+                p1.y.to_f32(),
+                p2.x.to_f32(),
+                p2.y.to_f32(),
+                p3.x.to_f32(),
+                p3.y.to_f32(),
+            ),
+            PathItem::ClosePath => content.close_path(),
+        };
+    }
+}
+
+/// A glyph that is drawn in color instead of through the embedded font.
+#[derive(Debug, Clone)]
+pub enum ColorGlyph {
+    /// Outlines from the font's `COLR` table, each filled with a color or
+    /// gradient.
+    Layers(Vec<ColorLayer>),
+    /// A glyph from the font's SVG table as a vector image or from its bitmap
+    /// tables rasterized into an image, of the given size in font units. The
+    /// image's top edge sits at the font's ascender.
+    Image(Image, Size),
+}
+
+/// Whether a font has any tables with color glyphs.
+pub fn has_color_glyphs(font: &Font) -> bool {
+    let face = font.ttf().raw_face();
+    [b"COLR", b"SVG ", b"sbix", b"CBDT"]
+        .into_iter()
+        .any(|tag| face.table(Tag::from_bytes(tag)).is_some())
+}
+
+/// Prepare a glyph to be drawn in color.
+///
+/// Returns `None` if the glyph is a plain outline glyph.
+#[comemo::memoize]
+pub fn color_glyph(font: &Font, id: u16) -> Option<ColorGlyph> {
+    if let Some(layers) = colr_layers(font, id) {
+        return Some(ColorGlyph::Layers(layers));
+    }
+
+    if let Some((svg, size)) = svg_glyph_document(font, id) {
+        let image =
+            Image::new(svg.into(), ImageFormat::Vector(VectorFormat::Svg)).ok()?;
+        return Some(ColorGlyph::Image(image, size));
+    }
+
+    let (png, size) = render_image_glyph(font, id)?;
+    let image = Image::new(png.into(), ImageFormat::Raster(RasterFormat::Png)).ok()?;
+    Some(ColorGlyph::Image(image, size))
+}
+
+/// Produce the six uppercase letters that tag a font subset.
+///
+/// The tag is derived from the font and the glyphs in the subset, so that
+/// different subsets get different tags, but the same subset always gets the
+/// same one.
+fn subset_tag(font: &Font, glyphs: &[u16]) -> EcoString {
+    const LEN: usize = 6;
+    const BASE: u128 = 26;
+    let mut hash = hash128(&(font, glyphs));
+    let mut tag = EcoString::with_capacity(LEN);
+    for _ in 0..LEN {
+        tag.push(char::from(b'A' + (hash % BASE) as u8));
+        hash /= BASE;
+    }
+    tag
+}
+
+/// Extract a glyph's outline in font units and transform it.
+pub(super) fn glyph_outline(font: &Font, id: u16, ts: Transform) -> geom::Path {
+    let mut builder = PathBuilder { path: geom::Path::new(), ts, last: Point::zero() };
+    font.ttf().outline_glyph(GlyphId(id), &mut builder);
+    builder.path
+}
+
+/// Builds transformed paths from glyph outlines.
+struct PathBuilder {
+    path: geom::Path,
+    ts: Transform,
+    last: Point,
+}
+
+impl PathBuilder {
+    fn point(&self, x: f32, y: f32) -> Point {
+        Point::new(Abs::pt(x.into()), Abs::pt(y.into())).transform(self.ts)
+    }
+}
+
+impl OutlineBuilder for PathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.last = self.point(x, y);
+        self.path.move_to(self.last);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.last = self.point(x, y);
+        self.path.line_to(self.last);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        // Elevate the quadratic curve to a cubic one.
+        let q = self.point(x1, y1);
+        let p = self.point(x, y);
+        let c1 = self.last + (q - self.last) * (2.0 / 3.0);
+        let c2 = p + (q - p) * (2.0 / 3.0);
+        self.path.cubic_to(c1, c2, p);
+        self.last = p;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.last = self.point(x, y);
+        self.path.cubic_to(self.point(x1, y1), self.point(x2, y2), self.last);
+    }
+
+    fn close(&mut self) {
+        self.path.close_path();
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use ecow::EcoString;
+use pdf_writer::writers::{Annotation, Catalog};
+use pdf_writer::{Content, Finish, Name, Rect, Ref, Str, TextStr};
+
+use super::{PdfContext, RefExt};
+use crate::doc::{FormField, FormFieldKind};
+
+/// Field flag for text fields that span multiple lines.
+const MULTILINE: i32 = 1 << 12;
+/// Field flag for radio groups where one button is always checked.
+const NO_TOGGLE_TO_OFF: i32 = 1 << 14;
+/// Field flag for buttons that form a radio group.
+const RADIO: i32 = 1 << 15;
+/// Field flag for choice fields that show a dropdown.
+const COMBO: i32 = 1 << 17;
+
+/// The appearance state of checked checkboxes.
+const ON: Name<'static> = Name(b"Yes");
+/// The appearance state of unchecked buttons.
+const OFF: Name<'static> = Name(b"Off");
+
+/// The font that viewers use for the values of text fields and dropdowns.
+const FONT: Name<'static> = Name(b"Helv");
+
+/// The widget annotation of a form field, as it is placed on a page.
+pub struct PdfWidget {
+    /// The field the widget belongs to.
+    pub field: FormField,
+    /// The widget's area in the PDF coordinate system.
+    pub rect: Rect,
+    /// The page the widget is placed on.
+    pub page_ref: Ref,
+    /// The indirect object id of the widget.
+    pub widget_ref: Ref,
+}
+
+/// Write all form fields and their widgets.
+///
+/// Widgets with the same name belong to the same field. Returns the ids of
+/// the fields.
+pub fn write_fields(ctx: &mut PdfContext) -> Vec<Ref> {
+    let mut indices: HashMap<EcoString, usize> = HashMap::new();
+    let mut groups: Vec<Vec<PdfWidget>> = vec![];
+    for widget in std::mem::take(&mut ctx.widgets) {
+        let index = *indices.entry(widget.field.name.clone()).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[index].push(widget);
+    }
+
+    let mut field_refs = vec![];
+    for widgets in groups {
+        let field_ref = ctx.alloc.bump();
+        field_refs.push(field_ref);
+        write_field(ctx, field_ref, &widgets);
+        for widget in &widgets {
+            write_widget(ctx, field_ref, widget);
+        }
+    }
+
+    field_refs
+}
+
+/// Write the interactive form dictionary into the catalog.
+pub fn write_acro_form(catalog: &mut Catalog, field_refs: &[Ref]) {
+    if field_refs.is_empty() {
+        return;
+    }
+
+    let mut form = catalog.insert(Name(b"AcroForm")).dict();
+    form.insert(Name(b"Fields")).array().items(field_refs.iter().copied());
+
+    // Let viewers draw the values of text fields and dropdowns themselves.
+    form.pair(Name(b"NeedAppearances"), true);
+    form.pair(Name(b"DA"), Str(b"/Helv 0 Tf 0 g"));
+
+    let mut resources = form.insert(Name(b"DR")).dict();
+    let mut fonts = resources.insert(Name(b"Font")).dict();
+    let mut font = fonts.insert(FONT).dict();
+    font.pair(Name(b"Type"), Name(b"Font"));
+    font.pair(Name(b"Subtype"), Name(b"Type1"));
+    font.pair(Name(b"BaseFont"), Name(b"Helvetica"));
+    font.pair(Name(b"Encoding"), Name(b"WinAnsiEncoding"));
+}
+
+/// Write a field, which takes its kind and value from its first widget.
+fn write_field(ctx: &mut PdfContext, field_ref: Ref, widgets: &[PdfWidget]) {
+    let first = &widgets[0].field;
+    let mut field = ctx.writer.indirect(field_ref).dict();
+    field.pair(Name(b"T"), TextStr(&first.name));
+    field
+        .insert(Name(b"Kids"))
+        .array()
+        .items(widgets.iter().map(|widget| widget.widget_ref));
+
+    match &first.kind {
+        FormFieldKind::Text { value, multiline } => {
+            field.pair(Name(b"FT"), Name(b"Tx"));
+            field.pair(Name(b"V"), TextStr(value));
+            if *multiline {
+                field.pair(Name(b"Ff"), MULTILINE);
+            }
+        }
+        FormFieldKind::Checkbox { checked } => {
+            field.pair(Name(b"FT"), Name(b"Btn"));
+            field.pair(Name(b"V"), if *checked { ON } else { OFF });
+        }
+        FormFieldKind::Radio { .. } => {
+            field.pair(Name(b"FT"), Name(b"Btn"));
+            field.pair(Name(b"Ff"), RADIO | NO_TOGGLE_TO_OFF);
+
+            // The value of the group is that of its checked button.
+            let checked = widgets.iter().find_map(|widget| match &widget.field.kind {
+                FormFieldKind::Radio { value, checked: true } => Some(value),
+                _ => None,
+            });
+            field.pair(Name(b"V"), checked.map_or(OFF, |value| Name(value.as_bytes())));
+        }
+        FormFieldKind::Dropdown { options, selected } => {
+            field.pair(Name(b"FT"), Name(b"Ch"));
+            field.pair(Name(b"Ff"), COMBO);
+            field
+                .insert(Name(b"Opt"))
+                .array()
+                .items(options.iter().map(|option| TextStr(option)));
+            if let Some(selected) = selected {
+                field.pair(Name(b"V"), TextStr(selected));
+            }
+        }
+    }
+}
+
+/// Write a widget annotation and, for buttons, the appearances of its
+/// checked and unchecked states.
+fn write_widget(ctx: &mut PdfContext, field_ref: Ref, widget: &PdfWidget) {
+    let Rect { x1, y1, x2, y2 } = widget.rect;
+    let w = (x2 - x1).abs();
+    let h = (y2 - y1).abs();
+
+    let button = match &widget.field.kind {
+        FormFieldKind::Checkbox { checked } => Some((ON, *checked, check_mark(w, h))),
+        FormFieldKind::Radio { value, checked } => {
+            Some((Name(value.as_bytes()), *checked, dot(w, h)))
+        }
+        _ => None,
+    };
+
+    let appearances = button.as_ref().map(|(_, _, content)| {
+        let on_ref = ctx.alloc.bump();
+        let off_ref = ctx.alloc.bump();
+        let bbox = Rect::new(0.0, 0.0, w, h);
+        ctx.writer.form_xobject(on_ref, content).bbox(bbox);
+        ctx.writer.form_xobject(off_ref, &[]).bbox(bbox);
+        (on_ref, off_ref)
+    });
+
+    let mut annotation = ctx.writer.indirect(widget.widget_ref).start::<Annotation>();
+    annotation.pair(Name(b"Subtype"), Name(b"Widget"));
+    annotation.rect(widget.rect);
+    annotation.pair(Name(b"P"), widget.page_ref);
+    annotation.pair(Name(b"Parent"), field_ref);
+
+    // Make the widget printable.
+    annotation.pair(Name(b"F"), 4);
+
+    if let (Some((on, checked, _)), Some((on_ref, off_ref))) = (button, appearances) {
+        let mut dict = annotation.insert(Name(b"AP")).dict();
+        let mut normal = dict.insert(Name(b"N")).dict();
+        normal.pair(on, on_ref);
+        normal.pair(OFF, off_ref);
+        normal.finish();
+        dict.finish();
+        annotation.pair(Name(b"AS"), if checked { on } else { OFF });
+    }
+}
+
+/// The appearance of a checked checkbox of the given size.
+fn check_mark(w: f32, h: f32) -> Vec<u8> {
+    let mut content = Content::new();
+    content.set_line_width(0.1 * w.min(h));
+    content.move_to(0.2 * w, 0.5 * h);
+    content.line_to(0.42 * w, 0.25 * h);
+    content.line_to(0.8 * w, 0.78 * h);
+    content.stroke();
+    content.finish()
+}
+
+/// The appearance of a checked radio button of the given size.
+fn dot(w: f32, h: f32) -> Vec<u8> {
+    // Control point distance for approximating a quarter circle.
+    const K: f32 = 0.5523;
+    let (cx, cy) = (w / 2.0, h / 2.0);
+    let r = 0.25 * w.min(h);
+
+    let mut content = Content::new();
+    content.move_to(cx + r, cy);
+    content.cubic_to(cx + r, cy + K * r, cx + K * r, cy + r, cx, cy + r);
+    content.cubic_to(cx - K * r, cy + r, cx - r, cy + K * r, cx - r, cy);
+    content.cubic_to(cx - r, cy - K * r, cx - K * r, cy - r, cx, cy - r);
+    content.cubic_to(cx + K * r, cy - r, cx + r, cy - K * r, cx + r, cy);
+    content.fill_nonzero();
+    content.finish()
+}
@@ -0,0 +1,213 @@
+use pdf_writer::{Content, Dict, Filter, Finish, Name};
+
+use super::{deflate, AbsExt, PdfContext, RefExt, TransformExt};
+use crate::geom::{Color, Gradient, Ratio, Size, Transform};
+
+/// A gradient as it is placed on a page.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PdfGradient {
+    /// The gradient itself.
+    pub gradient: Gradient,
+    /// The size of the box that the gradient stretches over.
+    pub size: Size,
+    /// The transform from the box's coordinate system, with the origin in its
+    /// top-left corner, to the default coordinate system of the page.
+    pub transform: Transform,
+}
+
+/// The opacity of a gradient with translucent stops, as it is placed on a page.
+///
+/// The mask is painted in the coordinate system that is current when the
+/// graphics state that references it is set.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PdfSoftMask {
+    /// The placed gradient whose opacity the mask carries.
+    pub gradient: PdfGradient,
+    /// The transform from the default coordinate system of the page to the
+    /// coordinate system in which the mask is applied.
+    pub transform: Transform,
+    /// The size of the page.
+    pub size: Size,
+}
+
+/// Whether any of the gradient's stops is not fully opaque.
+pub fn is_translucent(gradient: &Gradient) -> bool {
+    gradient.stops().iter().any(|(color, _)| color.to_rgba().a < 255)
+}
+
+/// Embed all used gradients into the PDF as shading patterns.
+///
+/// Shadings have no alpha channel, so the opacity of the stops is carried by
+/// a separate soft mask, see [`write_soft_masks`].
+pub fn write_gradients(ctx: &mut PdfContext) {
+    for placed in ctx.gradient_map.items() {
+        let pattern_ref = ctx.alloc.bump();
+        ctx.gradient_refs.push(pattern_ref);
+
+        let mut pattern = ctx.writer.indirect(pattern_ref).dict();
+        pattern.pair(Name(b"Type"), Name(b"Pattern"));
+        pattern.pair(Name(b"PatternType"), 2);
+
+        let mut shading = pattern.insert(Name(b"Shading")).dict();
+        let matrix = write_shading(&mut shading, placed, false);
+        shading.finish();
+
+        pattern.insert(Name(b"Matrix")).array().items(matrix.to_pdf_matrix());
+    }
+}
+
+/// Embed the soft masks of gradients with translucent stops into the PDF.
+///
+/// Each mask is a transparency group that paints the opacity of the stops as
+/// a grayscale shading, which is then used as the mask's luminosity.
+pub fn write_soft_masks(ctx: &mut PdfContext) {
+    for PdfSoftMask { gradient, transform, size } in ctx.soft_mask_map.items() {
+        // The group is painted in page space and mapped into the coordinate
+        // system in which the mask is applied.
+        let mut content = Content::new();
+        content.save_state();
+        let matrix = shading_matrix(gradient);
+        content.transform(matrix.to_pdf_matrix());
+        content.shading(Name(b"Sh0"));
+        content.restore_state();
+        let data = deflate(&content.finish());
+
+        let form_ref = ctx.alloc.bump();
+        ctx.soft_mask_refs.push(form_ref);
+
+        let mut form = ctx.writer.stream(form_ref, &data);
+        form.filter(Filter::FlateDecode);
+        form.pair(Name(b"Type"), Name(b"XObject"));
+        form.pair(Name(b"Subtype"), Name(b"Form"));
+        form.insert(Name(b"BBox")).array().items([
+            0.0,
+            0.0,
+            size.x.to_f32(),
+            size.y.to_f32(),
+        ]);
+        form.insert(Name(b"Matrix")).array().items(transform.to_pdf_matrix());
+
+        let mut group = form.insert(Name(b"Group")).dict();
+        group.pair(Name(b"Type"), Name(b"Group"));
+        group.pair(Name(b"S"), Name(b"Transparency"));
+        group.pair(Name(b"CS"), Name(b"DeviceGray"));
+        group.finish();
+
+        let mut resources = form.insert(Name(b"Resources")).dict();
+        let mut shadings = resources.insert(Name(b"Shading")).dict();
+        let mut shading = shadings.insert(Name(b"Sh0")).dict();
+        write_shading(&mut shading, gradient, true);
+    }
+}
+
+/// Write the shading of a placed gradient, either of its colors or of the
+/// opacity of its stops, and return the transform from the shading's
+/// coordinate system to the default coordinate system of the page.
+fn write_shading(shading: &mut Dict, placed: &PdfGradient, alpha: bool) -> Transform {
+    let PdfGradient { gradient, size, .. } = placed;
+    let space = if alpha { Name(b"DeviceGray") } else { Name(b"DeviceRGB") };
+    shading.pair(Name(b"ColorSpace"), space);
+    shading.insert(Name(b"Extend")).array().items([true, true]);
+
+    match gradient {
+        Gradient::Linear(linear) => {
+            let (start, end) = linear.axis(*size);
+            shading.pair(Name(b"ShadingType"), 2);
+            shading.insert(Name(b"Coords")).array().items([
+                start.x.to_f32(),
+                start.y.to_f32(),
+                end.x.to_f32(),
+                end.y.to_f32(),
+            ]);
+        }
+        Gradient::Radial(radial) => {
+            let x = radial.center.x.get() as f32;
+            let y = radial.center.y.get() as f32;
+            let r = radial.radius.get() as f32;
+            shading.pair(Name(b"ShadingType"), 3);
+            shading.insert(Name(b"Coords")).array().items([x, y, 0.0, x, y, r]);
+        }
+    }
+
+    // Blend between each pair of neighbouring stops with an exponential
+    // interpolation function and stitch them together.
+    let components = |color: Color| {
+        if alpha {
+            vec![color.to_rgba().a as f32 / 255.0]
+        } else {
+            rgb(color).to_vec()
+        }
+    };
+
+    let stops = pad_stops(gradient.stops());
+    let mut function = shading.insert(Name(b"Function")).dict();
+    function.pair(Name(b"FunctionType"), 3);
+    function.insert(Name(b"Domain")).array().items([0.0_f32, 1.0]);
+
+    let mut functions = function.insert(Name(b"Functions")).array();
+    for window in stops.windows(2) {
+        let mut interpolation = functions.push().dict();
+        interpolation.pair(Name(b"FunctionType"), 2);
+        interpolation.insert(Name(b"Domain")).array().items([0.0_f32, 1.0]);
+        interpolation
+            .insert(Name(b"C0"))
+            .array()
+            .items(components(window[0].0));
+        interpolation
+            .insert(Name(b"C1"))
+            .array()
+            .items(components(window[1].0));
+        interpolation.pair(Name(b"N"), 1.0_f32);
+    }
+
+    functions.finish();
+    function.insert(Name(b"Bounds")).array().items(
+        stops[1..stops.len() - 1]
+            .iter()
+            .map(|(_, offset)| offset.get() as f32),
+    );
+    function
+        .insert(Name(b"Encode"))
+        .array()
+        .items(stops[1..].iter().flat_map(|_| [0.0_f32, 1.0]));
+    function.finish();
+
+    shading_matrix(placed)
+}
+
+/// The transform from a gradient's shading coordinates to the default
+/// coordinate system of the page.
+fn shading_matrix(placed: &PdfGradient) -> Transform {
+    match &placed.gradient {
+        Gradient::Linear(_) => placed.transform,
+        // The coordinates of a radial gradient are relative to the box so
+        // that the circle is stretched into an ellipse in a box that isn't
+        // square.
+        Gradient::Radial(_) => placed.transform.pre_concat(Transform::scale(
+            Ratio::new(placed.size.x.to_pt()),
+            Ratio::new(placed.size.y.to_pt()),
+        )),
+    }
+}
+
+/// Extend the stops so that they cover the whole range from zero to one.
+fn pad_stops(stops: &[(Color, Ratio)]) -> Vec<(Color, Ratio)> {
+    let mut padded = stops.to_vec();
+    if let Some(&(color, offset)) = stops.first() {
+        if offset > Ratio::zero() {
+            padded.insert(0, (color, Ratio::zero()));
+        }
+    }
+    if let Some(&(color, offset)) = stops.last() {
+        if offset < Ratio::one() {
+            padded.push((color, Ratio::one()));
+        }
+    }
+    padded
+}
+
+/// The sRGB components of a color.
+fn rgb(color: Color) -> [f32; 3] {
+    let c = color.to_rgba();
+    [c.r, c.g, c.b].map(|v| v as f32 / 255.0)
+}
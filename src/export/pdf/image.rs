@@ -1,10 +1,12 @@
+use std::collections::HashMap;
 use std::io::Cursor;
 
 use image::{DynamicImage, GenericImageView, ImageResult, Rgba};
-use pdf_writer::{Filter, Finish};
+use pdf_writer::{Filter, Finish, Name, Null, Obj, PdfWriter, Rect, Ref, Str};
 
 use super::{deflate, PdfContext, RefExt};
-use crate::image::{DecodedImage, RasterFormat};
+use crate::image::pdf::{Object, ObjectId};
+use crate::image::{DecodedImage, PdfPage, RasterFormat};
 
 /// Embed all used images into the PDF.
 pub fn write_images(ctx: &mut PdfContext) {
@@ -59,6 +61,85 @@ pub fn write_images(ctx: &mut PdfContext) {
                 );
                 ctx.alloc = next_ref;
             }
+            DecodedImage::Pdf(page) => {
+                write_pdf_page(&mut ctx.writer, &mut ctx.alloc, image_ref, page);
+            }
+        }
+    }
+}
+
+/// Embed a page of a PDF file as a form XObject that maps the page onto the
+/// unit square, just like an image.
+///
+/// The objects that the page's resources refer to, like fonts and images, are
+/// copied over as they are.
+fn write_pdf_page(writer: &mut PdfWriter, alloc: &mut Ref, id: Ref, page: &PdfPage) {
+    let mut copier = ObjectCopier { refs: HashMap::new(), queue: vec![] };
+    let [left, bottom, right, top] = page.bbox().map(|v| v as f32);
+    let content = deflate(page.content());
+
+    let mut form = writer.form_xobject(id, &content);
+    form.filter(Filter::FlateDecode);
+    form.bbox(Rect::new(left, bottom, right, top));
+    form.matrix(page.matrix().map(|v| v as f32));
+    copier.copy(form.insert(Name(b"Resources")), page.resources(), alloc);
+    form.finish();
+
+    while let Some((source, id)) = copier.queue.pop() {
+        match page.resolve(source) {
+            Some(Object::Stream(dict, data)) => {
+                let mut stream = writer.stream(id, &data);
+                for (key, value) in dict.iter().filter(|&(key, _)| key != b"Length") {
+                    copier.copy(stream.insert(Name(key)), value, alloc);
+                }
+            }
+            Some(object) => copier.copy(writer.indirect(id), &object, alloc),
+            None => writer.indirect(id).primitive(Null),
+        }
+    }
+}
+
+/// Copies objects from an embedded PDF file into the exported one.
+struct ObjectCopier {
+    /// Maps from indirect objects of the embedded file to their copies.
+    refs: HashMap<ObjectId, Ref>,
+    /// Indirect objects that are referenced, but not yet copied.
+    queue: Vec<(ObjectId, Ref)>,
+}
+
+impl ObjectCopier {
+    /// Copy an object, renumbering the indirect objects it refers to.
+    fn copy(&mut self, obj: Obj, object: &Object, alloc: &mut Ref) {
+        match object {
+            Object::Null | Object::Stream(..) => obj.primitive(Null),
+            Object::Bool(v) => obj.primitive(*v),
+            Object::Int(v) => {
+                obj.primitive((*v).clamp(i32::MIN.into(), i32::MAX.into()) as i32)
+            }
+            Object::Real(v) => obj.primitive(*v as f32),
+            Object::Str(v) => obj.primitive(Str(v)),
+            Object::Name(v) => obj.primitive(Name(v)),
+            Object::Array(items) => {
+                let mut array = obj.array();
+                for item in items {
+                    self.copy(array.push(), item, alloc);
+                }
+            }
+            Object::Dict(dict) => {
+                // Don't pull in the page tree of the embedded file.
+                let mut copy = obj.dict();
+                for (key, value) in dict.iter().filter(|&(key, _)| key != b"Parent") {
+                    self.copy(copy.insert(Name(key)), value, alloc);
+                }
+            }
+            Object::Ref(source) => {
+                let id = *self.refs.entry(*source).or_insert_with(|| {
+                    let id = alloc.bump();
+                    self.queue.push((*source, id));
+                    id
+                });
+                obj.primitive(id);
+            }
         }
     }
 }
@@ -1,39 +1,208 @@
 //! Exporting into PDF documents.
 
+mod attachment;
+mod colr;
+mod extg;
 mod font;
+mod form;
+mod gradient;
 mod image;
 mod outline;
 mod page;
+mod pattern;
+mod pdfa;
+mod structure;
 
 use std::cmp::Eq;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::num::NonZeroUsize;
 
+use ecow::{eco_format, EcoString};
 use pdf_writer::types::Direction;
-use pdf_writer::{Finish, Name, PdfWriter, Ref, TextStr};
-use xmp_writer::{LangId, RenditionClass, XmpWriter};
+use pdf_writer::{Date, Finish, Name, PdfWriter, Ref, TextStr};
+use xmp_writer::{DateTime, LangId, Namespace, RenditionClass, XmpWriter};
 
+use self::extg::ExtGState;
+use self::font::Type3Map;
+use self::form::PdfWidget;
+use self::gradient::{PdfGradient, PdfSoftMask};
 use self::outline::HeadingNode;
 use self::page::Page;
-use crate::doc::{Document, Lang};
+use self::pattern::PdfPattern;
+use self::structure::StructTree;
+use crate::diag::StrResult;
+use crate::doc::{Attachment, Document, Lang, PageLabel};
 use crate::font::Font;
-use crate::geom::{Abs, Dir, Em};
+use crate::geom::{Abs, Dir, Em, Pattern, Smart, Transform};
 use crate::image::Image;
 use crate::model::Introspector;
+use crate::util::hash128;
 
 /// Export a document into a PDF file.
 ///
 /// Returns the raw bytes making up the PDF file.
 pub fn pdf(document: &Document) -> Vec<u8> {
-    let mut ctx = PdfContext::new(document);
+    let options = PdfOptions::default();
+    write(PdfContext::new(document, &options), &options)
+}
+
+/// Export a document into a PDF file with custom options.
+///
+/// Returns the raw bytes making up the PDF file or an error if the document
+/// uses features that the requested standard doesn't allow.
+pub fn pdf_with_options(document: &Document, options: &PdfOptions) -> StrResult<Vec<u8>> {
+    let ctx = PdfContext::new(document, options);
+    if let Some(standard) = options.standard {
+        pdfa::check(&ctx, standard)?;
+    }
+
+    Ok(write(ctx, options))
+}
+
+/// Write a document that is known to conform to the requested standard.
+fn write(mut ctx: PdfContext, options: &PdfOptions) -> Vec<u8> {
+    // Derive the file identifier from the document and the options so that
+    // exporting the same document twice yields the same file.
+    let document = ctx.document;
+    let id = hash128(&(document, options));
+
     page::construct_pages(&mut ctx, &document.pages);
+    outline::construct_heading_tree(&mut ctx);
     font::write_fonts(&mut ctx);
+    font::write_type3_fonts(&mut ctx);
     image::write_images(&mut ctx);
+    attachment::write_attachments(&mut ctx);
+    gradient::write_gradients(&mut ctx);
+    gradient::write_soft_masks(&mut ctx);
+    extg::write_external_graphics_states(&mut ctx);
+    pattern::write_patterns(&mut ctx);
     page::write_page_tree(&mut ctx);
     write_catalog(&mut ctx);
-    ctx.writer.finish()
+    write_file_id(ctx.writer.finish(), id)
+}
+
+/// Add the file identifier to the trailer of a finished file.
+///
+/// The writer has no way to write it, so it is inserted at the start of the
+/// trailer dictionary. The trailer follows the cross-reference table, so no
+/// offsets change.
+fn write_file_id(mut buf: Vec<u8>, id: u128) -> Vec<u8> {
+    const TRAILER: &[u8] = b"trailer\n<<";
+    let Some(pos) = buf.windows(TRAILER.len()).rposition(|w| w == TRAILER) else {
+        return buf;
+    };
+
+    let hex = format!("{id:032X}");
+    let entry = format!("\n  /ID [<{hex}> <{hex}>]");
+    let at = pos + TRAILER.len();
+    buf.splice(at..at, entry.into_bytes());
+    buf
+}
+
+/// Options for PDF export.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct PdfOptions {
+    /// The numbers of the pages to export, starting at one. All pages are
+    /// exported if this is `None`.
+    ///
+    /// Links to pages that are not exported are left out and so are the
+    /// outline entries of headings on such pages.
+    pub pages: Option<Vec<NonZeroUsize>>,
+    /// The standard the file must conform to, if any.
+    pub standard: Option<PdfStandard>,
+    /// Whether to tag the file with the logical structure of the document,
+    /// so that assistive technology like screen readers can make sense of it.
+    ///
+    /// Headings, paragraphs, lists, tables and figures become structure
+    /// elements. Everything else, like table lines, is marked as an artifact.
+    /// Paragraphs, lists and tables are only known if layout marked them,
+    /// which the standard library always does.
+    pub tagged: bool,
+    /// The creation and modification date of the file.
+    ///
+    /// Without a timestamp, the file has no dates. Either way, exporting the
+    /// same document with the same options yields the same bytes.
+    pub timestamp: Option<PdfTimestamp>,
 }
 
+/// A point in time in UTC.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PdfTimestamp {
+    /// The year, e.g. 2023.
+    pub year: u16,
+    /// The month, from 1 to 12.
+    pub month: u8,
+    /// The day of the month, from 1 to 31.
+    pub day: u8,
+    /// The hour, from 0 to 23.
+    pub hour: u8,
+    /// The minute, from 0 to 59.
+    pub minute: u8,
+    /// The second, from 0 to 59.
+    pub second: u8,
+}
+
+impl PdfTimestamp {
+    /// Create a timestamp from the number of seconds since the Unix epoch, as
+    /// given by the `SOURCE_DATE_EPOCH` environment variable of reproducible
+    /// builds.
+    ///
+    /// Returns `None` if the year is not within the range of four digits.
+    pub fn from_unix(seconds: i64) -> Option<Self> {
+        let days = seconds.div_euclid(86400);
+        let time = seconds.rem_euclid(86400);
+
+        // Convert the days to a civil date, following Howard Hinnant's
+        // `civil_from_days` algorithm.
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        if !(0..=9999).contains(&year) {
+            return None;
+        }
+
+        Some(Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (time / 3600) as u8,
+            minute: (time % 3600 / 60) as u8,
+            second: (time % 60) as u8,
+        })
+    }
+}
+
+/// A PDF standard that exported files can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PdfStandard {
+    /// PDF/A-2b, for the long-term archival of documents.
+    ///
+    /// Embeds an sRGB output intent and refuses CMYK colors as well as fonts
+    /// whose license forbids embedding them.
+    A2b,
+}
+
+impl PdfStandard {
+    /// The name of the standard.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::A2b => "PDF/A-2b",
+        }
+    }
+}
+
+/// The namespace of custom properties in the metadata stream.
+const PDFX: Namespace<'static> =
+    Namespace::Custom(("pdfx", "http://ns.adobe.com/pdfx/1.3/"));
+
 /// Identifies the color space definitions.
 const SRGB: Name<'static> = Name(b"srgb");
 const D65_GRAY: Name<'static> = Name(b"d65gray");
@@ -44,43 +213,117 @@ pub struct PdfContext<'a> {
     introspector: Introspector,
     writer: PdfWriter,
     pages: Vec<Page>,
+    /// The index of each page of the document among the exported pages, or
+    /// `None` if the page is not exported.
+    page_indices: Vec<Option<usize>>,
     page_heights: Vec<f32>,
+    /// The label of each exported page, if it has one.
+    page_labels: Vec<Option<PageLabel>>,
     alloc: Ref,
     page_tree_ref: Ref,
+    /// The resources shared by all pages and pattern tiles.
+    global_resources_ref: Ref,
     font_refs: Vec<Ref>,
+    /// The Type3 fonts that hold the glyphs of variable font instances.
+    type3_refs: Vec<Ref>,
     image_refs: Vec<Ref>,
+    ext_gs_refs: Vec<Ref>,
+    gradient_refs: Vec<Ref>,
+    /// The transparency groups that carry the opacity of gradients.
+    soft_mask_refs: Vec<Ref>,
+    pattern_refs: Vec<Ref>,
+    /// The file specifications of the attached files.
+    attachment_refs: Vec<Ref>,
+    /// The unique name of each attached file.
+    attachment_names: Vec<EcoString>,
     page_refs: Vec<Ref>,
     font_map: Remapper<Font>,
+    type3_map: Type3Map,
     image_map: Remapper<Image>,
+    ext_gs_map: Remapper<ExtGState>,
+    gradient_map: Remapper<PdfGradient>,
+    soft_mask_map: Remapper<PdfSoftMask>,
+    pattern_map: Remapper<PdfPattern>,
+    attachment_map: Remapper<Attachment>,
+    /// The widgets of form fields on the written pages.
+    widgets: Vec<PdfWidget>,
+    /// The compressed content stream of each pattern's tile.
+    pattern_tiles: HashMap<Pattern, Vec<u8>>,
     glyph_sets: HashMap<Font, HashSet<u16>>,
     languages: HashMap<Lang, usize>,
     heading_tree: Vec<HeadingNode>,
+    standard: Option<PdfStandard>,
+    timestamp: Option<PdfTimestamp>,
+    /// The logical structure of the document if the file is tagged.
+    struct_tree: Option<StructTree>,
 }
 
 impl<'a> PdfContext<'a> {
-    fn new(document: &'a Document) -> Self {
+    fn new(document: &'a Document, options: &PdfOptions) -> Self {
         let mut alloc = Ref::new(1);
         let page_tree_ref = alloc.bump();
+        let global_resources_ref = alloc.bump();
+
         Self {
             document,
             introspector: Introspector::new(&document.pages),
             writer: PdfWriter::new(),
             pages: vec![],
+            page_indices: page_indices(document.pages.len(), options.pages.as_deref()),
             page_heights: vec![],
+            page_labels: vec![],
             alloc,
             page_tree_ref,
+            global_resources_ref,
             page_refs: vec![],
             font_refs: vec![],
+            type3_refs: vec![],
             image_refs: vec![],
+            ext_gs_refs: vec![],
+            gradient_refs: vec![],
+            soft_mask_refs: vec![],
+            pattern_refs: vec![],
+            attachment_refs: vec![],
+            attachment_names: vec![],
             font_map: Remapper::new(),
+            type3_map: Type3Map::default(),
             image_map: Remapper::new(),
+            ext_gs_map: Remapper::new(),
+            gradient_map: Remapper::new(),
+            soft_mask_map: Remapper::new(),
+            pattern_map: Remapper::new(),
+            attachment_map: Remapper::new(),
+            widgets: vec![],
+            pattern_tiles: HashMap::new(),
             glyph_sets: HashMap::new(),
             languages: HashMap::new(),
             heading_tree: vec![],
+            standard: options.standard,
+            timestamp: options.timestamp,
+            struct_tree: options.tagged.then(StructTree::default),
         }
     }
 }
 
+/// Number the exported pages consecutively.
+///
+/// Returns the index of each of the document's pages among the exported
+/// pages, or `None` if the page is not exported. All pages are exported if
+/// there is no selection.
+fn page_indices(count: usize, pages: Option<&[NonZeroUsize]>) -> Vec<Option<usize>> {
+    let mut exported = 0;
+    (1..=count)
+        .map(|number| {
+            let selected =
+                pages.map_or(true, |pages| pages.iter().any(|page| page.get() == number));
+            selected.then(|| {
+                exported += 1;
+                exported - 1
+            })
+        })
+        .collect()
+}
+
 /// Write the document catalog.
 fn write_catalog(ctx: &mut PdfContext) {
     // Build the outline tree.
@@ -103,14 +346,16 @@ fn write_catalog(ctx: &mut PdfContext) {
         let mut outline_root = ctx.writer.outline(outline_root_id);
         outline_root.first(outline_start_ref);
         outline_root.last(Ref::new(ctx.alloc.get() - 1));
-        outline_root.count(ctx.heading_tree.len() as i32);
+        outline_root.count(len as i32);
     }
 
-    let lang = ctx
-        .languages
-        .iter()
-        .max_by_key(|(&lang, &count)| (count, lang))
-        .map(|(&k, _)| k);
+    // Prefer the document's language over the most common one in its text.
+    let lang = ctx.document.lang.or_else(|| {
+        ctx.languages
+            .iter()
+            .max_by_key(|(&lang, &count)| (count, lang))
+            .map(|(&k, _)| k)
+    });
 
     let dir = if lang.map(Lang::dir) == Some(Dir::RTL) {
         Direction::R2L
@@ -128,18 +373,94 @@ fn write_catalog(ctx: &mut PdfContext) {
 
     let authors = &ctx.document.author;
     if !authors.is_empty() {
-        info.author(TextStr(&authors.join(", ")));
-        xmp.creator(authors.iter().map(|s| s.as_str()));
+        let joined = authors.join(", ");
+        info.author(TextStr(&joined));
+        if ctx.standard.is_some() {
+            // PDF/A requires the creator to match the document information.
+            xmp.creator([joined.as_str()]);
+        } else {
+            xmp.creator(authors.iter().map(|s| s.as_str()));
+        }
     }
+
+    if let Some(description) = &ctx.document.description {
+        info.subject(TextStr(description));
+        xmp.description([(None, description.as_str())]);
+    }
+
+    let keywords = &ctx.document.keywords;
+    if !keywords.is_empty() {
+        let joined = keywords.join(", ");
+        info.keywords(TextStr(&joined));
+        xmp.pdf_keywords(&joined);
+        xmp.subject(keywords.iter().map(|s| s.as_str()));
+    }
+
     info.creator(TextStr("Typst"));
+    if let Some(timestamp) = ctx.timestamp {
+        let date = Date::new(timestamp.year)
+            .month(timestamp.month)
+            .day(timestamp.day)
+            .hour(timestamp.hour)
+            .minute(timestamp.minute)
+            .second(timestamp.second)
+            .utc_offset_hour(0);
+        info.modified_date(date);
+        if ctx.document.date.is_auto() {
+            info.creation_date(date);
+        }
+
+        let date = DateTime::new(
+            timestamp.year,
+            timestamp.month,
+            timestamp.day,
+            timestamp.hour,
+            timestamp.minute,
+            timestamp.second,
+            0,
+            0,
+        );
+        xmp.modify_date(date);
+        if ctx.document.date.is_auto() {
+            xmp.create_date(date);
+        }
+    }
+
+    if let Smart::Custom(Some(date)) = ctx.document.date {
+        info.creation_date(Date::new(date.year).month(date.month).day(date.day));
+        xmp.create_date(DateTime::date(date.year, date.month, date.day));
+    }
+
+    // Custom properties are free-form entries in the document information.
+    // PDF/A only allows them in the metadata stream with a schema description,
+    // so they are left out there.
+    for (key, value) in &ctx.document.properties {
+        info.pair(Name(key.as_bytes()), TextStr(value));
+        if ctx.standard.is_none() {
+            xmp.element(key.as_str(), PDFX).value(value.as_str());
+        }
+    }
+
     info.finish();
     xmp.creator_tool("Typst");
-    xmp.num_pages(ctx.document.pages.len() as u32);
+    xmp.num_pages(ctx.page_refs.len() as u32);
     xmp.format("application/pdf");
-    xmp.language(ctx.languages.keys().map(|lang| LangId(lang.as_str())));
+
+    // Sort the languages to keep the output deterministic.
+    let mut languages: Vec<_> = ctx.languages.keys().map(Lang::as_str).collect();
+    languages.sort_unstable();
+    xmp.language(languages.into_iter().map(LangId));
     xmp.rendition_class(RenditionClass::Proof);
     xmp.pdf_version("1.7");
 
+    match ctx.standard {
+        Some(PdfStandard::A2b) => {
+            xmp.pdfa_part("2");
+            xmp.pdfa_conformance("B");
+        }
+        None => {}
+    }
+
     let xmp_buf = xmp.finish(None);
     let meta_ref = ctx.alloc.bump();
     let mut meta_stream = ctx.writer.stream(meta_ref, xmp_buf.as_bytes());
@@ -147,6 +468,13 @@ fn write_catalog(ctx: &mut PdfContext) {
     meta_stream.pair(Name(b"Subtype"), Name(b"XML"));
     meta_stream.finish();
 
+    let field_refs = form::write_fields(ctx);
+    let icc_ref = ctx.standard.is_some().then(|| pdfa::write_icc_profile(ctx));
+    let struct_tree_ref = ctx
+        .struct_tree
+        .take()
+        .map(|tree| structure::write_struct_tree(ctx, tree));
+
     // Write the document catalog.
     let mut catalog = ctx.writer.catalog(ctx.alloc.bump());
     catalog.pages(ctx.page_tree_ref);
@@ -157,8 +485,33 @@ fn write_catalog(ctx: &mut PdfContext) {
         catalog.outlines(outline_root_id);
     }
 
+    page::write_page_labels(&mut catalog, &ctx.page_labels);
+    attachment::write_embedded_files(
+        &mut catalog,
+        &ctx.attachment_names,
+        &ctx.attachment_refs,
+    );
+    form::write_acro_form(&mut catalog, &field_refs);
+
     if let Some(lang) = lang {
-        catalog.lang(TextStr(lang.as_str()));
+        match ctx.document.region {
+            Some(region) if ctx.document.lang == Some(lang) => {
+                let tag = eco_format!("{}-{}", lang.as_str(), region.as_str());
+                catalog.lang(TextStr(&tag));
+            }
+            _ => {
+                catalog.lang(TextStr(lang.as_str()));
+            }
+        }
+    }
+
+    if let Some(icc_ref) = icc_ref {
+        pdfa::write_output_intents(&mut catalog, icc_ref);
+    }
+
+    if let Some(struct_tree_ref) = struct_tree_ref {
+        catalog.pair(Name(b"StructTreeRoot"), struct_tree_ref);
+        catalog.insert(Name(b"MarkInfo")).dict().pair(Name(b"Marked"), true);
     }
 }
 
@@ -233,6 +586,26 @@ impl EmExt for Em {
     }
 }
 
+/// Additional methods for [`Transform`].
+trait TransformExt {
+    /// Convert a transform to the six numbers of a PDF matrix.
+    fn to_pdf_matrix(self) -> [f32; 6];
+}
+
+impl TransformExt for Transform {
+    fn to_pdf_matrix(self) -> [f32; 6] {
+        let Self { sx, ky, kx, sy, tx, ty } = self;
+        [
+            sx.get() as f32,
+            ky.get() as f32,
+            kx.get() as f32,
+            sy.get() as f32,
+            tx.to_f32(),
+            ty.to_f32(),
+        ]
+    }
+}
+
 /// Additional methods for [`Ref`].
 trait RefExt {
     /// Bump the reference up by one and return the previous one.
@@ -246,3 +619,86 @@ impl RefExt for Ref {
         prev
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::Frame;
+    use crate::geom::Size;
+
+    fn pages(numbers: &[usize]) -> Vec<NonZeroUsize> {
+        numbers.iter().map(|&n| NonZeroUsize::new(n).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_pdf_page_indices() {
+        assert_eq!(page_indices(0, None), vec![]);
+        assert_eq!(page_indices(3, None), vec![Some(0), Some(1), Some(2)]);
+        assert_eq!(
+            page_indices(5, Some(&pages(&[2, 4, 5]))),
+            vec![None, Some(0), None, Some(1), Some(2)],
+        );
+
+        // The order of the selection and duplicates don't matter.
+        assert_eq!(
+            page_indices(4, Some(&pages(&[3, 1, 3]))),
+            vec![Some(0), None, Some(1), None],
+        );
+
+        // Pages past the end of the document are ignored.
+        assert_eq!(page_indices(2, Some(&pages(&[2, 7]))), vec![None, Some(0)]);
+        assert_eq!(page_indices(2, Some(&[])), vec![None, None]);
+    }
+
+    #[track_caller]
+    fn test_timestamp(seconds: i64, date: (u16, u8, u8), time: (u8, u8, u8)) {
+        let (year, month, day) = date;
+        let (hour, minute, second) = time;
+        assert_eq!(
+            PdfTimestamp::from_unix(seconds),
+            Some(PdfTimestamp { year, month, day, hour, minute, second }),
+        );
+    }
+
+    #[test]
+    fn test_pdf_timestamp_from_unix() {
+        test_timestamp(0, (1970, 1, 1), (0, 0, 0));
+        test_timestamp(1_000_000_000, (2001, 9, 9), (1, 46, 40));
+
+        // Leap days, including in a year divisible by 100 but not by 400.
+        test_timestamp(951_827_415, (2000, 2, 29), (12, 30, 15));
+        test_timestamp(951_868_800, (2000, 3, 1), (0, 0, 0));
+        test_timestamp(-2_203_891_201, (1900, 2, 28), (23, 59, 59));
+        test_timestamp(-2_203_891_200, (1900, 3, 1), (0, 0, 0));
+
+        // Times before the epoch.
+        test_timestamp(-1, (1969, 12, 31), (23, 59, 59));
+        test_timestamp(-86_400, (1969, 12, 31), (0, 0, 0));
+
+        // The years that have four digits.
+        test_timestamp(-62_167_219_200, (0, 1, 1), (0, 0, 0));
+        test_timestamp(253_402_300_799, (9999, 12, 31), (23, 59, 59));
+        assert_eq!(PdfTimestamp::from_unix(-62_167_219_201), None);
+        assert_eq!(PdfTimestamp::from_unix(253_402_300_800), None);
+        assert_eq!(PdfTimestamp::from_unix(i64::MIN / 2), None);
+        assert_eq!(PdfTimestamp::from_unix(i64::MAX / 2), None);
+    }
+
+    #[test]
+    fn test_pdf_reproducible() {
+        let mut document = Document::default();
+        document.title = Some("Reproducible".into());
+        let size = Size::new(Abs::pt(100.0), Abs::pt(50.0));
+        document.pages.push(Frame::new(size));
+
+        let options = PdfOptions {
+            timestamp: PdfTimestamp::from_unix(1_000_000_000),
+            ..PdfOptions::default()
+        };
+
+        let dated = pdf_with_options(&document, &options).unwrap();
+        assert_eq!(dated, pdf_with_options(&document, &options).unwrap());
+        assert_eq!(pdf(&document), pdf(&document));
+        assert_ne!(dated, pdf(&document));
+    }
+}
@@ -1,8 +1,12 @@
+use std::num::NonZeroUsize;
+
 use ecow::EcoString;
 use pdf_writer::{Finish, Ref, TextStr};
 
 use super::{AbsExt, PdfContext, RefExt};
+use crate::eval::LANG_ITEMS;
 use crate::geom::{Abs, Point};
+use crate::model::Content;
 
 /// A heading in the outline panel.
 #[derive(Debug, Clone)]
@@ -19,14 +23,14 @@ impl HeadingNode {
         1 + self.children.iter().map(Self::len).sum::<usize>()
     }
 
-    #[allow(unused)]
-    pub fn try_insert(&mut self, child: Self, level: usize) -> bool {
-        if level >= child.level {
+    /// Insert a heading below this one if its level is higher.
+    pub fn try_insert(&mut self, child: Self) -> bool {
+        if child.level <= self.level {
             return false;
         }
 
         if let Some(last) = self.children.last_mut() {
-            if last.try_insert(child.clone(), level + 1) {
+            if last.try_insert(child.clone()) {
                 return true;
             }
         }
@@ -36,6 +40,49 @@ impl HeadingNode {
     }
 }
 
+/// Construct the outline tree from the document's headings.
+///
+/// Headings on pages that are not exported are left out, so that their
+/// subheadings move up to the closest preceding heading of a lower level.
+pub fn construct_heading_tree(ctx: &mut PdfContext) {
+    // Without a library, the document can't contain any headings.
+    let Some(items) = LANG_ITEMS.get() else { return };
+    let selector = items.heading_func.select();
+    for heading in ctx.introspector.query(&selector) {
+        if !heading.cast_field::<bool>("outlined").unwrap_or(true) {
+            continue;
+        }
+
+        let Some(location) = heading.location() else { continue };
+        let pos = ctx.introspector.position(location);
+        let Some(index) = ctx.page_indices[pos.page.get() - 1] else { continue };
+
+        // Flip the y-axis because PDF coordinates start at the bottom.
+        let height = Abs::pt(ctx.page_heights[index].into());
+        let node = HeadingNode {
+            content: heading.expect_field::<Content>("body").plain_text(),
+            level: heading.expect_field::<NonZeroUsize>("level").get(),
+            position: Point::new(pos.point.x, height - pos.point.y),
+            page: ctx.page_refs[index],
+            children: vec![],
+        };
+
+        insert_heading(&mut ctx.heading_tree, node);
+    }
+}
+
+/// Insert a heading below the last heading in the tree if its level is
+/// higher and at the top level otherwise.
+fn insert_heading(tree: &mut Vec<HeadingNode>, node: HeadingNode) {
+    if let Some(last) = tree.last_mut() {
+        if last.try_insert(node.clone()) {
+            return;
+        }
+    }
+
+    tree.push(node);
+}
+
 /// Write an outline item and all its children.
 pub fn write_outline_item(
     ctx: &mut PdfContext,
@@ -87,3 +134,68 @@ pub fn write_outline_item(
 
     id
 }
+
+#[cfg(test)]
+mod tests {
+    use ecow::eco_format;
+
+    use super::*;
+
+    /// Build a tree from headings given by their level and page number.
+    fn tree(headings: &[(usize, usize)], page_indices: &[Option<usize>]) -> String {
+        let mut tree = vec![];
+        for (i, &(level, page)) in headings.iter().enumerate() {
+            let Some(index) = page_indices[page - 1] else { continue };
+            let node = HeadingNode {
+                content: eco_format!("{}", i + 1),
+                level,
+                position: Point::zero(),
+                page: Ref::new(index as i32 + 1),
+                children: vec![],
+            };
+            insert_heading(&mut tree, node);
+        }
+        format(&tree)
+    }
+
+    /// Write a tree as nested parentheses.
+    fn format(nodes: &[HeadingNode]) -> String {
+        let mut out = String::new();
+        for (i, node) in nodes.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            out.push_str(&node.content);
+            if !node.children.is_empty() {
+                out.push('(');
+                out.push_str(&format(&node.children));
+                out.push(')');
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_pdf_heading_tree() {
+        let all = [Some(0)];
+        assert_eq!(tree(&[], &all), "");
+        assert_eq!(tree(&[(1, 1), (1, 1)], &all), "1 2");
+        assert_eq!(tree(&[(1, 1), (2, 1), (3, 1), (2, 1), (1, 1)], &all), "1(2(3) 4) 5");
+
+        // Skipped levels still nest and a leading deep heading stays on top.
+        assert_eq!(tree(&[(3, 1), (1, 1), (3, 1), (2, 1)], &all), "1 2(3 4)");
+    }
+
+    #[test]
+    fn test_pdf_heading_tree_pruned() {
+        let headings = [(1, 1), (2, 1), (1, 2), (2, 2), (3, 3), (2, 3)];
+        assert_eq!(tree(&headings, &[Some(0), Some(1), Some(2)]), "1(2) 3(4(5) 6)");
+
+        // Subheadings of a heading on a pruned page move up to the closest
+        // preceding heading of a lower level.
+        assert_eq!(tree(&headings, &[Some(0), None, Some(1)]), "1(2(5) 6)");
+        assert_eq!(tree(&headings, &[None, Some(0), Some(1)]), "3(4(5) 6)");
+        assert_eq!(tree(&headings, &[None, None, Some(0)]), "5 6");
+        assert_eq!(tree(&headings, &[None, None, None]), "");
+    }
+}
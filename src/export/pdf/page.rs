@@ -1,80 +1,154 @@
-use ecow::eco_format;
+use ecow::{eco_format, EcoString};
 use pdf_writer::types::{
     ActionType, AnnotationType, ColorSpaceOperand, LineCapStyle, LineJoinStyle,
 };
-use pdf_writer::writers::ColorSpace;
-use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str};
-
-use super::{deflate, AbsExt, EmExt, PdfContext, RefExt, D65_GRAY, SRGB};
-use crate::doc::{Destination, Frame, FrameItem, GroupItem, Meta, TextItem};
+use pdf_writer::writers::{Annotation, Catalog, ColorSpace, Resources};
+use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str, TextStr};
+
+use super::colr::{ColorLayer, LayerFill};
+use super::extg::ExtGState;
+use super::font::{color_glyph, has_color_glyphs, ColorGlyph};
+use super::form::PdfWidget;
+use super::gradient::{is_translucent, PdfGradient, PdfSoftMask};
+use super::pattern::PdfPattern;
+use super::structure::Mark;
+use super::{deflate, AbsExt, EmExt, PdfContext, RefExt, TransformExt, D65_GRAY, SRGB};
+use crate::doc::{
+    Attachment, Destination, FormField, Frame, FrameItem, Glyph, GroupItem, Meta,
+    PageLabel, PageLabelStyle, TextItem,
+};
 use crate::font::Font;
 use crate::geom::{
-    self, Abs, Color, Em, Geometry, LineCap, LineJoin, Numeric, Paint, Point, Ratio,
-    Shape, Size, Stroke, Transform,
+    self, Abs, Color, Em, Geometry, Gradient, LineCap, LineJoin, Numeric, Paint, Pattern,
+    Point, Ratio, RelativeTo, Shape, Size, Stroke, Transform,
 };
 use crate::image::Image;
+use crate::util::SliceExt;
 
 /// Construct page objects.
+///
+/// Only the pages that are selected for export are constructed.
 pub fn construct_pages(ctx: &mut PdfContext, frames: &[Frame]) {
-    for frame in frames {
-        construct_page(ctx, frame);
+    for (i, frame) in frames.iter().enumerate() {
+        if ctx.page_indices[i].is_some() {
+            construct_page(ctx, frame);
+        }
     }
 }
 
 /// Construct a page object.
 pub fn construct_page(ctx: &mut PdfContext, frame: &Frame) {
     let page_ref = ctx.alloc.bump();
+    let index = ctx.page_refs.len();
     ctx.page_refs.push(page_ref);
     ctx.page_heights.push(frame.height().to_f32());
-
-    let mut ctx = PageContext {
-        parent: ctx,
-        page_ref,
-        content: Content::new(),
-        state: State::default(),
-        saves: vec![],
-        bottom: 0.0,
-        links: vec![],
-    };
+    ctx.page_labels.push(find_label(frame).cloned());
 
     let size = frame.size();
+    let mut ctx = PageContext::new(ctx, page_ref, index, size);
 
     // Make the coordinate system start at the top-left.
-    ctx.bottom = size.y.to_f32();
-    ctx.transform(Transform {
-        sx: Ratio::one(),
-        ky: Ratio::zero(),
-        kx: Ratio::zero(),
-        sy: Ratio::new(-1.0),
-        tx: Abs::zero(),
-        ty: size.y,
-    });
+    ctx.transform(page_transform(size));
 
     // Encode the page into the content stream.
     write_frame(&mut ctx, frame);
 
+    if let Some(tree) = &mut ctx.parent.struct_tree {
+        tree.finish_page(ctx.mcids);
+    }
+
     let page = Page {
         size,
         content: ctx.content,
         id: ctx.page_ref,
         links: ctx.links,
+        attachments: ctx.attachments,
+        widgets: ctx.widgets,
     };
 
     ctx.parent.pages.push(page);
 }
 
+/// Find the label of a page in its frame.
+fn find_label(frame: &Frame) -> Option<&PageLabel> {
+    frame.items().find_map(|(_, item)| match item {
+        FrameItem::Group(group) => find_label(&group.frame),
+        FrameItem::Meta(Meta::PageLabel(label), _) => Some(label),
+        _ => None,
+    })
+}
+
+/// Write the number tree that maps page indices to their labels.
+///
+/// Each entry starts a range of pages whose numbers count up from the
+/// entry's label. Pages without a label are labelled with their index, just
+/// like viewers do when there are no labels.
+pub fn write_page_labels(catalog: &mut Catalog, labels: &[Option<PageLabel>]) {
+    if labels.iter().all(Option::is_none) {
+        return;
+    }
+
+    let mut tree = catalog.insert(Name(b"PageLabels")).dict();
+    let mut nums = tree.insert(Name(b"Nums")).array();
+    let mut prev: Option<&PageLabel> = None;
+
+    for (i, label) in labels.iter().enumerate() {
+        let continues = match (label, prev) {
+            (Some(label), Some(prev)) => label.continues(prev),
+            (None, None) => i > 0,
+            _ => false,
+        };
+
+        prev = label.as_ref();
+        if continues {
+            continue;
+        }
+
+        nums.item(i as i32);
+        let mut entry = nums.push().dict();
+        let Some(label) = label else {
+            entry.pair(Name(b"S"), Name(b"D"));
+            entry.pair(Name(b"St"), i as i32 + 1);
+            continue;
+        };
+
+        if let Some(style) = label.style {
+            let name = match style {
+                PageLabelStyle::Arabic => b"D",
+                PageLabelStyle::LowerRoman => b"r",
+                PageLabelStyle::UpperRoman => b"R",
+                PageLabelStyle::LowerAlpha => b"a",
+                PageLabelStyle::UpperAlpha => b"A",
+            };
+            entry.pair(Name(b"S"), Name(name));
+        }
+
+        if !label.prefix.is_empty() {
+            entry.pair(Name(b"P"), TextStr(&label.prefix));
+        }
+
+        if label.number.get() > 1 {
+            let number = i32::try_from(label.number.get()).unwrap_or(i32::MAX);
+            entry.pair(Name(b"St"), number);
+        }
+    }
+}
+
 /// Write the page tree.
 pub fn write_page_tree(ctx: &mut PdfContext) {
-    for page in std::mem::take(&mut ctx.pages).into_iter() {
-        write_page(ctx, page);
+    for (i, page) in std::mem::take(&mut ctx.pages).into_iter().enumerate() {
+        write_page(ctx, i, page);
     }
 
     let mut pages = ctx.writer.pages(ctx.page_tree_ref);
     pages
         .count(ctx.page_refs.len() as i32)
         .kids(ctx.page_refs.iter().copied());
+    pages.pair(Name(b"Resources"), ctx.global_resources_ref);
+    pages.finish();
 
-    let mut resources = pages.resources();
+    let mut resources =
+        ctx.writer.indirect(ctx.global_resources_ref).start::<Resources>();
     let mut spaces = resources.color_spaces();
     spaces.insert(SRGB).start::<ColorSpace>().srgb();
     spaces.insert(D65_GRAY).start::<ColorSpace>().d65_gray();
@@ -86,6 +160,11 @@ pub fn write_page_tree(ctx: &mut PdfContext) {
         fonts.pair(Name(name.as_bytes()), font_ref);
     }
 
+    for (t, &font_ref) in ctx.type3_refs.iter().enumerate() {
+        let name = eco_format!("T{}", t);
+        fonts.pair(Name(name.as_bytes()), font_ref);
+    }
+
     fonts.finish();
 
     let mut images = resources.x_objects();
@@ -95,12 +174,32 @@ pub fn write_page_tree(ctx: &mut PdfContext) {
     }
 
     images.finish();
+
+    let mut ext_gs_states = resources.ext_g_states();
+    for (gs_ref, gs) in ctx.ext_gs_map.pdf_indices(&ctx.ext_gs_refs) {
+        let name = eco_format!("Gs{}", gs);
+        ext_gs_states.pair(Name(name.as_bytes()), gs_ref);
+    }
+
+    ext_gs_states.finish();
+
+    let mut patterns = resources.insert(Name(b"Pattern")).dict();
+    for (pattern_ref, gr) in ctx.gradient_map.pdf_indices(&ctx.gradient_refs) {
+        let name = eco_format!("Gr{}", gr);
+        patterns.pair(Name(name.as_bytes()), pattern_ref);
+    }
+
+    for (pattern_ref, p) in ctx.pattern_map.pdf_indices(&ctx.pattern_refs) {
+        let name = eco_format!("P{}", p);
+        patterns.pair(Name(name.as_bytes()), pattern_ref);
+    }
+
+    patterns.finish();
     resources.finish();
-    pages.finish();
 }
 
 /// Write a page tree node.
-fn write_page(ctx: &mut PdfContext, page: Page) {
+fn write_page(ctx: &mut PdfContext, index: usize, page: Page) {
     let content_id = ctx.alloc.bump();
 
     let mut page_writer = ctx.writer.page(page.id);
@@ -111,36 +210,70 @@ fn write_page(ctx: &mut PdfContext, page: Page) {
     page_writer.media_box(Rect::new(0.0, 0.0, w, h));
     page_writer.contents(content_id);
 
-    let mut annotations = page_writer.annotations();
+    if ctx.struct_tree.is_some() {
+        // Navigate the page in the order of its logical structure.
+        page_writer.pair(Name(b"StructParents"), index as i32);
+        page_writer.pair(Name(b"Tabs"), Name(b"S"));
+    }
+
+    // The widgets of form fields are referenced by their fields, so the
+    // annotations are a mix of direct and indirect objects.
+    let mut annotations = page_writer.insert(Name(b"Annots")).array();
     for (dest, rect) in page.links {
-        let mut annotation = annotations.push();
-        annotation.subtype(AnnotationType::Link).rect(rect);
-        annotation.border(0.0, 0.0, 0.0, None);
+        // Resolve the target of internal links. Links to pages that are not
+        // exported are left out.
+        let target = match &dest {
+            Destination::Url(_) => None,
+            Destination::Position(pos) => Some(*pos),
+            Destination::Location(loc) => Some(ctx.introspector.position(*loc)),
+        };
 
-        let pos = match dest {
-            Destination::Url(uri) => {
-                annotation
-                    .action()
-                    .action_type(ActionType::Uri)
-                    .uri(Str(uri.as_bytes()));
-                continue;
-            }
-            Destination::Position(pos) => pos,
-            Destination::Location(loc) => ctx.introspector.position(loc),
+        let target = match target {
+            Some(pos) => match ctx.page_indices.get(pos.page.get() - 1) {
+                Some(&Some(index)) => Some((pos, index)),
+                _ => continue,
+            },
+            None => None,
         };
 
-        let index = pos.page.get() - 1;
-        let y = (pos.point.y - Abs::pt(10.0)).max(Abs::zero());
-        if let Some(&height) = ctx.page_heights.get(index) {
-            annotation
-                .action()
+        let mut annotation = annotations.push().start::<Annotation>();
+        annotation.subtype(AnnotationType::Link).rect(rect);
+        annotation.border(0.0, 0.0, 0.0, None);
+        if ctx.standard.is_some() {
+            // PDF/A requires annotations to be printable.
+            annotation.pair(Name(b"F"), 4);
+        }
+
+        let mut action = annotation.action();
+        if let Some((pos, index)) = target {
+            let height = ctx.page_heights[index];
+            let y = (pos.point.y - Abs::pt(10.0)).max(Abs::zero());
+            action
                 .action_type(ActionType::GoTo)
                 .destination_direct()
                 .page(ctx.page_refs[index])
                 .xyz(pos.point.x.to_f32(), height - y.to_f32(), None);
+        } else if let Destination::Url(uri) = &dest {
+            action.action_type(ActionType::Uri).uri(Str(uri.as_bytes()));
         }
     }
 
+    for (attachment, rect) in page.attachments {
+        let spec_ref = ctx.attachment_refs[ctx.attachment_map.map(attachment)];
+        let mut annotation = annotations.push().start::<Annotation>();
+        annotation.pair(Name(b"Subtype"), Name(b"FileAttachment"));
+        annotation.rect(rect);
+        annotation.pair(Name(b"FS"), spec_ref);
+        annotation.pair(Name(b"Name"), Name(b"Paperclip"));
+    }
+
+    for (field, rect) in page.widgets {
+        let widget_ref = ctx.alloc.bump();
+        annotations.item(widget_ref);
+        ctx.widgets
+            .push(PdfWidget { field, rect, page_ref: page.id, widget_ref });
+    }
+
     annotations.finish();
     page_writer.finish();
 
@@ -159,17 +292,32 @@ pub struct Page {
     pub content: Content,
     /// Links in the PDF coordinate system.
     pub links: Vec<(Destination, Rect)>,
+    /// Areas that link to attached files in the PDF coordinate system.
+    pub attachments: Vec<(Attachment, Rect)>,
+    /// The areas of form fields in the PDF coordinate system.
+    pub widgets: Vec<(FormField, Rect)>,
 }
 
 /// An exporter for the contents of a single PDF page.
 struct PageContext<'a, 'b> {
     parent: &'a mut PdfContext<'b>,
     page_ref: Ref,
+    /// The index of the page among the exported pages.
+    index: usize,
+    /// The page's dimensions.
+    size: Size,
     content: Content,
     state: State,
     saves: Vec<State>,
     bottom: f32,
     links: Vec<(Destination, Rect)>,
+    attachments: Vec<(Attachment, Rect)>,
+    widgets: Vec<(FormField, Rect)>,
+    /// The structure marks of the current item, from the outermost to the
+    /// innermost.
+    marks: Vec<Mark>,
+    /// The structure element of each marked-content sequence on the page.
+    mcids: Vec<usize>,
 }
 
 /// A simulated graphics state used to deduplicate graphics state changes and
@@ -177,11 +325,51 @@ struct PageContext<'a, 'b> {
 #[derive(Debug, Default, Clone)]
 struct State {
     transform: Transform,
-    font: Option<(Font, Abs)>,
+    font: Option<(PdfFont, Abs)>,
     fill: Option<Paint>,
     fill_space: Option<Name<'static>>,
     stroke: Option<Stroke>,
     stroke_space: Option<Name<'static>>,
+    external_graphics_state: ExtGState,
+    /// The soft mask of the fill if it is a gradient with translucent stops.
+    fill_mask: Option<usize>,
+    /// The soft mask of the stroke if it is a gradient with translucent stops.
+    stroke_mask: Option<usize>,
+}
+
+/// A font resource that text can be set in.
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum PdfFont {
+    /// A font that is embedded as is.
+    Embedded(Font),
+    /// A Type3 font with glyphs of a variable font instance.
+    Type3(usize),
+}
+
+impl<'a, 'b> PageContext<'a, 'b> {
+    /// Create a context for a page or pattern tile with the given size.
+    fn new(
+        parent: &'a mut PdfContext<'b>,
+        page_ref: Ref,
+        index: usize,
+        size: Size,
+    ) -> Self {
+        Self {
+            parent,
+            page_ref,
+            index,
+            size,
+            content: Content::new(),
+            state: State::default(),
+            saves: vec![],
+            bottom: size.y.to_f32(),
+            links: vec![],
+            attachments: vec![],
+            widgets: vec![],
+            marks: vec![],
+            mcids: vec![],
+        }
+    }
 }
 
 impl PageContext<'_, '_> {
@@ -196,41 +384,78 @@ impl PageContext<'_, '_> {
     }
 
     fn transform(&mut self, transform: Transform) {
-        let Transform { sx, ky, kx, sy, tx, ty } = transform;
         self.state.transform = self.state.transform.pre_concat(transform);
-        self.content.transform([
-            sx.get() as _,
-            ky.get() as _,
-            kx.get() as _,
-            sy.get() as _,
-            tx.to_f32(),
-            ty.to_f32(),
-        ]);
+        self.content.transform(transform.to_pdf_matrix());
     }
 
-    fn set_font(&mut self, font: &Font, size: Abs) {
+    fn set_external_graphics_state(&mut self, graphics_state: &ExtGState) {
+        if self.state.external_graphics_state != *graphics_state {
+            self.parent.ext_gs_map.insert(*graphics_state);
+            let name = eco_format!("Gs{}", self.parent.ext_gs_map.map(*graphics_state));
+            self.content.set_parameters(Name(name.as_bytes()));
+            self.state.external_graphics_state = *graphics_state;
+        }
+    }
+
+    fn set_opacities(&mut self, stroke: Option<&Stroke>, fill: Option<&Paint>) {
+        let opacity = |paint: &Paint| match paint {
+            Paint::Solid(Color::Rgba(c)) => c.a,
+            Paint::Solid(Color::Luma(_) | Color::Cmyk(_))
+            | Paint::Gradient(_)
+            | Paint::Pattern(_) => 255,
+        };
+
+        self.set_external_graphics_state(&ExtGState {
+            stroke_opacity: stroke.map_or(255, |stroke| opacity(&stroke.paint)),
+            fill_opacity: fill.map_or(255, opacity),
+            soft_mask: fill
+                .and(self.state.fill_mask)
+                .or(stroke.and(self.state.stroke_mask)),
+        });
+    }
+
+    fn set_font(&mut self, font: &PdfFont, size: Abs) {
         if self.state.font.as_ref().map(|(f, s)| (f, *s)) != Some((font, size)) {
-            self.parent.font_map.insert(font.clone());
-            let name = eco_format!("F{}", self.parent.font_map.map(font.clone()));
+            let name = match font {
+                PdfFont::Embedded(font) => {
+                    self.parent.font_map.insert(font.clone());
+                    eco_format!("F{}", self.parent.font_map.map(font.clone()))
+                }
+                PdfFont::Type3(index) => eco_format!("T{}", index),
+            };
             self.content.set_font(Name(name.as_bytes()), size.to_f32());
             self.state.font = Some((font.clone(), size));
         }
     }
 
-    fn set_fill(&mut self, fill: &Paint) {
-        if self.state.fill.as_ref() != Some(fill) {
+    /// Set the fill paint. A gradient or pattern is placed relative to the
+    /// given bounding box in the current coordinate system unless it is
+    /// relative to the page.
+    fn set_fill(&mut self, fill: &Paint, on_text: bool, bbox: (Point, Size)) {
+        // The same gradient or pattern looks different on a different box, so
+        // it is always set anew.
+        if self.state.fill.as_ref() != Some(fill) || !matches!(fill, Paint::Solid(_)) {
             let f = |c| c as f32 / 255.0;
-            let Paint::Solid(color) = fill;
-            match color {
-                Color::Luma(c) => {
+            self.state.fill_mask = None;
+            match fill {
+                Paint::Gradient(gradient) => {
+                    let placed = self.place_gradient(gradient, on_text, bbox);
+                    self.set_fill_gradient(placed);
+                }
+                Paint::Pattern(pattern) => {
+                    let name = self.pattern(pattern, on_text, bbox.0);
+                    self.set_fill_color_space(Name(b"Pattern"));
+                    self.content.set_fill_pattern(None, Name(name.as_bytes()));
+                }
+                Paint::Solid(Color::Luma(c)) => {
                     self.set_fill_color_space(D65_GRAY);
                     self.content.set_fill_gray(f(c.0));
                 }
-                Color::Rgba(c) => {
+                Paint::Solid(Color::Rgba(c)) => {
                     self.set_fill_color_space(SRGB);
                     self.content.set_fill_color([f(c.r), f(c.g), f(c.b)]);
                 }
-                Color::Cmyk(c) => {
+                Paint::Solid(Color::Cmyk(c)) => {
                     self.reset_fill_color_space();
                     self.content.set_fill_cmyk(f(c.c), f(c.m), f(c.y), f(c.k));
                 }
@@ -239,6 +464,16 @@ impl PageContext<'_, '_> {
         }
     }
 
+    /// Set a gradient that is already placed as the fill paint.
+    fn set_fill_gradient(&mut self, placed: PdfGradient) {
+        let fill = Paint::Gradient(placed.gradient.clone());
+        let (name, mask) = self.gradient(placed);
+        self.state.fill_mask = mask;
+        self.set_fill_color_space(Name(b"Pattern"));
+        self.content.set_fill_pattern(None, Name(name.as_bytes()));
+        self.state.fill = Some(fill);
+    }
+
     fn set_fill_color_space(&mut self, space: Name<'static>) {
         if self.state.fill_space != Some(space) {
             self.content.set_fill_color_space(ColorSpaceOperand::Named(space));
@@ -250,8 +485,13 @@ impl PageContext<'_, '_> {
         self.state.fill_space = None;
     }
 
-    fn set_stroke(&mut self, stroke: &Stroke) {
-        if self.state.stroke.as_ref() != Some(stroke) {
+    /// Set the stroke. A gradient or pattern is placed relative to the given
+    /// bounding box in the current coordinate system unless it is relative to
+    /// the page.
+    fn set_stroke(&mut self, stroke: &Stroke, bbox: (Point, Size)) {
+        if self.state.stroke.as_ref() != Some(stroke)
+            || !matches!(stroke.paint, Paint::Solid(_))
+        {
             let Stroke {
                 paint,
                 thickness,
@@ -262,17 +502,29 @@ impl PageContext<'_, '_> {
             } = stroke;
 
             let f = |c| c as f32 / 255.0;
-            let Paint::Solid(color) = paint;
-            match color {
-                Color::Luma(c) => {
+            self.state.stroke_mask = None;
+            match paint {
+                Paint::Gradient(gradient) => {
+                    let placed = self.place_gradient(gradient, false, bbox);
+                    let (name, mask) = self.gradient(placed);
+                    self.state.stroke_mask = mask;
+                    self.set_stroke_color_space(Name(b"Pattern"));
+                    self.content.set_stroke_pattern(None, Name(name.as_bytes()));
+                }
+                Paint::Pattern(pattern) => {
+                    let name = self.pattern(pattern, false, bbox.0);
+                    self.set_stroke_color_space(Name(b"Pattern"));
+                    self.content.set_stroke_pattern(None, Name(name.as_bytes()));
+                }
+                Paint::Solid(Color::Luma(c)) => {
                     self.set_stroke_color_space(D65_GRAY);
                     self.content.set_stroke_gray(f(c.0));
                 }
-                Color::Rgba(c) => {
+                Paint::Solid(Color::Rgba(c)) => {
                     self.set_stroke_color_space(SRGB);
                     self.content.set_stroke_color([f(c.r), f(c.g), f(c.b)]);
                 }
-                Color::Cmyk(c) => {
+                Paint::Solid(Color::Cmyk(c)) => {
                     self.reset_stroke_color_space();
                     self.content.set_stroke_cmyk(f(c.c), f(c.m), f(c.y), f(c.k));
                 }
@@ -312,26 +564,194 @@ impl PageContext<'_, '_> {
     fn reset_stroke_color_space(&mut self) {
         self.state.stroke_space = None;
     }
+
+    /// Place a gradient on the page relative to the given bounding box or the
+    /// page.
+    fn place_gradient(
+        &self,
+        gradient: &Gradient,
+        on_text: bool,
+        (origin, size): (Point, Size),
+    ) -> PdfGradient {
+        match gradient.relative_to(on_text) {
+            RelativeTo::Shape => PdfGradient {
+                gradient: gradient.clone(),
+                size,
+                transform: self
+                    .state
+                    .transform
+                    .pre_concat(Transform::translate(origin.x, origin.y)),
+            },
+            RelativeTo::Page => PdfGradient {
+                gradient: gradient.clone(),
+                size: self.size,
+                transform: page_transform(self.size),
+            },
+        }
+    }
+
+    /// Return the name of a placed gradient's pattern as well as its soft
+    /// mask if it has translucent stops.
+    fn gradient(&mut self, placed: PdfGradient) -> (EcoString, Option<usize>) {
+        // The mask is applied in the current coordinate system. If that is
+        // degenerate, nothing is visible anyway.
+        let mask = match self.state.transform.invert() {
+            Some(inverse) if is_translucent(&placed.gradient) => {
+                let mask = PdfSoftMask {
+                    gradient: placed.clone(),
+                    transform: inverse,
+                    size: self.size,
+                };
+                self.parent.soft_mask_map.insert(mask.clone());
+                Some(self.parent.soft_mask_map.map(mask))
+            }
+            _ => None,
+        };
+
+        self.parent.gradient_map.insert(placed.clone());
+        let name = eco_format!("Gr{}", self.parent.gradient_map.map(placed));
+        (name, mask)
+    }
+
+    /// Place a pattern on the page and return its name.
+    fn pattern(&mut self, pattern: &Pattern, on_text: bool, origin: Point) -> EcoString {
+        if !self.parent.pattern_tiles.contains_key(pattern) {
+            let tile = self.encode_tile(pattern);
+            self.parent.pattern_tiles.insert(pattern.clone(), tile);
+        }
+
+        let transform = match pattern.relative_to(on_text) {
+            RelativeTo::Shape => self
+                .state
+                .transform
+                .pre_concat(Transform::translate(origin.x, origin.y)),
+            RelativeTo::Page => page_transform(self.size),
+        };
+
+        // The tile's content stream flips its own coordinate system, so the
+        // pattern space must be flipped back.
+        let placed = PdfPattern {
+            pattern: pattern.clone(),
+            transform: transform.pre_concat(page_transform(pattern.size())),
+        };
+
+        self.parent.pattern_map.insert(placed.clone());
+        eco_format!("P{}", self.parent.pattern_map.map(placed))
+    }
+
+    /// Encode the tile of a pattern into a compressed content stream.
+    ///
+    /// Links in the tile are dropped and its content doesn't belong to the
+    /// logical structure of the document.
+    fn encode_tile(&mut self, pattern: &Pattern) -> Vec<u8> {
+        let struct_tree = self.parent.struct_tree.take();
+        let size = pattern.size();
+
+        let mut ctx =
+            PageContext::new(&mut *self.parent, self.page_ref, self.index, size);
+
+        ctx.transform(page_transform(size));
+        write_frame(&mut ctx, pattern.frame());
+
+        let content = ctx.content.finish();
+        self.parent.struct_tree = struct_tree;
+        deflate(&content)
+    }
 }
 
 /// Encode a frame into the content stream.
 fn write_frame(ctx: &mut PageContext, frame: &Frame) {
+    let inherited = ctx.marks.len();
+    let mut area = None;
+
     for &(pos, ref item) in frame.items() {
+        if ctx.parent.struct_tree.is_some() {
+            track_marks(ctx, inherited, &mut area, pos, item);
+        }
+
         let x = pos.x.to_f32();
         let y = pos.y.to_f32();
         match item {
             FrameItem::Group(group) => write_group(ctx, pos, group),
-            FrameItem::Text(text) => write_text(ctx, x, y, text),
-            FrameItem::Shape(shape, _) => write_shape(ctx, x, y, shape),
-            FrameItem::Image(image, size, _) => write_image(ctx, x, y, image, *size),
+            FrameItem::Text(text) => write_marked(ctx, |ctx| write_text(ctx, x, y, text)),
+            FrameItem::Shape(shape, _) => {
+                write_marked(ctx, |ctx| write_shape(ctx, x, y, shape))
+            }
+            FrameItem::Image(image, size, _) => {
+                write_marked(ctx, |ctx| write_image(ctx, x, y, image, *size))
+            }
             FrameItem::Meta(meta, size) => match meta {
                 Meta::Link(dest) => write_link(ctx, pos, dest, *size),
                 Meta::Elem(_) => {}
+                Meta::Structure(_) => {}
+                Meta::Cell(..) => {}
+                Meta::Artifact => {}
                 Meta::Hide => {}
                 Meta::PageNumbering(_) => {}
+                Meta::PageLabel(_) => {}
+                Meta::Attachment(attachment) => {
+                    write_attachment(ctx, pos, attachment, *size)
+                }
+                Meta::FormField(field) => write_widget(ctx, pos, field, *size),
             },
         }
     }
+
+    ctx.marks.truncate(inherited);
+}
+
+/// Keep track of the structure marks that apply to the items of a frame.
+///
+/// Layout attaches metadata at the start of the frames that elements produce,
+/// with the size of the frame. When frames are merged into their parent, a
+/// run of metadata thus applies to the following items within its area.
+fn track_marks(
+    ctx: &mut PageContext,
+    inherited: usize,
+    area: &mut Option<(Point, Size)>,
+    pos: Point,
+    item: &FrameItem,
+) {
+    if let FrameItem::Meta(meta, size) = item {
+        if *area != Some((pos, *size)) {
+            ctx.marks.truncate(inherited);
+            *area = Some((pos, *size));
+        }
+        ctx.marks.extend(Mark::new(meta));
+    } else if let Some((start, size)) = *area {
+        let end = start + size.to_point();
+        if !(start.x..=end.x).contains(&pos.x) || !(start.y..=end.y).contains(&pos.y) {
+            ctx.marks.truncate(inherited);
+            *area = None;
+        }
+    }
+}
+
+/// Encode an item as a marked-content sequence of the structure element it
+/// belongs to, or as an artifact if it doesn't belong to any.
+fn write_marked(ctx: &mut PageContext, f: impl FnOnce(&mut PageContext)) {
+    let Some(tree) = &mut ctx.parent.struct_tree else {
+        f(ctx);
+        return;
+    };
+
+    match tree.resolve(&ctx.marks) {
+        Some(node) => {
+            let mcid = ctx.mcids.len() as i32;
+            let tag = tree.push_content(node, ctx.index, mcid);
+            ctx.mcids.push(node);
+            ctx.content
+                .begin_marked_content_with_properties(tag)
+                .properties_direct()
+                .identify(mcid);
+        }
+        None => {
+            ctx.content.begin_marked_content(Name(b"Artifact"));
+        }
+    }
+
+    f(ctx);
+    ctx.content.end_marked_content();
 }
 
 /// Encode a group into the content stream.
@@ -358,73 +778,262 @@ fn write_group(ctx: &mut PageContext, pos: Point, group: &GroupItem) {
 }
 
 /// Encode a text run into the content stream.
+///
+/// Glyphs from a font's color tables are drawn one by one as images or filled
+/// outlines and the others as text in the embedded font.
 fn write_text(ctx: &mut PageContext, x: f32, y: f32, text: &TextItem) {
     *ctx.parent.languages.entry(text.lang).or_insert(0) += text.glyphs.len();
-    ctx.parent
-        .glyph_sets
-        .entry(text.font.clone())
-        .or_default()
-        .extend(text.glyphs.iter().map(|g| g.id));
-
-    ctx.set_fill(&text.fill);
-    ctx.set_font(&text.font, text.size);
+
+    let origin = Point::new(Abs::pt(x.into()), Abs::pt(y.into()) - text.size);
+    ctx.set_fill(&text.fill, true, (origin, Size::new(text.width(), text.size)));
+    ctx.set_opacities(None, Some(&text.fill));
+
+    if !has_color_glyphs(&text.font) {
+        write_glyphs(ctx, x, y, text, &text.glyphs);
+        return;
+    }
+
+    // The start of the current run of outline glyphs and the pen position.
+    let mut run = (x, 0);
+    let mut cursor = x;
+    for (i, glyph) in text.glyphs.iter().enumerate() {
+        let advance = glyph.x_advance.at(text.size).to_f32();
+        if let Some(color) = color_glyph(&text.font, glyph.id) {
+            write_glyphs(ctx, run.0, y, text, &text.glyphs[run.1..i]);
+            let offset = glyph.x_offset.at(text.size).to_f32();
+            let actual = actual_text(text, i);
+            write_color_glyph(ctx, cursor + offset, y, text, &actual, &color);
+            run = (cursor + advance, i + 1);
+        }
+        cursor += advance;
+    }
+
+    write_glyphs(ctx, run.0, y, text, &text.glyphs[run.1..]);
+}
+
+/// Encode glyphs of a text run as text.
+fn write_glyphs(
+    ctx: &mut PageContext,
+    x: f32,
+    y: f32,
+    text: &TextItem,
+    glyphs: &[Glyph],
+) {
+    if glyphs.is_empty() {
+        return;
+    }
+
+    // Determine the font resource and code of each glyph. Variable font
+    // instances can't be embedded as they are, so their glyphs are drawn
+    // through Type3 fonts instead.
+    let codes: Vec<(PdfFont, u16, &Glyph)> = if text.font.variations().is_empty() {
+        ctx.parent
+            .glyph_sets
+            .entry(text.font.clone())
+            .or_default()
+            .extend(glyphs.iter().map(|g| g.id));
+
+        let font = PdfFont::Embedded(text.font.clone());
+        glyphs.iter().map(|g| (font.clone(), g.id, g)).collect()
+    } else {
+        glyphs
+            .iter()
+            .map(|g| {
+                let (index, code) = ctx.parent.type3_map.code(&text.font, g.id);
+                (PdfFont::Type3(index), code.into(), g)
+            })
+            .collect()
+    };
+
     ctx.content.begin_text();
 
-    // Positiosn the text.
+    // Position the text.
     ctx.content.set_text_matrix([1.0, 0.0, 0.0, -1.0, x, y]);
 
-    let mut positioned = ctx.content.show_positioned();
-    let mut items = positioned.items();
     let mut adjustment = Em::zero();
-    let mut encoded = vec![];
+    for (font, run) in codes.group_by_key(|(font, ..)| font.clone()) {
+        ctx.set_font(&font, text.size);
+
+        let mut positioned = ctx.content.show_positioned();
+        let mut items = positioned.items();
+        let mut encoded = vec![];
+
+        // Write the glyphs with kerning adjustments.
+        for &(_, code, glyph) in run {
+            adjustment += glyph.x_offset;
 
-    // Write the glyphs with kerning adjustments.
-    for glyph in &text.glyphs {
-        adjustment += glyph.x_offset;
+            if !adjustment.is_zero() {
+                if !encoded.is_empty() {
+                    items.show(Str(&encoded));
+                    encoded.clear();
+                }
 
-        if !adjustment.is_zero() {
-            if !encoded.is_empty() {
-                items.show(Str(&encoded));
-                encoded.clear();
+                items.adjust(-adjustment.to_font_units());
+                adjustment = Em::zero();
             }
 
-            items.adjust(-adjustment.to_font_units());
-            adjustment = Em::zero();
-        }
+            match font {
+                PdfFont::Embedded(_) => encoded.extend(code.to_be_bytes()),
+                PdfFont::Type3(_) => encoded.push(code as u8),
+            }
 
-        encoded.push((glyph.id >> 8) as u8);
-        encoded.push((glyph.id & 0xff) as u8);
+            if let Some(advance) = text.font.advance(glyph.id) {
+                adjustment += glyph.x_advance - advance;
+            }
 
-        if let Some(advance) = text.font.advance(glyph.id) {
-            adjustment += glyph.x_advance - advance;
+            adjustment -= glyph.x_offset;
         }
 
-        adjustment -= glyph.x_offset;
-    }
+        if !encoded.is_empty() {
+            items.show(Str(&encoded));
+        }
 
-    if !encoded.is_empty() {
-        items.show(Str(&encoded));
+        items.finish();
+        positioned.finish();
     }
 
-    items.finish();
-    positioned.finish();
     ctx.content.end_text();
 }
 
+/// The text that the glyph at the given index of a run stands for.
+///
+/// A cluster's whole text belongs to its first glyph, so that flags and emoji
+/// sequences are extracted in full, and the cluster's other glyphs stand for
+/// nothing.
+fn actual_text(text: &TextItem, i: usize) -> EcoString {
+    let glyph = &text.glyphs[i];
+    if i > 0 && text.glyphs[i - 1].range == glyph.range {
+        return EcoString::new();
+    }
+
+    let mut buf = [0; 4];
+    match text.cluster(glyph) {
+        "" => (&*glyph.c.encode_utf8(&mut buf)).into(),
+        cluster => cluster.into(),
+    }
+}
+
+/// Draw a glyph from the font's color tables with its pen position at the
+/// given point.
+fn write_color_glyph(
+    ctx: &mut PageContext,
+    x: f32,
+    y: f32,
+    text: &TextItem,
+    actual: &str,
+    color: &ColorGlyph,
+) {
+    // Keep the glyph's text extractable even though it is drawn as graphics.
+    ctx.content
+        .begin_marked_content_with_properties(Name(b"Span"))
+        .properties_direct()
+        .actual_text(TextStr(actual));
+
+    let upem = Abs::pt(text.font.units_per_em());
+    let scale = text.size / upem;
+    match color {
+        ColorGlyph::Image(image, size) => {
+            let ascender = text.font.metrics().ascender.at(text.size).to_f32();
+            write_image(ctx, x, y - ascender, image, *size * scale);
+        }
+        ColorGlyph::Layers(layers) => {
+            // Font units point upwards, so we flip the y-axis.
+            ctx.save_state();
+            ctx.transform(Transform {
+                sx: Ratio::new(scale),
+                sy: Ratio::new(-scale),
+                tx: Abs::pt(x.into()),
+                ty: Abs::pt(y.into()),
+                ..Transform::identity()
+            });
+
+            for layer in layers.iter().filter(|layer| !layer.path.0.is_empty()) {
+                write_color_layer(ctx, text, layer);
+            }
+
+            ctx.restore_state();
+        }
+    }
+
+    ctx.content.end_marked_content();
+}
+
+/// Fill a layer of a color glyph in font units.
+fn write_color_layer(ctx: &mut PageContext, text: &TextItem, layer: &ColorLayer) {
+    match &layer.fill {
+        LayerFill::Solid(color) => {
+            let fill = match color {
+                Some(color) => Paint::Solid(Color::Rgba(*color)),
+                None => text.fill.clone(),
+            };
+
+            let upem = Size::splat(Abs::pt(text.font.units_per_em()));
+            ctx.set_fill(&fill, false, (Point::zero(), upem));
+            ctx.set_opacities(None, Some(&fill));
+            write_path(ctx, 0.0, 0.0, &layer.path);
+            ctx.content.fill_nonzero();
+        }
+        LayerFill::Gradient(gradient, size, transform) => {
+            ctx.set_fill_gradient(PdfGradient {
+                gradient: gradient.clone(),
+                size: *size,
+                transform: ctx.state.transform.pre_concat(*transform),
+            });
+            ctx.set_opacities(None, Some(&Paint::Gradient(gradient.clone())));
+            write_path(ctx, 0.0, 0.0, &layer.path);
+            ctx.content.fill_nonzero();
+        }
+        LayerFill::Wedges(wedges) => {
+            ctx.save_state();
+            write_path(ctx, 0.0, 0.0, &layer.path);
+            ctx.content.clip_nonzero();
+            ctx.content.end_path();
+
+            for (wedge, color) in wedges {
+                let fill = Paint::Solid(Color::Rgba(*color));
+                ctx.set_fill(&fill, false, (Point::zero(), Size::zero()));
+                ctx.set_opacities(None, Some(&fill));
+                write_path(ctx, 0.0, 0.0, wedge);
+                ctx.content.fill_nonzero();
+            }
+
+            ctx.restore_state();
+        }
+    }
+}
+
 /// Encode a geometrical shape into the content stream.
 fn write_shape(ctx: &mut PageContext, x: f32, y: f32, shape: &Shape) {
     if shape.fill.is_none() && shape.stroke.is_none() {
         return;
     }
 
+    // A soft mask applies to both the fill and the stroke, so a shape whose
+    // fill or stroke needs one is filled and stroked separately.
+    if let (Some(fill), Some(stroke)) = (&shape.fill, &shape.stroke) {
+        let translucent = |paint: &Paint| match paint {
+            Paint::Gradient(gradient) => is_translucent(gradient),
+            Paint::Solid(_) | Paint::Pattern(_) => false,
+        };
+        if translucent(fill) || translucent(&stroke.paint) {
+            write_shape(ctx, x, y, &Shape { stroke: None, ..shape.clone() });
+            write_shape(ctx, x, y, &Shape { fill: None, ..shape.clone() });
+            return;
+        }
+    }
+
+    let (origin, size) = shape.geometry.bbox();
+    let bbox = (Point::new(Abs::pt(x.into()), Abs::pt(y.into())) + origin, size);
     if let Some(fill) = &shape.fill {
-        ctx.set_fill(fill);
+        ctx.set_fill(fill, false, bbox);
     }
 
     if let Some(stroke) = &shape.stroke {
-        ctx.set_stroke(stroke);
+        ctx.set_stroke(stroke, bbox);
     }
 
+    ctx.set_opacities(shape.stroke.as_ref(), shape.fill.as_ref());
+
     match shape.geometry {
         Geometry::Line(target) => {
             let dx = target.x.to_f32();
@@ -452,6 +1061,19 @@ fn write_shape(ctx: &mut PageContext, x: f32, y: f32, shape: &Shape) {
     };
 }
 
+/// The transform that makes the coordinate system of a page with the given
+/// size start at the top-left.
+fn page_transform(size: Size) -> Transform {
+    Transform {
+        sx: Ratio::one(),
+        ky: Ratio::zero(),
+        kx: Ratio::zero(),
+        sy: Ratio::new(-1.0),
+        tx: Abs::zero(),
+        ty: size.y,
+    }
+}
+
 /// Encode a bezier path into the content stream.
 fn write_path(ctx: &mut PageContext, x: f32, y: f32, path: &geom::Path) {
     for elem in &path.0 {
@@ -489,12 +1111,39 @@ fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size)
 
 /// Save a link for later writing in the annotations dictionary.
 fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size) {
+    let rect = annotation_rect(ctx, pos, size);
+    ctx.links.push((dest.clone(), rect));
+}
+
+/// Save an attached file for later embedding and link to it if the metadata
+/// covers an area.
+fn write_attachment(
+    ctx: &mut PageContext,
+    pos: Point,
+    attachment: &Attachment,
+    size: Size,
+) {
+    ctx.parent.attachment_map.insert(attachment.clone());
+    if !size.is_zero() {
+        let rect = annotation_rect(ctx, pos, size);
+        ctx.attachments.push((attachment.clone(), rect));
+    }
+}
+
+/// Save the area of a form field for later writing as a widget annotation.
+fn write_widget(ctx: &mut PageContext, pos: Point, field: &FormField, size: Size) {
+    let rect = annotation_rect(ctx, pos, size);
+    ctx.widgets.push((field.clone(), rect));
+}
+
+/// The bounding box of a transformed area in the PDF coordinate system.
+fn annotation_rect(ctx: &PageContext, pos: Point, size: Size) -> Rect {
     let mut min_x = Abs::inf();
     let mut min_y = Abs::inf();
     let mut max_x = -Abs::inf();
     let mut max_y = -Abs::inf();
 
-    // Compute the bounding box of the transformed link.
+    // Compute the bounding box of the transformed area.
     for point in [
         pos,
         pos + Point::with_x(size.x),
@@ -512,9 +1161,7 @@ fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size)
     let x2 = max_x.to_f32();
     let y1 = max_y.to_f32();
     let y2 = min_y.to_f32();
-    let rect = Rect::new(x1, y1, x2, y2);
-
-    ctx.links.push((dest.clone(), rect));
+    Rect::new(x1, y1, x2, y2)
 }
 
 impl From<&LineCap> for LineCapStyle {
@@ -536,3 +1183,98 @@ impl From<&LineJoin> for LineJoinStyle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::{Document, Lang};
+    use crate::export::PdfOptions;
+    use crate::syntax::Span;
+    use crate::util::Buffer;
+
+    fn track(
+        ctx: &mut PageContext,
+        area: &mut Option<(Point, Size)>,
+        y: f64,
+        item: &FrameItem,
+    ) -> Vec<Mark> {
+        track_marks(ctx, 0, area, Point::with_y(Abs::pt(y)), item);
+        ctx.marks.clone()
+    }
+
+    #[test]
+    fn test_pdf_track_marks() {
+        let document = Document::default();
+        let options = PdfOptions { tagged: true, ..PdfOptions::default() };
+        let mut parent = PdfContext::new(&document, &options);
+        let size = Size::splat(Abs::pt(100.0));
+        let mut ctx = PageContext::new(&mut parent, Ref::new(1), 0, size);
+
+        let meta = |meta, height| {
+            FrameItem::Meta(meta, Size::new(Abs::pt(100.0), Abs::pt(height)))
+        };
+        let line = FrameItem::Shape(
+            Geometry::Line(Point::with_x(Abs::pt(100.0))).stroked(Stroke::default()),
+            Span::detached(),
+        );
+
+        // Metadata in the same area accumulates and applies to the items
+        // within it, including those on its edge.
+        let area = &mut None;
+        let cell = Mark::Cell(0, 0);
+        assert_eq!(
+            track(&mut ctx, area, 0.0, &meta(Meta::Cell(0, 0), 40.0)),
+            [cell.clone()]
+        );
+        assert_eq!(
+            track(&mut ctx, area, 0.0, &meta(Meta::Artifact, 40.0)),
+            [cell, Mark::Artifact],
+        );
+        assert_eq!(track(&mut ctx, area, 40.0, &line).len(), 2);
+
+        // Metadata for another area replaces it.
+        assert_eq!(
+            track(&mut ctx, area, 50.0, &meta(Meta::Artifact, 20.0)),
+            [Mark::Artifact]
+        );
+        assert_eq!(track(&mut ctx, area, 60.0, &line), [Mark::Artifact]);
+
+        // Items outside of the area have no marks.
+        assert!(track(&mut ctx, area, 90.0, &line).is_empty());
+        assert!(track(&mut ctx, area, 60.0, &line).is_empty());
+    }
+
+    #[test]
+    fn test_pdf_actual_text() {
+        let data = include_bytes!("../../../assets/fonts/PTSans-Regular.ttf");
+        let font = Font::new(Buffer::from(&data[..]), 0).unwrap();
+        let glyph = |c, range| Glyph {
+            id: 0,
+            x_advance: Em::one(),
+            x_offset: Em::zero(),
+            c,
+            range,
+            span: Span::detached(),
+            offset: 0,
+        };
+
+        // A flag is a cluster of two regional indicators, which is drawn as
+        // two glyphs here.
+        let text = TextItem {
+            font,
+            size: Abs::pt(10.0),
+            fill: Color::BLACK.into(),
+            lang: Lang::ENGLISH,
+            text: "🇩🇪x".into(),
+            glyphs: vec![glyph('🇩', 0..8), glyph('🇩', 0..8), glyph('x', 8..9)],
+        };
+
+        assert_eq!(actual_text(&text, 0), "🇩🇪");
+        assert_eq!(actual_text(&text, 1), "");
+        assert_eq!(actual_text(&text, 2), "x");
+
+        // Without the run's text, only the first character is known.
+        let text = TextItem { text: EcoString::new(), ..text };
+        assert_eq!(actual_text(&text, 0), "🇩");
+    }
+}
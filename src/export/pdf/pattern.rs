@@ -0,0 +1,48 @@
+use pdf_writer::{Filter, Name};
+
+use super::{AbsExt, PdfContext, RefExt, TransformExt};
+use crate::geom::{Pattern, Transform};
+
+/// A pattern as it is placed on a page.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PdfPattern {
+    /// The pattern itself.
+    pub pattern: Pattern,
+    /// The transform from the pattern space, in which the tile spans from the
+    /// origin to its size with the y-axis pointing upwards, to the default
+    /// coordinate system of the page.
+    pub transform: Transform,
+}
+
+/// Embed all used patterns into the PDF as colored tiling patterns.
+///
+/// The tiles share the resources of the pages.
+pub fn write_patterns(ctx: &mut PdfContext) {
+    for PdfPattern { pattern, transform } in ctx.pattern_map.items() {
+        let pattern_ref = ctx.alloc.bump();
+        ctx.pattern_refs.push(pattern_ref);
+
+        let size = pattern.size();
+        let cell = pattern.cell();
+        let content = &ctx.pattern_tiles[pattern];
+        let mut stream = ctx.writer.stream(pattern_ref, content);
+        stream.filter(Filter::FlateDecode);
+        stream.pair(Name(b"Type"), Name(b"Pattern"));
+        stream.pair(Name(b"PatternType"), 1);
+        stream.pair(Name(b"PaintType"), 1);
+        stream.pair(Name(b"TilingType"), 1);
+        stream.insert(Name(b"BBox")).array().items([
+            0.0,
+            0.0,
+            size.x.to_f32(),
+            size.y.to_f32(),
+        ]);
+        stream.pair(Name(b"XStep"), cell.x.to_f32());
+        stream.pair(Name(b"YStep"), cell.y.to_f32());
+        stream.pair(Name(b"Resources"), ctx.global_resources_ref);
+        stream
+            .insert(Name(b"Matrix"))
+            .array()
+            .items(transform.to_pdf_matrix());
+    }
+}
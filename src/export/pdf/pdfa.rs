@@ -0,0 +1,119 @@
+use ecow::eco_format;
+use pdf_writer::{Filter, Finish, Name, Ref, TextStr};
+use ttf_parser::Permissions;
+
+use super::{deflate, PdfContext, PdfStandard, RefExt};
+use crate::diag::StrResult;
+use crate::doc::{Frame, FrameItem, GroupItem, Meta};
+use crate::geom::{Color, Paint};
+use crate::image::{ImageFormat, VectorFormat};
+
+/// The sRGB color profile that PDF/A output intents refer to.
+const SRGB_ICC: &[u8] = include_bytes!("icc/sRGB-v4.icc");
+
+/// Check that the exported pages only use features that the standard allows.
+pub fn check(ctx: &PdfContext, standard: PdfStandard) -> StrResult<()> {
+    let frames = ctx
+        .document
+        .pages
+        .iter()
+        .zip(&ctx.page_indices)
+        .filter(|(_, index)| index.is_some())
+        .map(|(frame, _)| frame);
+
+    for frame in frames {
+        check_frame(frame, standard)?;
+    }
+
+    Ok(())
+}
+
+/// Check a frame and all its children.
+fn check_frame(frame: &Frame, standard: PdfStandard) -> StrResult<()> {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(GroupItem { frame, .. }) => check_frame(frame, standard)?,
+            FrameItem::Text(text) => {
+                check_paint(&text.fill, standard)?;
+                if text.font.ttf().permissions() == Some(Permissions::Restricted) {
+                    return Err(eco_format!(
+                        "{} requires all fonts to be embedded, but the license of \
+                         the font {} forbids embedding it",
+                        standard.name(),
+                        text.font.info().family,
+                    ));
+                }
+            }
+            FrameItem::Shape(shape, _) => {
+                if let Some(fill) = &shape.fill {
+                    check_paint(fill, standard)?;
+                }
+                if let Some(stroke) = &shape.stroke {
+                    check_paint(&stroke.paint, standard)?;
+                }
+            }
+            FrameItem::Meta(Meta::Attachment(_), _) => {
+                return Err(eco_format!(
+                    "{} does not allow file attachments",
+                    standard.name(),
+                ));
+            }
+            FrameItem::Meta(Meta::FormField(_), _) => {
+                return Err(eco_format!(
+                    "{} does not allow form fields without appearance streams",
+                    standard.name(),
+                ));
+            }
+            // The content of an embedded page is copied as is and may use
+            // fonts, colors or transparency that the standard doesn't allow.
+            FrameItem::Image(image, ..)
+                if image.format() == ImageFormat::Vector(VectorFormat::Pdf) =>
+            {
+                return Err(eco_format!(
+                    "{} does not allow embedding pages of other pdf files",
+                    standard.name(),
+                ));
+            }
+            FrameItem::Image(..) | FrameItem::Meta(..) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that a paint's color space is compatible with the output intent.
+fn check_paint(paint: &Paint, standard: PdfStandard) -> StrResult<()> {
+    match paint {
+        Paint::Solid(Color::Cmyk(_)) => Err(eco_format!(
+            "{} does not allow CMYK colors in a document with an sRGB output intent",
+            standard.name(),
+        )),
+        Paint::Pattern(pattern) => check_frame(pattern.frame(), standard),
+        Paint::Solid(_) | Paint::Gradient(_) => Ok(()),
+    }
+}
+
+/// Write the sRGB color profile that the output intent refers to.
+pub fn write_icc_profile(ctx: &mut PdfContext) -> Ref {
+    let icc_ref = ctx.alloc.bump();
+    ctx.writer
+        .stream(icc_ref, &deflate(SRGB_ICC))
+        .filter(Filter::FlateDecode)
+        .pair(Name(b"N"), 3);
+    icc_ref
+}
+
+/// Write the catalog's output intents, which declare that all device colors
+/// are sRGB colors.
+pub fn write_output_intents(catalog: &mut pdf_writer::writers::Catalog, icc_ref: Ref) {
+    let mut intents = catalog.insert(Name(b"OutputIntents")).array();
+    let mut intent = intents.push().dict();
+    intent.pair(Name(b"Type"), Name(b"OutputIntent"));
+    intent.pair(Name(b"S"), Name(b"GTS_PDFA1"));
+    intent.pair(Name(b"OutputConditionIdentifier"), TextStr("sRGB"));
+    intent.pair(Name(b"RegistryName"), TextStr("http://www.color.org"));
+    intent.pair(Name(b"Info"), TextStr("sRGB IEC61966-2.1"));
+    intent.pair(Name(b"DestOutputProfile"), icc_ref);
+    intent.finish();
+    intents.finish();
+}
@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+
+use ecow::EcoString;
+use pdf_writer::{Finish, Name, Ref, TextStr};
+
+use super::{PdfContext, RefExt};
+use crate::doc::{Meta, Role};
+use crate::model::{Location, Structural};
+
+/// The logical structure of a document, assembled while its pages are written.
+#[derive(Default)]
+pub struct StructTree {
+    /// The structure elements.
+    nodes: Vec<StructNode>,
+    /// The structure elements by what produced them.
+    keys: HashMap<NodeKey, usize>,
+    /// For each exported page, the structure element that each marked-content
+    /// sequence on the page belongs to.
+    pages: Vec<Vec<usize>>,
+}
+
+/// A part of the document's structure that an item in a frame belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mark {
+    /// An element with a role.
+    Elem(Location, Role),
+    /// A table cell at the given column and row.
+    Cell(usize, usize),
+    /// Decoration that is not part of the structure.
+    Artifact,
+}
+
+impl Mark {
+    /// The mark that a piece of metadata stands for, if any.
+    pub fn new(meta: &Meta) -> Option<Self> {
+        match meta {
+            Meta::Elem(elem) | Meta::Structure(elem) => {
+                let role = elem.with::<dyn Structural>()?.role();
+                Some(Self::Elem(elem.location()?, role))
+            }
+            Meta::Cell(x, y) => Some(Self::Cell(*x, *y)),
+            Meta::Artifact => Some(Self::Artifact),
+            _ => None,
+        }
+    }
+}
+
+/// A structure element.
+struct StructNode {
+    kind: Kind,
+    parent: Option<usize>,
+    children: Vec<StructChild>,
+    alt: Option<EcoString>,
+}
+
+/// A child of a structure element.
+enum StructChild {
+    /// Another structure element.
+    Node(usize),
+    /// A marked-content sequence, given by the index of the exported page and
+    /// the marked-content identifier.
+    Content(usize, i32),
+}
+
+/// Identifies a structure element.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum NodeKey {
+    Elem(Location),
+    ListBody(usize),
+    Row(usize, usize),
+    Cell(usize, usize, usize),
+}
+
+/// The kind of a structure element.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Kind {
+    Paragraph,
+    Heading(usize),
+    List,
+    ListItem,
+    ListBody,
+    Table,
+    TableRow,
+    TableCell,
+    Figure,
+}
+
+impl Kind {
+    /// The standard structure type of the kind.
+    fn name(self) -> Name<'static> {
+        Name(match self {
+            Self::Paragraph => b"P",
+            Self::Heading(1) => b"H1",
+            Self::Heading(2) => b"H2",
+            Self::Heading(3) => b"H3",
+            Self::Heading(4) => b"H4",
+            Self::Heading(5) => b"H5",
+            Self::Heading(_) => b"H6",
+            Self::List => b"L",
+            Self::ListItem => b"LI",
+            Self::ListBody => b"LBody",
+            Self::Table => b"Table",
+            Self::TableRow => b"TR",
+            Self::TableCell => b"TD",
+            Self::Figure => b"Figure",
+        })
+    }
+}
+
+impl StructTree {
+    /// Find or create the structure element for content with the given
+    /// marks, from the outermost to the innermost.
+    ///
+    /// Returns `None` if the content is not part of the document's structure.
+    pub fn resolve(&mut self, marks: &[Mark]) -> Option<usize> {
+        let mut path: Vec<usize> = vec![];
+        for mark in marks {
+            let parent = path.last().copied();
+            let node = match mark {
+                Mark::Elem(location, role) => {
+                    let kind = match role {
+                        Role::Paragraph => Kind::Paragraph,
+                        Role::Heading(level) => Kind::Heading(level.get()),
+                        Role::List => Kind::List,
+                        Role::ListItem => Kind::ListItem,
+                        Role::Table => Kind::Table,
+                        Role::Figure(_) => Kind::Figure,
+                    };
+
+                    // The text of a heading is laid out as a paragraph, but
+                    // the heading already is one.
+                    if kind == Kind::Paragraph
+                        && parent.map_or(false, |parent| {
+                            matches!(self.nodes[parent].kind, Kind::Heading(_))
+                        })
+                    {
+                        continue;
+                    }
+
+                    let node = self.node(NodeKey::Elem(*location), parent, kind);
+                    if let Role::Figure(alt) = role {
+                        self.nodes[node].alt = alt.clone();
+                    }
+
+                    // Only the body of an item is marked, not its marker.
+                    if kind == Kind::ListItem {
+                        if !path.contains(&node) {
+                            path.push(node);
+                        }
+                        self.node(NodeKey::ListBody(node), Some(node), Kind::ListBody)
+                    } else {
+                        node
+                    }
+                }
+                Mark::Cell(x, y) => {
+                    // A cell belongs to the innermost table around it.
+                    let table = path
+                        .iter()
+                        .rev()
+                        .copied()
+                        .find(|&node| self.nodes[node].kind == Kind::Table);
+                    let Some(table) = table else { continue };
+
+                    let key = NodeKey::Row(table, *y);
+                    let row = self.node(key, Some(table), Kind::TableRow);
+                    let key = NodeKey::Cell(table, *x, *y);
+                    self.node(key, Some(row), Kind::TableCell)
+                }
+                Mark::Artifact => return None,
+            };
+
+            if !path.contains(&node) {
+                path.push(node);
+            }
+        }
+
+        path.last().copied()
+    }
+
+    /// Record a marked-content sequence of a structure element.
+    ///
+    /// Returns the structure type of the element.
+    pub fn push_content(&mut self, node: usize, page: usize, mcid: i32) -> Name<'static> {
+        let node = &mut self.nodes[node];
+        node.children.push(StructChild::Content(page, mcid));
+        node.kind.name()
+    }
+
+    /// Record the structure elements of the marked-content sequences on the
+    /// next exported page.
+    pub fn finish_page(&mut self, mcids: Vec<usize>) {
+        self.pages.push(mcids);
+    }
+
+    /// Find or create a structure element.
+    fn node(&mut self, key: NodeKey, parent: Option<usize>, kind: Kind) -> usize {
+        if let Some(&node) = self.keys.get(&key) {
+            return node;
+        }
+
+        let node = self.nodes.len();
+        self.nodes
+            .push(StructNode { kind, parent, children: vec![], alt: None });
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(StructChild::Node(node));
+        }
+
+        self.keys.insert(key, node);
+        node
+    }
+}
+
+/// Write the structure elements and the structure tree root.
+///
+/// Returns the reference to the structure tree root.
+pub fn write_struct_tree(ctx: &mut PdfContext, tree: StructTree) -> Ref {
+    let root_ref = ctx.alloc.bump();
+    let document_ref = ctx.alloc.bump();
+    let refs: Vec<Ref> = tree.nodes.iter().map(|_| ctx.alloc.bump()).collect();
+
+    // The document element holds all elements that have no other parent.
+    let mut document = ctx.writer.indirect(document_ref).dict();
+    document.pair(Name(b"Type"), Name(b"StructElem"));
+    document.pair(Name(b"S"), Name(b"Document"));
+    document.pair(Name(b"P"), root_ref);
+    document.insert(Name(b"K")).array().items(
+        tree.nodes
+            .iter()
+            .zip(&refs)
+            .filter(|(node, _)| node.parent.is_none())
+            .map(|(_, &node_ref)| node_ref),
+    );
+    document.finish();
+
+    for (node, &node_ref) in tree.nodes.iter().zip(&refs) {
+        let mut elem = ctx.writer.indirect(node_ref).dict();
+        elem.pair(Name(b"Type"), Name(b"StructElem"));
+        elem.pair(Name(b"S"), node.kind.name());
+        elem.pair(Name(b"P"), node.parent.map_or(document_ref, |parent| refs[parent]));
+        if let Some(alt) = &node.alt {
+            elem.pair(Name(b"Alt"), TextStr(alt));
+        }
+
+        let mut kids = elem.insert(Name(b"K")).array();
+        for child in &node.children {
+            match *child {
+                StructChild::Node(child) => {
+                    kids.item(refs[child]);
+                }
+                StructChild::Content(page, mcid) => {
+                    let mut mcr = kids.push().dict();
+                    mcr.pair(Name(b"Type"), Name(b"MCR"));
+                    mcr.pair(Name(b"Pg"), ctx.page_refs[page]);
+                    mcr.pair(Name(b"MCID"), mcid);
+                }
+            }
+        }
+
+        kids.finish();
+        elem.finish();
+    }
+
+    // The parent tree maps from the marked-content sequences on each page
+    // back to their structure elements.
+    let mut parent_refs = vec![];
+    for mcids in &tree.pages {
+        let parents_ref = ctx.alloc.bump();
+        ctx.writer
+            .indirect(parents_ref)
+            .array()
+            .items(mcids.iter().map(|&node| refs[node]));
+        parent_refs.push(parents_ref);
+    }
+
+    let mut root = ctx.writer.indirect(root_ref).dict();
+    root.pair(Name(b"Type"), Name(b"StructTreeRoot"));
+    root.pair(Name(b"K"), document_ref);
+
+    let mut parent_tree = root.insert(Name(b"ParentTree")).dict();
+    let mut nums = parent_tree.insert(Name(b"Nums")).array();
+    for (i, &parents_ref) in parent_refs.iter().enumerate() {
+        nums.item(i as i32);
+        nums.item(parents_ref);
+    }
+
+    nums.finish();
+    parent_tree.finish();
+    root.pair(Name(b"ParentTreeNextKey"), parent_refs.len() as i32);
+    root.finish();
+
+    root_ref
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+    use crate::model::StabilityProvider;
+
+    /// Create a mark for a new element with the given role.
+    fn elem(provider: &mut StabilityProvider, role: Role) -> Mark {
+        Mark::Elem(provider.locate(0), role)
+    }
+
+    /// Describe the structure elements below the document element.
+    fn describe(tree: &StructTree) -> String {
+        fn visit(tree: &StructTree, node: usize, out: &mut String) {
+            let node = &tree.nodes[node];
+            out.push_str(std::str::from_utf8(node.kind.name().0).unwrap());
+            let children: Vec<_> = node
+                .children
+                .iter()
+                .filter_map(|child| match *child {
+                    StructChild::Node(child) => Some(child),
+                    StructChild::Content(..) => None,
+                })
+                .collect();
+
+            if !children.is_empty() {
+                out.push('(');
+                for (i, &child) in children.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    visit(tree, child, out);
+                }
+                out.push(')');
+            }
+        }
+
+        let mut out = String::new();
+        let roots = tree.nodes.iter().enumerate().filter(|(_, n)| n.parent.is_none());
+        for (i, (root, _)) in roots.enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            visit(tree, root, &mut out);
+        }
+        out
+    }
+
+    #[test]
+    fn test_struct_tree_paragraphs() {
+        let mut provider = StabilityProvider::new();
+        let mut tree = StructTree::default();
+        let heading = elem(&mut provider, Role::Heading(NonZeroUsize::new(2).unwrap()));
+        let par = elem(&mut provider, Role::Paragraph);
+
+        assert_eq!(tree.resolve(&[]), None);
+        assert_eq!(tree.resolve(&[par.clone()]), Some(0));
+        assert_eq!(tree.resolve(&[par]), Some(0));
+
+        // The paragraph of a heading's text is not a separate element.
+        let inner = elem(&mut provider, Role::Paragraph);
+        assert_eq!(tree.resolve(&[heading, inner]), Some(1));
+        assert_eq!(describe(&tree), "P H2");
+    }
+
+    #[test]
+    fn test_struct_tree_lists() {
+        let mut provider = StabilityProvider::new();
+        let mut tree = StructTree::default();
+        let list = elem(&mut provider, Role::List);
+        let first = elem(&mut provider, Role::ListItem);
+        let second = elem(&mut provider, Role::ListItem);
+        let par = elem(&mut provider, Role::Paragraph);
+
+        let body = tree.resolve(&[list.clone(), first, par]).unwrap();
+        assert_eq!(tree.nodes[body].kind, Kind::Paragraph);
+        assert_eq!(tree.resolve(&[list.clone(), second]), Some(5));
+
+        // Content directly in a list, like the markers, belongs to the list.
+        assert_eq!(tree.resolve(&[list]), Some(0));
+        assert_eq!(describe(&tree), "L(LI(LBody(P)) LI(LBody))");
+    }
+
+    #[test]
+    fn test_struct_tree_tables() {
+        let mut provider = StabilityProvider::new();
+        let mut tree = StructTree::default();
+        let table = elem(&mut provider, Role::Table);
+        let first = elem(&mut provider, Role::Paragraph);
+        let second = elem(&mut provider, Role::Paragraph);
+
+        tree.resolve(&[table.clone(), Mark::Cell(0, 0), first]).unwrap();
+        tree.resolve(&[table.clone(), Mark::Cell(1, 0), second]).unwrap();
+        tree.resolve(&[table, Mark::Cell(0, 1)]).unwrap();
+        assert_eq!(describe(&tree), "Table(TR(TD(P) TD(P)) TR(TD))");
+
+        // Cells outside of tables are ignored.
+        let par = elem(&mut provider, Role::Paragraph);
+        assert_eq!(tree.resolve(&[Mark::Cell(0, 0), par]), Some(8));
+        assert_eq!(tree.nodes[8].kind, Kind::Paragraph);
+        assert_eq!(tree.nodes[8].parent, None);
+    }
+
+    #[test]
+    fn test_struct_tree_artifacts() {
+        let mut provider = StabilityProvider::new();
+        let mut tree = StructTree::default();
+        let table = elem(&mut provider, Role::Table);
+        let figure = elem(&mut provider, Role::Figure(Some("A chart".into())));
+
+        assert_eq!(tree.resolve(&[Mark::Artifact]), None);
+        assert_eq!(tree.resolve(&[figure.clone(), table, Mark::Artifact]), None);
+        assert_eq!(tree.resolve(&[figure]), Some(0));
+        assert_eq!(tree.nodes[0].alt.as_deref(), Some("A chart"));
+    }
+}
@@ -3,40 +3,72 @@
 use std::io::Read;
 use std::sync::Arc;
 
+use ecow::{eco_format, EcoString};
 use image::imageops::FilterType;
 use image::{GenericImageView, Rgba};
 use tiny_skia as sk;
 use ttf_parser::{GlyphId, OutlineBuilder};
 use usvg::{FitTo, NodeExt};
 
-use crate::doc::{Frame, FrameItem, GroupItem, Meta, TextItem};
+use crate::diag::StrResult;
+use crate::doc::{Frame, FrameItem, Glyph, GroupItem, Lang, Meta, TextItem};
+use crate::font::Font;
 use crate::geom::{
-    self, Abs, Color, Geometry, LineCap, LineJoin, Paint, PathItem, Shape, Size, Stroke,
-    Transform,
+    self, Abs, Color, Em, Geometry, Gradient, LineCap, LineJoin, Paint, PathItem,
+    Pattern, Point, Ratio, RelativeTo, RgbaColor, Shape, Size, Stroke, Transform,
 };
 use crate::image::{DecodedImage, Image};
+use crate::syntax::Span;
+
+/// The resolution at which glyphs without outlines are rasterized for vector
+/// exports, in pixels per em.
+const GLYPH_PPEM: f32 = 128.0;
+
+/// The maximum number of pixels in a rendered image, which keeps its four
+/// bytes per pixel addressable with 32 bits.
+const MAX_PIXELS: u64 = i32::MAX as u64 / 4;
 
 /// Export a frame into a raster image.
 ///
 /// This renders the frame at the given number of pixels per point and returns
-/// the resulting `tiny-skia` pixel buffer.
-pub fn render(frame: &Frame, pixel_per_pt: f32, fill: Color) -> sk::Pixmap {
+/// the resulting `tiny-skia` pixel buffer. Fails if the resolution is not
+/// positive or if the image would be too large to allocate.
+pub fn render(frame: &Frame, pixel_per_pt: f32, fill: Color) -> StrResult<sk::Pixmap> {
+    if !pixel_per_pt.is_finite() || pixel_per_pt <= 0.0 {
+        return Err("resolution must be positive".into());
+    }
+
     let size = frame.size();
     let pxw = (pixel_per_pt * size.x.to_f32()).round().max(1.0) as u32;
     let pxh = (pixel_per_pt * size.y.to_f32()).round().max(1.0) as u32;
 
-    let mut canvas = sk::Pixmap::new(pxw, pxh).unwrap();
+    let Some(mut canvas) = (u64::from(pxw) * u64::from(pxh) <= MAX_PIXELS)
+        .then(|| sk::Pixmap::new(pxw, pxh))
+        .flatten()
+    else {
+        return Err(eco_format!("image of {pxw}x{pxh} pixels is too large to render"));
+    };
     canvas.fill(fill.into());
 
     let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt);
-    render_frame(&mut canvas, ts, None, frame);
+    let page = Page { ts, size };
+    render_frame(&mut canvas, &page, ts, None, frame);
 
-    canvas
+    Ok(canvas)
+}
+
+/// The page that is rendered, which gradients and patterns can be relative to.
+struct Page {
+    /// The transform from the page to the canvas.
+    ts: sk::Transform,
+    /// The size of the page.
+    size: Size,
 }
 
 /// Render a frame into the canvas.
 fn render_frame(
     canvas: &mut sk::Pixmap,
+    page: &Page,
     ts: sk::Transform,
     mask: Option<&sk::ClipMask>,
     frame: &Frame,
@@ -48,13 +80,13 @@ fn render_frame(
 
         match item {
             FrameItem::Group(group) => {
-                render_group(canvas, ts, mask, group);
+                render_group(canvas, page, ts, mask, group);
             }
             FrameItem::Text(text) => {
-                render_text(canvas, ts, mask, text);
+                render_text(canvas, page, ts, mask, text);
             }
             FrameItem::Shape(shape, _) => {
-                render_shape(canvas, ts, mask, shape);
+                render_shape(canvas, page, ts, mask, shape);
             }
             FrameItem::Image(image, size, _) => {
                 render_image(canvas, ts, mask, image, *size);
@@ -62,7 +94,13 @@ fn render_frame(
             FrameItem::Meta(meta, _) => match meta {
                 Meta::Link(_) => {}
                 Meta::Elem(_) => {}
+                Meta::Structure(_) => {}
+                Meta::Cell(..) => {}
+                Meta::Artifact => {}
                 Meta::PageNumbering(_) => {}
+                Meta::PageLabel(_) => {}
+                Meta::Attachment(_) => {}
+                Meta::FormField(_) => {}
                 Meta::Hide => {}
             },
         }
@@ -72,6 +110,7 @@ fn render_frame(
 /// Render a group frame with optional transform and clipping into the canvas.
 fn render_group(
     canvas: &mut sk::Pixmap,
+    page: &Page,
     ts: sk::Transform,
     mask: Option<&sk::ClipMask>,
     group: &GroupItem,
@@ -108,12 +147,75 @@ fn render_group(
         }
     }
 
-    render_frame(canvas, ts, mask, &group.frame);
+    render_frame(canvas, page, ts, mask, &group.frame);
+}
+
+/// Rasterize a glyph from the font's SVG or bitmap tables into a PNG image.
+///
+/// Vector exports use this for glyphs they cannot draw as outlines. The image
+/// fills the box given by [`image_glyph_size`]. Returns the encoded image and
+/// its size in font units or `None` if the glyph is a plain outline glyph.
+pub(super) fn render_image_glyph(font: &Font, id: u16) -> Option<(Vec<u8>, Size)> {
+    let ttf = font.ttf();
+    let glyph_id = GlyphId(id);
+    if ttf.glyph_svg_image(glyph_id).is_none()
+        && ttf.glyph_raster_image(glyph_id, u16::MAX).is_none()
+    {
+        return None;
+    }
+
+    // We lay the glyph out at a font size of one point per font unit, so that
+    // the frame's coordinates are directly in font units.
+    let upem = font.units_per_em();
+    let ascender = font.metrics().ascender.get() * upem;
+    let advance = font.advance(id).unwrap_or(Em::one());
+    let size = image_glyph_size(font, id);
+
+    let mut frame = Frame::new(size);
+    frame.push(
+        Point::with_y(Abs::pt(ascender)),
+        FrameItem::Text(TextItem {
+            font: font.clone(),
+            size: Abs::pt(upem),
+            fill: Color::BLACK.into(),
+            lang: Lang::ENGLISH,
+            text: EcoString::new(),
+            glyphs: vec![Glyph {
+                id,
+                x_advance: advance,
+                x_offset: Em::zero(),
+                c: ' ',
+                range: 0..0,
+                span: Span::detached(),
+                offset: 0,
+            }],
+        }),
+    );
+
+    let transparent = Color::Rgba(RgbaColor::new(0, 0, 0, 0));
+    let pixmap = render(&frame, GLYPH_PPEM / upem as f32, transparent).ok()?;
+    let png = pixmap.encode_png().ok()?;
+    Some((png, size))
+}
+
+/// The size in font units of the box that a glyph from the font's SVG or
+/// bitmap tables is drawn into by vector exports.
+///
+/// The box spans the glyph's advance, but at least one em, horizontally and
+/// the space from the font's ascender to its descender vertically.
+pub(super) fn image_glyph_size(font: &Font, id: u16) -> Size {
+    let upem = font.units_per_em();
+    let metrics = font.metrics();
+    let ascender = metrics.ascender.get() * upem;
+    let descender = metrics.descender.get() * upem;
+    let advance = font.advance(id).unwrap_or(Em::one());
+    Size::new(Abs::pt((advance.get() * upem).max(upem)), Abs::pt(ascender - descender))
 }
 
 /// Render a text run into the canvas.
 fn render_text(
     canvas: &mut sk::Pixmap,
+    page: &Page,
     ts: sk::Transform,
     mask: Option<&sk::ClipMask>,
     text: &TextItem,
@@ -124,9 +226,13 @@ fn render_text(
         let offset = x + glyph.x_offset.at(text.size).to_f32();
         let ts = ts.pre_translate(offset, 0.0);
 
+        // The bounding box of the whole run, relative to the glyph.
+        let origin = Point::new(Abs::pt(-offset as f64), -text.size);
+        let bbox = (origin, Size::new(text.width(), text.size));
+
         render_svg_glyph(canvas, ts, mask, text, id)
             .or_else(|| render_bitmap_glyph(canvas, ts, mask, text, id))
-            .or_else(|| render_outline_glyph(canvas, ts, mask, text, id));
+            .or_else(|| render_outline_glyph(canvas, page, ts, mask, text, id, bbox));
 
         x += glyph.x_advance.at(text.size).to_f32();
     }
@@ -140,43 +246,18 @@ fn render_svg_glyph(
     text: &TextItem,
     id: GlyphId,
 ) -> Option<()> {
-    let mut data = text.font.ttf().glyph_svg_image(id)?;
-
-    // Decompress SVGZ.
-    let mut decoded = vec![];
-    if data.starts_with(&[0x1f, 0x8b]) {
-        let mut decoder = flate2::read::GzDecoder::new(data);
-        decoder.read_to_end(&mut decoded).ok()?;
-        data = &decoded;
-    }
-
     // Parse XML.
-    let xml = std::str::from_utf8(data).ok()?;
-    let document = roxmltree::Document::parse(xml).ok()?;
+    let xml = svg_glyph_source(&text.font, id)?;
+    let document = roxmltree::Document::parse(&xml).ok()?;
     let root = document.root_element();
 
     // Parse SVG.
     let opts = usvg::Options::default();
     let tree = usvg::Tree::from_xmltree(&document, &opts.to_ref()).ok()?;
-    let view_box = tree.svg_node().view_box.rect;
-
-    // If there's no viewbox defined, use the em square for our scale
-    // transformation ...
-    let upem = text.font.units_per_em() as f32;
-    let (mut width, mut height) = (upem, upem);
-
-    // ... but if there's a viewbox or width, use that.
-    if root.has_attribute("viewBox") || root.has_attribute("width") {
-        width = view_box.width() as f32;
-    }
-
-    // Same as for width.
-    if root.has_attribute("viewBox") || root.has_attribute("height") {
-        height = view_box.height() as f32;
-    }
+    let (width, height) = svg_glyph_em(&text.font, root, &tree);
 
     let size = text.size.to_f32();
-    let ts = ts.pre_scale(size / width, size / height);
+    let ts = ts.pre_scale(size / width as f32, size / height as f32);
 
     // Compute the space we need to draw our glyph.
     // See https://github.com/RazrFalcon/resvg/issues/602 for why
@@ -227,6 +308,49 @@ fn render_svg_glyph(
     )
 }
 
+/// The document of a glyph from the font's SVG table, decompressed if
+/// necessary.
+pub(super) fn svg_glyph_source(font: &Font, id: GlyphId) -> Option<String> {
+    let data = font.ttf().glyph_svg_image(id)?;
+
+    // Decompress SVGZ.
+    if data.starts_with(&[0x1f, 0x8b]) {
+        let mut decoded = String::new();
+        let mut decoder = flate2::read::GzDecoder::new(data);
+        decoder.read_to_string(&mut decoded).ok()?;
+        return Some(decoded);
+    }
+
+    std::str::from_utf8(data).ok().map(Into::into)
+}
+
+/// The width and height that one em spans in the coordinate system of a
+/// glyph's SVG document.
+pub(super) fn svg_glyph_em(
+    font: &Font,
+    root: roxmltree::Node,
+    tree: &usvg::Tree,
+) -> (f64, f64) {
+    let view_box = tree.svg_node().view_box.rect;
+
+    // If there's no viewbox defined, use the em square for our scale
+    // transformation ...
+    let upem = font.units_per_em();
+    let (mut width, mut height) = (upem, upem);
+
+    // ... but if there's a viewbox or width, use that.
+    if root.has_attribute("viewBox") || root.has_attribute("width") {
+        width = view_box.width();
+    }
+
+    // Same as for width.
+    if root.has_attribute("viewBox") || root.has_attribute("height") {
+        height = view_box.height();
+    }
+
+    (width, height)
+}
+
 /// Render a bitmap glyph into the canvas.
 fn render_bitmap_glyph(
     canvas: &mut sk::Pixmap,
@@ -254,33 +378,26 @@ fn render_bitmap_glyph(
 /// Render an outline glyph into the canvas. This is the "normal" case.
 fn render_outline_glyph(
     canvas: &mut sk::Pixmap,
+    page: &Page,
     ts: sk::Transform,
     mask: Option<&sk::ClipMask>,
     text: &TextItem,
     id: GlyphId,
+    bbox: (Point, Size),
 ) -> Option<()> {
     let ppem = text.size.to_f32() * ts.sy;
 
     // Render a glyph directly as a path. This only happens when the fast glyph
-    // rasterization can't be used due to very large text size or weird
-    // scale/skewing transforms.
-    if ppem > 100.0 || ts.kx != 0.0 || ts.ky != 0.0 || ts.sx != ts.sy {
-        let path = {
-            let mut builder = WrappedPathBuilder(sk::PathBuilder::new());
-            text.font.ttf().outline_glyph(id, &mut builder)?;
-            builder.0.finish()?
-        };
-
-        let paint = (&text.fill).into();
-        let rule = sk::FillRule::default();
-
-        // Flip vertically because font design coordinate
-        // system is Y-up.
-        let scale = text.size.to_f32() / text.font.units_per_em() as f32;
-        let ts = ts.pre_scale(scale, -scale);
-        canvas.fill_path(&path, &paint, rule, ts, mask)?;
-        return Some(());
-    }
+    // rasterization can't be used due to very large text size, weird
+    // scale/skewing transforms or a gradient or pattern fill.
+    let color = match text.fill {
+        Paint::Solid(color)
+            if ppem <= 100.0 && ts.kx == 0.0 && ts.ky == 0.0 && ts.sx == ts.sy =>
+        {
+            color
+        }
+        _ => return render_path_glyph(canvas, page, ts, mask, text, id, bbox),
+    };
 
     // Rasterize the glyph with `pixglyph`.
     // Try to retrieve a prepared glyph or prepare it from scratch if it
@@ -294,7 +411,6 @@ fn render_outline_glyph(
         let mw = bitmap.width;
         let mh = bitmap.height;
 
-        let Paint::Solid(color) = text.fill;
         let c = color.to_rgba();
 
         // Pad the pixmap with 1 pixel in each dimension so that we do
@@ -332,7 +448,6 @@ fn render_outline_glyph(
         let bottom = top + mh;
 
         // Premultiply the text color.
-        let Paint::Solid(color) = text.fill;
         let c = color.to_rgba();
         let color = sk::ColorU8::from_rgba(c.r, c.g, c.b, 255).premultiply().get();
 
@@ -361,9 +476,37 @@ fn render_outline_glyph(
     }
 }
 
+/// Render a glyph as a path into the canvas.
+fn render_path_glyph(
+    canvas: &mut sk::Pixmap,
+    page: &Page,
+    ts: sk::Transform,
+    mask: Option<&sk::ClipMask>,
+    text: &TextItem,
+    id: GlyphId,
+    bbox: (Point, Size),
+) -> Option<()> {
+    let path = {
+        let mut builder = WrappedPathBuilder(sk::PathBuilder::new());
+        text.font.ttf().outline_glyph(id, &mut builder)?;
+        builder.0.finish()?
+    };
+
+    // Flip vertically because font design coordinate
+    // system is Y-up.
+    let scale = text.size.to_f32() / text.font.units_per_em() as f32;
+    let path = path.transform(sk::Transform::from_scale(scale, -scale))?;
+
+    let mut storage = None;
+    let paint = to_sk_paint(&text.fill, page, ts, true, bbox, &mut storage);
+    let rule = sk::FillRule::default();
+    canvas.fill_path(&path, &paint, rule, ts, mask)
+}
+
 /// Render a geometrical shape into the canvas.
 fn render_shape(
     canvas: &mut sk::Pixmap,
+    page: &Page,
     ts: sk::Transform,
     mask: Option<&sk::ClipMask>,
     shape: &Shape,
@@ -383,8 +526,10 @@ fn render_shape(
         Geometry::Path(ref path) => convert_path(path)?,
     };
 
+    let bbox = shape.geometry.bbox();
     if let Some(fill) = &shape.fill {
-        let mut paint: sk::Paint = fill.into();
+        let mut storage = None;
+        let mut paint = to_sk_paint(fill, page, ts, false, bbox, &mut storage);
         if matches!(shape.geometry, Geometry::Rect(_)) {
             paint.anti_alias = false;
         }
@@ -415,7 +560,8 @@ fn render_shape(
 
             sk::StrokeDash::new(dash_array, pattern.phase.to_f32())
         });
-        let paint = paint.into();
+        let mut storage = None;
+        let paint = to_sk_paint(paint, page, ts, false, bbox, &mut storage);
         let stroke = sk::Stroke {
             width: thickness.to_f32(),
             line_cap: line_cap.into(),
@@ -518,6 +664,23 @@ fn scaled_texture(image: &Image, w: u32, h: u32) -> Option<Arc<sk::Pixmap>> {
                 pixmap.as_mut(),
             )?;
         }
+        // PDF pages can't be rasterized, so they show up as a crossed-out
+        // placeholder of the same size.
+        DecodedImage::Pdf(_) => {
+            pixmap.fill(Color::SILVER.into());
+            let mut builder = sk::PathBuilder::new();
+            builder.move_to(0.0, 0.0);
+            builder.line_to(w as f32, h as f32);
+            builder.move_to(w as f32, 0.0);
+            builder.line_to(0.0, h as f32);
+            let path = builder.finish()?;
+            let mut paint = sk::Paint::default();
+            paint.set_color(Color::GRAY.into());
+            paint.anti_alias = true;
+            let width = w.min(h) as f32 / 50.0;
+            let stroke = sk::Stroke { width, ..Default::default() };
+            pixmap.stroke_path(&path, &paint, &stroke, sk::Transform::identity(), None);
+        }
     }
     Some(Arc::new(pixmap))
 }
@@ -536,16 +699,158 @@ impl From<Transform> for sk::Transform {
     }
 }
 
-impl From<&Paint> for sk::Paint<'static> {
-    fn from(paint: &Paint) -> Self {
-        let mut sk_paint = sk::Paint::default();
-        let Paint::Solid(color) = *paint;
-        sk_paint.set_color(color.into());
-        sk_paint.anti_alias = true;
-        sk_paint
+/// Convert a paint into a tiny-skia paint for content drawn with the given
+/// transform.
+///
+/// A gradient stretches over the given bounding box in the coordinate system
+/// of the content and a pattern starts in its top-left corner, unless they are
+/// relative to the page. The texture of a pattern is kept alive in `storage`.
+fn to_sk_paint<'a>(
+    paint: &Paint,
+    page: &Page,
+    ts: sk::Transform,
+    on_text: bool,
+    bbox: (Point, Size),
+    storage: &'a mut Option<Arc<sk::Pixmap>>,
+) -> sk::Paint<'a> {
+    let mut sk_paint = sk::Paint::default();
+    match paint {
+        Paint::Solid(color) => sk_paint.set_color((*color).into()),
+        Paint::Gradient(gradient) => {
+            match gradient_shader(gradient, page, ts, on_text, bbox) {
+                Some(shader) => sk_paint.shader = shader,
+                // The gradient can't be placed onto a box without area.
+                None => sk_paint.set_color(gradient.sample(Ratio::new(0.5)).into()),
+            }
+        }
+        Paint::Pattern(pattern) => {
+            match pattern_shader(pattern, page, ts, on_text, bbox.0, storage) {
+                Some(shader) => sk_paint.shader = shader,
+                // The transform can't be inverted, so nothing is visible.
+                None => sk_paint.set_color(sk::Color::TRANSPARENT),
+            }
+        }
+    }
+    sk_paint.anti_alias = true;
+    sk_paint
+}
+
+/// Create a shader that draws a gradient.
+fn gradient_shader(
+    gradient: &Gradient,
+    page: &Page,
+    ts: sk::Transform,
+    on_text: bool,
+    (origin, size): (Point, Size),
+) -> Option<sk::Shader<'static>> {
+    // The transform from the box to the coordinate system of the content.
+    let (box_ts, size) = match gradient.relative_to(on_text) {
+        RelativeTo::Shape => {
+            (sk::Transform::from_translate(origin.x.to_f32(), origin.y.to_f32()), size)
+        }
+        RelativeTo::Page => (invert(ts)?.pre_concat(page.ts), page.size),
+    };
+
+    let stops = gradient
+        .stops()
+        .iter()
+        .map(|&(color, offset)| sk::GradientStop::new(offset.get() as f32, color.into()))
+        .collect();
+
+    match gradient {
+        Gradient::Linear(linear) => {
+            let (start, end) = linear.axis(size);
+            sk::LinearGradient::new(
+                sk::Point::from_xy(start.x.to_f32(), start.y.to_f32()),
+                sk::Point::from_xy(end.x.to_f32(), end.y.to_f32()),
+                stops,
+                sk::SpreadMode::Pad,
+                box_ts,
+            )
+        }
+        Gradient::Radial(radial) => {
+            // The center and radius are relative to the box, which stretches
+            // the circle into an ellipse in a box that isn't square.
+            let center = sk::Point::from_xy(
+                radial.center.x.get() as f32,
+                radial.center.y.get() as f32,
+            );
+            sk::RadialGradient::new(
+                center,
+                center,
+                radial.radius.get() as f32,
+                stops,
+                sk::SpreadMode::Pad,
+                box_ts.pre_scale(size.x.to_f32(), size.y.to_f32()),
+            )
+        }
     }
 }
 
+/// Invert a transform unless it is degenerate.
+fn invert(ts: sk::Transform) -> Option<sk::Transform> {
+    let det = ts.sx * ts.sy - ts.kx * ts.ky;
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+
+    let (sx, ky, kx, sy) = (ts.sy / det, -ts.ky / det, -ts.kx / det, ts.sx / det);
+    let tx = -(sx * ts.tx + kx * ts.ty);
+    let ty = -(ky * ts.tx + sy * ts.ty);
+    Some(sk::Transform::from_row(sx, ky, kx, sy, tx, ty))
+}
+
+/// Create a shader that repeats the tile of a pattern.
+fn pattern_shader<'a>(
+    pattern: &Pattern,
+    page: &Page,
+    ts: sk::Transform,
+    on_text: bool,
+    origin: Point,
+    storage: &'a mut Option<Arc<sk::Pixmap>>,
+) -> Option<sk::Shader<'a>> {
+    // The transform from the tiling to the coordinate system of the content.
+    let box_ts = match pattern.relative_to(on_text) {
+        RelativeTo::Shape => {
+            sk::Transform::from_translate(origin.x.to_f32(), origin.y.to_f32())
+        }
+        RelativeTo::Page => invert(ts)?.pre_concat(page.ts),
+    };
+
+    // Render the tile at the resolution at which it ends up on the canvas,
+    // but never into a texture so large that it can't be allocated.
+    let full = ts.pre_concat(box_ts);
+    let scale = full.sx.hypot(full.ky).max(full.kx.hypot(full.sy));
+    let cell = pattern.cell();
+    let w = (scale * cell.x.to_f32()).ceil().clamp(1.0, MAX_TEXTURE_SIZE) as u32;
+    let h = (scale * cell.y.to_f32()).ceil().clamp(1.0, MAX_TEXTURE_SIZE) as u32;
+    let pixmap = storage.insert(pattern_texture(pattern, w, h)?);
+
+    Some(sk::Pattern::new(
+        (**pixmap).as_ref(),
+        sk::SpreadMode::Repeat,
+        sk::FilterQuality::Nearest,
+        1.0,
+        box_ts.pre_scale(cell.x.to_f32() / w as f32, cell.y.to_f32() / h as f32),
+    ))
+}
+
+/// The maximum width and height of a pattern's texture in pixels.
+const MAX_TEXTURE_SIZE: f32 = 4096.0;
+
+/// Render the tile of a pattern into a texture that covers one cell of the
+/// tiling, including the spacing.
+#[comemo::memoize]
+fn pattern_texture(pattern: &Pattern, w: u32, h: u32) -> Option<Arc<sk::Pixmap>> {
+    let mut pixmap = sk::Pixmap::new(w, h)?;
+    let cell = pattern.cell();
+    let ts =
+        sk::Transform::from_scale(w as f32 / cell.x.to_f32(), h as f32 / cell.y.to_f32());
+    let page = Page { ts, size: pattern.size() };
+    render_frame(&mut pixmap, &page, ts, None, pattern.frame());
+    Some(Arc::new(pixmap))
+}
+
 impl From<Color> for sk::Color {
     fn from(color: Color) -> Self {
         let c = color.to_rgba();
@@ -0,0 +1,963 @@
+//! Exporting into SVG images.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter, Write};
+
+use ecow::EcoString;
+use ttf_parser::{GlyphId, OutlineBuilder};
+
+use super::render::{
+    image_glyph_size, render_image_glyph, svg_glyph_em, svg_glyph_source,
+};
+use crate::doc::{Destination, Frame, FrameItem, GroupItem, Meta, TextItem};
+use crate::font::Font;
+use crate::geom::{
+    self, Abs, Color, Em, Geometry, Gradient, LineCap, LineJoin, Paint, PathItem,
+    Pattern, Point, Ratio, RelativeTo, Shape, Size, Stroke, Transform,
+};
+use crate::image::{Image, ImageFormat, RasterFormat, VectorFormat};
+use crate::util::hash128;
+
+/// Export a frame into an SVG image.
+///
+/// Each glyph is defined once as a reusable symbol and the text is laid
+/// invisibly over the glyphs so that it can be selected. Links to URLs are
+/// preserved as clickable regions on top of the content.
+pub fn svg(frame: &Frame) -> String {
+    svg_with_links(frame, &|_| None)
+}
+
+/// Export a frame into an SVG image, resolving links within the document with
+/// the given function.
+///
+/// The function maps the destination of each link that doesn't point to a URL
+/// to the URL that the link should point to instead. Links for which it
+/// returns `None` are dropped.
+pub fn svg_with_links(
+    frame: &Frame,
+    resolve: &dyn Fn(&Destination) -> Option<EcoString>,
+) -> String {
+    let mut ctx = SvgContext {
+        size: frame.size(),
+        prefix: format!("t{:x}-", hash128(frame) as u32),
+        resolve,
+        body: String::new(),
+        defs: String::new(),
+        glyphs: HashMap::new(),
+        clips: 0,
+        gradients: 0,
+        tiles: HashMap::new(),
+        patterns: 0,
+        links: vec![],
+    };
+    ctx.write_frame(Transform::identity(), frame);
+    ctx.finish()
+}
+
+/// Context for exporting a frame into an SVG image.
+struct SvgContext<'a> {
+    /// The size of the image.
+    size: Size,
+    /// Prefixes the ids of all definitions so that they don't collide when
+    /// multiple images are inlined into the same page.
+    prefix: String,
+    /// Resolves links within the document to URLs.
+    resolve: &'a dyn Fn(&Destination) -> Option<EcoString>,
+    /// The markup for the visible content.
+    body: String,
+    /// Definitions referenced from the body.
+    defs: String,
+    /// Maps from glyphs to the indices of their symbol definitions.
+    glyphs: HashMap<(Font, u16), usize>,
+    /// The number of clip paths defined so far.
+    clips: usize,
+    /// The number of gradients defined so far.
+    gradients: usize,
+    /// Maps from patterns to the indices of the definitions of their tiles.
+    tiles: HashMap<Pattern, usize>,
+    /// The number of patterns defined so far.
+    patterns: usize,
+    /// Links with their URL, transform and size.
+    links: Vec<(EcoString, Transform, Size)>,
+}
+
+impl SvgContext<'_> {
+    /// Assemble the final SVG image.
+    fn finish(self) -> String {
+        let w = Num(self.size.x.to_pt());
+        let h = Num(self.size.y.to_pt());
+
+        let mut svg = String::new();
+        write!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{w}pt\" height=\"{h}pt\" viewBox=\"0 0 {w} {h}\">"
+        )
+        .unwrap();
+
+        if !self.defs.is_empty() {
+            svg.push_str("<defs>");
+            svg.push_str(&self.defs);
+            svg.push_str("</defs>");
+        }
+
+        svg.push_str(&self.body);
+
+        // Links are written last so that they are on top of the content they
+        // cover and receive the clicks.
+        for (url, ts, size) in &self.links {
+            write!(
+                svg,
+                "<a xlink:href=\"{}\"><rect transform=\"{}\" width=\"{}\" \
+                 height=\"{}\" fill=\"none\" pointer-events=\"all\"/></a>",
+                Escaped(url),
+                Matrix(*ts),
+                Num(size.x.to_pt()),
+                Num(size.y.to_pt()),
+            )
+            .unwrap();
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Write a frame into the body. The transform is the frame's total
+    /// transform relative to the image.
+    fn write_frame(&mut self, ts: Transform, frame: &Frame) {
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => self.write_group(ts, *pos, group),
+                FrameItem::Text(text) => self.write_text(ts, *pos, text),
+                FrameItem::Shape(shape, _) => self.write_shape(ts, *pos, shape),
+                FrameItem::Image(image, size, _) => self.write_image(*pos, image, *size),
+                FrameItem::Meta(meta, size) => match meta {
+                    Meta::Link(dest) => {
+                        let url = match dest {
+                            Destination::Url(url) => Some(url.clone()),
+                            dest => (self.resolve)(dest),
+                        };
+                        if let Some(url) = url {
+                            let ts = ts.pre_concat(Transform::translate(pos.x, pos.y));
+                            self.links.push((url, ts, *size));
+                        }
+                    }
+                    Meta::Elem(_) => {}
+                    Meta::Structure(_) => {}
+                    Meta::Cell(..) => {}
+                    Meta::Artifact => {}
+                    Meta::PageNumbering(_) => {}
+                    Meta::PageLabel(_) => {}
+                    Meta::Attachment(_) => {}
+                    Meta::FormField(_) => {}
+                    Meta::Hide => {}
+                },
+            }
+        }
+    }
+
+    /// Write a group with optional transform and clipping.
+    fn write_group(&mut self, ts: Transform, pos: Point, group: &GroupItem) {
+        let transform = Transform::translate(pos.x, pos.y).pre_concat(group.transform);
+        write!(self.body, "<g transform=\"{}\">", Matrix(transform)).unwrap();
+
+        if group.clips {
+            let id = self.clips;
+            self.clips += 1;
+
+            let size = group.frame.size();
+            let prefix = &self.prefix;
+            write!(
+                self.defs,
+                "<clipPath id=\"{prefix}c{id}\"><rect width=\"{}\" height=\"{}\"/>\
+                 </clipPath>",
+                Num(size.x.to_pt()),
+                Num(size.y.to_pt()),
+            )
+            .unwrap();
+            write!(self.body, "<g clip-path=\"url(#{prefix}c{id})\">").unwrap();
+        }
+
+        self.write_frame(ts.pre_concat(transform), &group.frame);
+
+        if group.clips {
+            self.body.push_str("</g>");
+        }
+
+        self.body.push_str("</g>");
+    }
+
+    /// Write a text run.
+    fn write_text(&mut self, ts: Transform, pos: Point, text: &TextItem) {
+        // Glyph symbols are defined in font units, so we scale them to the
+        // font size.
+        let upem = text.font.units_per_em();
+        let scale = text.size.to_pt() / upem;
+
+        write!(
+            self.body,
+            "<g transform=\"translate({} {}) scale({})\"",
+            Num(pos.x.to_pt()),
+            Num(pos.y.to_pt()),
+            scale,
+        )
+        .unwrap();
+        if let Paint::Solid(color) = text.fill {
+            write_color(&mut self.body, "fill", color);
+        }
+        self.body.push('>');
+
+        // The bounding box of the whole run.
+        let run_ts = ts.pre_concat(Transform::translate(pos.x, pos.y));
+        let bbox_ts = run_ts.pre_concat(Transform::translate(Abs::zero(), -text.size));
+        let bbox = (bbox_ts, Size::new(text.width(), text.size));
+
+        let mut x = Em::zero();
+        for glyph in &text.glyphs {
+            let index = self.glyph(&text.font, glyph.id);
+            let offset = (x + glyph.x_offset).get() * upem;
+            write!(
+                self.body,
+                "<use xlink:href=\"#{}g{index}\" x=\"{}\"",
+                self.prefix,
+                Num(offset),
+            )
+            .unwrap();
+
+            // A gradient or pattern is painted in the coordinate system of
+            // each glyph, so each glyph needs its own.
+            if !matches!(text.fill, Paint::Solid(_)) {
+                let glyph_ts = run_ts
+                    .pre_concat(Transform::scale(Ratio::new(scale), Ratio::new(scale)))
+                    .pre_concat(Transform::translate(Abs::pt(offset), Abs::zero()));
+                self.write_paint("fill", &text.fill, true, glyph_ts, bbox);
+            }
+
+            self.body.push_str("/>");
+            x += glyph.x_advance;
+        }
+
+        self.write_selectable_text(text);
+        self.body.push_str("</g>");
+    }
+
+    /// Write a run's text invisibly on top of its glyphs, in font units.
+    ///
+    /// Each cluster's characters are placed at the cluster's first glyph, so
+    /// that selecting the text highlights roughly the right area.
+    fn write_selectable_text(&mut self, text: &TextItem) {
+        let upem = text.font.units_per_em();
+        let mut clusters: Vec<(usize, f64, &str)> = vec![];
+        let mut x = Em::zero();
+        for glyph in &text.glyphs {
+            // The glyphs of a cluster are adjacent.
+            let start = glyph.range.start;
+            if clusters.last().map_or(true, |&(prev, ..)| prev != start) {
+                clusters.push((start, x.get() * upem, text.cluster(glyph)));
+            }
+            x += glyph.x_advance;
+        }
+
+        // Right-to-left clusters are stored in visual order, but the text must
+        // be in logical order to be copied correctly.
+        clusters.sort_by_key(|&(start, ..)| start);
+        clusters.retain(|&(.., cluster)| !cluster.is_empty());
+        if clusters.is_empty() {
+            return;
+        }
+
+        self.body.push_str("<text x=\"");
+        for (i, x) in clusters
+            .iter()
+            .flat_map(|&(_, x, cluster)| cluster.chars().map(move |_| x))
+            .enumerate()
+        {
+            if i > 0 {
+                self.body.push(' ');
+            }
+            write!(self.body, "{}", Num(x)).unwrap();
+        }
+
+        write!(
+            self.body,
+            "\" font-family=\"{}\" font-size=\"{}\" fill=\"#000000\" \
+             fill-opacity=\"0\" xml:space=\"preserve\">",
+            Escaped(&text.font.info().family),
+            Num(upem),
+        )
+        .unwrap();
+
+        for &(.., cluster) in &clusters {
+            write!(self.body, "{}", Escaped(cluster)).unwrap();
+        }
+
+        self.body.push_str("</text>");
+    }
+
+    /// Retrieve the index of a glyph's symbol, defining it if necessary.
+    fn glyph(&mut self, font: &Font, id: u16) -> usize {
+        let key = (font.clone(), id);
+        if let Some(&index) = self.glyphs.get(&key) {
+            return index;
+        }
+
+        let index = self.glyphs.len();
+        self.glyphs.insert(key, index);
+
+        write!(self.defs, "<symbol id=\"{}g{index}\" overflow=\"visible\">", self.prefix)
+            .unwrap();
+        if !write_image_glyph(&mut self.defs, font, id) {
+            write_outline_glyph(&mut self.defs, font, id);
+        }
+        self.defs.push_str("</symbol>");
+
+        index
+    }
+
+    /// Write a geometrical shape.
+    fn write_shape(&mut self, ts: Transform, pos: Point, shape: &Shape) {
+        write!(
+            self.body,
+            "<path transform=\"translate({} {})\" d=\"",
+            Num(pos.x.to_pt()),
+            Num(pos.y.to_pt()),
+        )
+        .unwrap();
+
+        match &shape.geometry {
+            Geometry::Line(target) => {
+                write!(
+                    self.body,
+                    "M0 0L{} {}",
+                    Num(target.x.to_pt()),
+                    Num(target.y.to_pt())
+                )
+                .unwrap();
+            }
+            Geometry::Rect(size) => {
+                write!(
+                    self.body,
+                    "M0 0H{}V{}H0Z",
+                    Num(size.x.to_pt()),
+                    Num(size.y.to_pt())
+                )
+                .unwrap();
+            }
+            Geometry::Path(path) => write_path(&mut self.body, path),
+        }
+
+        self.body.push('"');
+
+        let shape_ts = ts.pre_concat(Transform::translate(pos.x, pos.y));
+        let (origin, size) = shape.geometry.bbox();
+        let bbox = (shape_ts.pre_concat(Transform::translate(origin.x, origin.y)), size);
+
+        match &shape.fill {
+            Some(fill) => self.write_paint("fill", fill, false, shape_ts, bbox),
+            None => self.body.push_str(" fill=\"none\""),
+        }
+
+        if let Some(stroke) = &shape.stroke {
+            self.write_paint("stroke", &stroke.paint, false, shape_ts, bbox);
+            write_stroke(&mut self.body, stroke);
+        }
+
+        self.body.push_str("/>");
+    }
+
+    /// Write the attributes for a fill or stroke paint of an element whose
+    /// coordinate system has the given transform relative to the image.
+    ///
+    /// A gradient stretches over the bounding box with the given transform
+    /// and size and a pattern starts in its top-left corner, unless they are
+    /// relative to the page.
+    fn write_paint(
+        &mut self,
+        attr: &str,
+        paint: &Paint,
+        on_text: bool,
+        ts: Transform,
+        bbox: (Transform, Size),
+    ) {
+        match paint {
+            Paint::Solid(color) => write_color(&mut self.body, attr, *color),
+            Paint::Gradient(gradient) => {
+                match self.gradient(gradient, on_text, ts, bbox) {
+                    Some(id) => {
+                        write!(self.body, " {attr}=\"url(#{}gr{id})\"", self.prefix)
+                            .unwrap()
+                    }
+                    // A gradient can't be placed into a flattened coordinate
+                    // system.
+                    None => {
+                        let color = gradient.sample(Ratio::new(0.5));
+                        write_color(&mut self.body, attr, color);
+                    }
+                }
+            }
+            Paint::Pattern(pattern) => match self.pattern(pattern, on_text, ts, bbox.0) {
+                Some(id) => {
+                    write!(self.body, " {attr}=\"url(#{}p{id})\"", self.prefix).unwrap()
+                }
+                None => write!(self.body, " {attr}=\"none\"").unwrap(),
+            },
+        }
+    }
+
+    /// Define a gradient for an element whose coordinate system has the given
+    /// transform relative to the image and return its index.
+    fn gradient(
+        &mut self,
+        gradient: &Gradient,
+        on_text: bool,
+        ts: Transform,
+        (bbox_ts, size): (Transform, Size),
+    ) -> Option<usize> {
+        let (bbox_ts, size) = match gradient.relative_to(on_text) {
+            RelativeTo::Shape => (bbox_ts, size),
+            RelativeTo::Page => (Transform::identity(), self.size),
+        };
+
+        let transform = ts.invert()?.pre_concat(bbox_ts);
+        let id = self.gradients;
+        let prefix = &self.prefix;
+        self.gradients += 1;
+
+        match gradient {
+            Gradient::Linear(linear) => {
+                let (start, end) = linear.axis(size);
+                write!(
+                    self.defs,
+                    "<linearGradient id=\"{prefix}gr{id}\" gradientUnits=\"userSpaceOnUse\" \
+                     x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" gradientTransform=\"{}\">",
+                    Num(start.x.to_pt()),
+                    Num(start.y.to_pt()),
+                    Num(end.x.to_pt()),
+                    Num(end.y.to_pt()),
+                    Matrix(transform),
+                )
+                .unwrap();
+            }
+            Gradient::Radial(radial) => {
+                // The center and radius are relative to the box, which
+                // stretches the circle into an ellipse in a box that isn't
+                // square.
+                let scale = Transform::scale(
+                    Ratio::new(size.x.to_pt()),
+                    Ratio::new(size.y.to_pt()),
+                );
+                write!(
+                    self.defs,
+                    "<radialGradient id=\"{prefix}gr{id}\" gradientUnits=\"userSpaceOnUse\" \
+                     cx=\"{}\" cy=\"{}\" r=\"{}\" gradientTransform=\"{}\">",
+                    Num(radial.center.x.get()),
+                    Num(radial.center.y.get()),
+                    Num(radial.radius.get()),
+                    Matrix(transform.pre_concat(scale)),
+                )
+                .unwrap();
+            }
+        }
+
+        for &(color, offset) in gradient.stops() {
+            write!(self.defs, "<stop offset=\"{}\"", Num(offset.get())).unwrap();
+            write_color(&mut self.defs, "stop-color", color);
+            self.defs.push_str("/>");
+        }
+
+        self.defs.push_str(match gradient {
+            Gradient::Linear(_) => "</linearGradient>",
+            Gradient::Radial(_) => "</radialGradient>",
+        });
+
+        Some(id)
+    }
+
+    /// Define a pattern for an element whose coordinate system has the given
+    /// transform relative to the image and return its index.
+    fn pattern(
+        &mut self,
+        pattern: &Pattern,
+        on_text: bool,
+        ts: Transform,
+        bbox_ts: Transform,
+    ) -> Option<usize> {
+        let bbox_ts = match pattern.relative_to(on_text) {
+            RelativeTo::Shape => bbox_ts,
+            RelativeTo::Page => Transform::identity(),
+        };
+
+        let transform = ts.invert()?.pre_concat(bbox_ts);
+        let tile = self.tile(pattern);
+        let id = self.patterns;
+        let prefix = &self.prefix;
+        self.patterns += 1;
+
+        let cell = pattern.cell();
+        write!(
+            self.defs,
+            "<pattern id=\"{prefix}p{id}\" patternUnits=\"userSpaceOnUse\" \
+             width=\"{}\" height=\"{}\" patternTransform=\"{}\">\
+             <use xlink:href=\"#{prefix}pt{tile}\"/></pattern>",
+            Num(cell.x.to_pt()),
+            Num(cell.y.to_pt()),
+            Matrix(transform),
+        )
+        .unwrap();
+
+        Some(id)
+    }
+
+    /// Retrieve the index of a pattern's tile definition, defining it if
+    /// necessary.
+    ///
+    /// Links in the tile are dropped.
+    fn tile(&mut self, pattern: &Pattern) -> usize {
+        if let Some(&index) = self.tiles.get(pattern) {
+            return index;
+        }
+
+        let index = self.tiles.len();
+        self.tiles.insert(pattern.clone(), index);
+
+        // Write the tile like a separate image whose body becomes the
+        // definition.
+        let size = std::mem::replace(&mut self.size, pattern.size());
+        let body = std::mem::take(&mut self.body);
+        let links = std::mem::take(&mut self.links);
+        self.write_frame(Transform::identity(), pattern.frame());
+        let tile = std::mem::replace(&mut self.body, body);
+        self.links = links;
+        self.size = size;
+
+        write!(self.defs, "<g id=\"{}pt{index}\">{tile}</g>", self.prefix).unwrap();
+        index
+    }
+
+    /// Write a raster or vector image.
+    ///
+    /// PDF pages can't be displayed in SVG, so they show up as a crossed-out
+    /// placeholder.
+    fn write_image(&mut self, pos: Point, image: &Image, size: Size) {
+        if image.format() == ImageFormat::Vector(VectorFormat::Pdf) {
+            let (w, h) = (Num(size.x.to_pt()), Num(size.y.to_pt()));
+            write!(
+                self.body,
+                "<g transform=\"translate({} {})\"><rect width=\"{w}\" height=\"{h}\" \
+                 fill=\"#dddddd\"/><path d=\"M0 0L{w} {h}M{w} 0L0 {h}\" \
+                 stroke=\"#aaaaaa\"/></g>",
+                Num(pos.x.to_pt()),
+                Num(pos.y.to_pt()),
+            )
+            .unwrap();
+            return;
+        }
+
+        write!(
+            self.body,
+            "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+             preserveAspectRatio=\"none\" xlink:href=\"{}\"/>",
+            Num(pos.x.to_pt()),
+            Num(pos.y.to_pt()),
+            Num(size.x.to_pt()),
+            Num(size.y.to_pt()),
+            data_url(image),
+        )
+        .unwrap();
+    }
+}
+
+/// Write a glyph's outline as a path in font units.
+fn write_outline_glyph(buf: &mut String, font: &Font, id: u16) {
+    let mut builder = SvgPathBuilder(String::new());
+    if font.ttf().outline_glyph(GlyphId(id), &mut builder).is_some() {
+        write!(buf, "<path d=\"{}\"/>", builder.0).unwrap();
+    }
+}
+
+/// Write a glyph from the font's SVG or bitmap tables as an image in font
+/// units.
+///
+/// Returns `false` if the glyph is a plain outline glyph.
+fn write_image_glyph(buf: &mut String, font: &Font, id: u16) -> bool {
+    let Some((png, size)) = render_image_glyph(font, id) else { return false };
+    let ascender = font.metrics().ascender.get() * font.units_per_em();
+
+    write!(
+        buf,
+        "<image x=\"0\" y=\"{}\" width=\"{}\" height=\"{}\" \
+         preserveAspectRatio=\"none\" xlink:href=\"data:image/png;base64,{}\"/>",
+        Num(-ascender),
+        Num(size.x.to_pt()),
+        Num(size.y.to_pt()),
+        base64::encode(png),
+    )
+    .unwrap();
+
+    true
+}
+
+/// Wrap a glyph from the font's SVG table into a standalone SVG document.
+///
+/// The document's viewport is the box that vector exports draw such glyphs
+/// into, with its top edge at the font's ascender, and its units are font
+/// units. Returns the document and its size in font units or `None` if the
+/// glyph is not an SVG glyph.
+pub(super) fn svg_glyph_document(font: &Font, id: u16) -> Option<(Vec<u8>, Size)> {
+    let xml = svg_glyph_source(font, GlyphId(id))?;
+    let document = roxmltree::Document::parse(&xml).ok()?;
+    let root = document.root_element();
+    let opts = usvg::Options::default();
+    let tree = usvg::Tree::from_xmltree(&document, &opts.to_ref()).ok()?;
+
+    // Map the document's view box onto its viewport like a renderer would
+    // and then one em onto the em square.
+    let upem = font.units_per_em();
+    let (width, height) = svg_glyph_em(font, root, &tree);
+    let svg = tree.svg_node();
+    let sx = upem / width * svg.size.width() / svg.view_box.rect.width();
+    let sy = upem / height * svg.size.height() / svg.view_box.rect.height();
+    let (tx, ty) = (-svg.view_box.rect.x() * sx, -svg.view_box.rect.y() * sy);
+
+    let size = image_glyph_size(font, id);
+    let ascender = font.metrics().ascender.get() * upem;
+    let mut buf = String::from("<svg xmlns=\"http://www.w3.org/2000/svg\"");
+    for ns in root.namespaces() {
+        if let Some(name) = ns.name().filter(|&name| name != "xml") {
+            write!(buf, " xmlns:{}=\"{}\"", name, Escaped(ns.uri())).unwrap();
+        }
+    }
+
+    write!(
+        buf,
+        " width=\"{0}\" height=\"{1}\" viewBox=\"0 {2} {0} {1}\">",
+        Num(size.x.to_pt()),
+        Num(size.y.to_pt()),
+        Num(-ascender),
+    )
+    .unwrap();
+
+    // The root's presentation attributes are inherited by the glyph, so they
+    // move onto the group.
+    write!(
+        buf,
+        "<g transform=\"matrix({} 0 0 {} {} {})\"",
+        Num(sx),
+        Num(sy),
+        Num(tx),
+        Num(ty)
+    )
+    .unwrap();
+    let viewport = [
+        "x",
+        "y",
+        "width",
+        "height",
+        "viewBox",
+        "preserveAspectRatio",
+        "version",
+        "transform",
+    ];
+    for attr in root.attributes() {
+        if attr.namespace().is_none() && !viewport.contains(&attr.name()) {
+            write!(buf, " {}=\"{}\"", attr.name(), Escaped(attr.value())).unwrap();
+        }
+    }
+
+    buf.push('>');
+    if let (Some(first), Some(last)) = (root.first_child(), root.last_child()) {
+        buf.push_str(&xml[first.range().start..last.range().end]);
+    }
+    buf.push_str("</g></svg>");
+
+    Some((buf.into_bytes(), size))
+}
+
+/// Write the path data for a bezier path.
+fn write_path(buf: &mut String, path: &geom::Path) {
+    for item in &path.0 {
+        match item {
+            PathItem::MoveTo(p) => {
+                write!(buf, "M{} {}", Num(p.x.to_pt()), Num(p.y.to_pt())).unwrap();
+            }
+            PathItem::LineTo(p) => {
+                write!(buf, "L{} {}", Num(p.x.to_pt()), Num(p.y.to_pt())).unwrap();
+            }
+            PathItem::CubicTo(p1, p2, p3) => {
+                write!(
+                    buf,
+                    "C{} {} {} {} {} {}",
+                    Num(p1.x.to_pt()),
+                    Num(p1.y.to_pt()),
+                    Num(p2.x.to_pt()),
+                    Num(p2.y.to_pt()),
+                    Num(p3.x.to_pt()),
+                    Num(p3.y.to_pt()),
+                )
+                .unwrap();
+            }
+            PathItem::ClosePath => buf.push('Z'),
+        }
+    }
+}
+
+/// Write the attributes for a color.
+///
+/// The opacity goes into an attribute with the `-opacity` suffix, except for
+/// `stop-color`, whose opacity attribute is `stop-opacity`.
+fn write_color(buf: &mut String, attr: &str, color: Color) {
+    let c = color.to_rgba();
+    write!(buf, " {attr}=\"#{:02x}{:02x}{:02x}\"", c.r, c.g, c.b).unwrap();
+    if c.a != 255 {
+        let attr = attr.strip_suffix("-color").unwrap_or(attr);
+        write!(buf, " {attr}-opacity=\"{}\"", Num(c.a as f64 / 255.0)).unwrap();
+    }
+}
+
+/// Write the attributes for a stroke, except for its paint.
+fn write_stroke(buf: &mut String, stroke: &Stroke) {
+    write!(buf, " stroke-width=\"{}\"", Num(stroke.thickness.to_pt())).unwrap();
+
+    let cap = match stroke.line_cap {
+        LineCap::Butt => "butt",
+        LineCap::Round => "round",
+        LineCap::Square => "square",
+    };
+
+    let join = match stroke.line_join {
+        LineJoin::Miter => "miter",
+        LineJoin::Round => "round",
+        LineJoin::Bevel => "bevel",
+    };
+
+    write!(buf, " stroke-linecap=\"{cap}\" stroke-linejoin=\"{join}\"").unwrap();
+    write!(buf, " stroke-miterlimit=\"{}\"", Num(stroke.miter_limit.0)).unwrap();
+
+    if let Some(pattern) = &stroke.dash_pattern {
+        buf.push_str(" stroke-dasharray=\"");
+        for (i, length) in pattern.array.iter().enumerate() {
+            if i > 0 {
+                buf.push(' ');
+            }
+            write!(buf, "{}", Num(length.to_pt())).unwrap();
+        }
+        write!(buf, "\" stroke-dashoffset=\"{}\"", Num(pattern.phase.to_pt())).unwrap();
+    }
+}
+
+/// Encode an image's data as a data URL.
+pub(super) fn data_url(image: &Image) -> String {
+    let mime = match image.format() {
+        ImageFormat::Raster(RasterFormat::Png) => "image/png",
+        ImageFormat::Raster(RasterFormat::Jpg) => "image/jpeg",
+        ImageFormat::Raster(RasterFormat::Gif) => "image/gif",
+        ImageFormat::Vector(VectorFormat::Svg) => "image/svg+xml",
+        ImageFormat::Vector(VectorFormat::Pdf) => "application/pdf",
+    };
+    format!("data:{mime};base64,{}", base64::encode(image.data()))
+}
+
+/// Allows to build SVG path data from glyph outlines.
+///
+/// Flips the y-axis because the font design coordinate system is y-up.
+struct SvgPathBuilder(String);
+
+impl OutlineBuilder for SvgPathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        write!(self.0, "M{} {}", x, -y).unwrap();
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        write!(self.0, "L{} {}", x, -y).unwrap();
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        write!(self.0, "Q{} {} {} {}", x1, -y1, x, -y).unwrap();
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        write!(self.0, "C{} {} {} {} {} {}", x1, -y1, x2, -y2, x, -y).unwrap();
+    }
+
+    fn close(&mut self) {
+        self.0.push('Z');
+    }
+}
+
+/// Displays a number with at most three decimal places.
+struct Num(f64);
+
+impl Display for Num {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let rounded = (self.0 * 1000.0).round() / 1000.0;
+        if rounded == 0.0 {
+            f.write_str("0")
+        } else {
+            write!(f, "{rounded}")
+        }
+    }
+}
+
+/// Displays a transform as an SVG transformation matrix.
+struct Matrix(Transform);
+
+impl Display for Matrix {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Transform { sx, ky, kx, sy, tx, ty } = self.0;
+        write!(
+            f,
+            "matrix({} {} {} {} {} {})",
+            Num(sx.get()),
+            Num(ky.get()),
+            Num(kx.get()),
+            Num(sy.get()),
+            Num(tx.to_pt()),
+            Num(ty.to_pt()),
+        )
+    }
+}
+
+/// Displays a string with XML special characters escaped.
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use ecow::eco_format;
+
+    use super::*;
+    use crate::doc::{Glyph, Lang, Position};
+    use crate::syntax::Span;
+    use crate::util::Buffer;
+
+    fn frame() -> Frame {
+        let size = Size::new(Abs::pt(100.0), Abs::pt(50.0));
+        let mut inner = Frame::new(size);
+        let rect = Geometry::Rect(size).filled(Color::BLACK.into());
+        inner.push(Point::zero(), FrameItem::Shape(rect, Span::detached()));
+
+        let mut group = GroupItem::new(inner);
+        group.clips = true;
+
+        let mut frame = Frame::new(size);
+        frame.push(Point::zero(), FrameItem::Group(group));
+        frame
+    }
+
+    fn link(frame: &mut Frame, dest: Destination) {
+        frame.push(Point::zero(), FrameItem::Meta(Meta::Link(dest), frame.size()));
+    }
+
+    #[test]
+    fn test_svg_num() {
+        assert_eq!(Num(1.0).to_string(), "1");
+        assert_eq!(Num(0.12345).to_string(), "0.123");
+        assert_eq!(Num(-0.0001).to_string(), "0");
+    }
+
+    #[test]
+    fn test_svg_ids_are_prefixed() {
+        let first = svg(&frame());
+        let mut other = frame();
+        other.push(Point::zero(), FrameItem::Group(GroupItem::new(frame())));
+        let second = svg(&other);
+
+        let prefix = |svg: &str| {
+            let start = svg.find("<clipPath id=\"").unwrap() + 14;
+            let end = start + svg[start..].find('-').unwrap() + 1;
+            svg[start..end].to_string()
+        };
+
+        let (a, b) = (prefix(&first), prefix(&second));
+        assert_ne!(a, b);
+        assert!(first.contains(&format!("<clipPath id=\"{a}c0\"")));
+        assert!(first.contains(&format!("clip-path=\"url(#{a}c0)\"")));
+        assert!(!first.contains("id=\"c0\""));
+    }
+
+    #[test]
+    fn test_svg_links() {
+        let position = Position {
+            page: NonZeroUsize::new(2).unwrap(),
+            point: Point::zero(),
+        };
+
+        let mut frame = frame();
+        link(&mut frame, Destination::Url("https://typst.app".into()));
+        link(&mut frame, Destination::Position(position));
+
+        // Without a resolver, only the link to the URL is kept.
+        let plain = svg(&frame);
+        assert_eq!(plain.matches("<a ").count(), 1);
+        assert!(plain.contains("xlink:href=\"https://typst.app\""));
+
+        let resolved = svg_with_links(&frame, &|dest| match dest {
+            Destination::Position(pos) => Some(eco_format!("page-{}.svg", pos.page)),
+            _ => None,
+        });
+        assert_eq!(resolved.matches("<a ").count(), 2);
+        assert!(resolved.contains("xlink:href=\"page-2.svg\""));
+    }
+
+    #[test]
+    fn test_svg_selectable_text() {
+        let data = include_bytes!("../../assets/fonts/PTSans-Regular.ttf");
+        let font = Font::new(Buffer::from(&data[..]), 0).unwrap();
+        let glyph = |c: char, start: usize| Glyph {
+            id: font.ttf().glyph_index(c).unwrap().0,
+            x_advance: Em::new(0.5),
+            x_offset: Em::zero(),
+            c,
+            range: start..start + 1,
+            span: Span::detached(),
+            offset: 0,
+        };
+
+        let text = TextItem {
+            font: font.clone(),
+            size: Abs::pt(10.0),
+            fill: Color::BLACK.into(),
+            lang: Lang::ENGLISH,
+            text: "a<b".into(),
+            glyphs: vec![glyph('a', 0), glyph('<', 1), glyph('b', 2)],
+        };
+
+        let mut frame = Frame::new(Size::new(Abs::pt(100.0), Abs::pt(20.0)));
+        frame.push(Point::with_y(Abs::pt(10.0)), FrameItem::Text(text.clone()));
+
+        let output = svg(&frame);
+        let upem = font.units_per_em();
+        let x = format!("<text x=\"0 {} {}\"", Num(upem / 2.0), Num(upem));
+        assert!(output.contains(&x));
+        assert!(output.contains("fill-opacity=\"0\""));
+        assert!(output.contains(">a&lt;b</text>"));
+
+        // Clusters more than 64 KiB into a run keep their text.
+        let long = TextItem {
+            text: format!("{}bc", "a".repeat(70_000)).into(),
+            glyphs: vec![glyph('b', 70_000), glyph('c', 70_001)],
+            ..text
+        };
+        let mut frame = Frame::new(Size::new(Abs::pt(100.0), Abs::pt(20.0)));
+        frame.push(Point::with_y(Abs::pt(10.0)), FrameItem::Text(long));
+        assert!(svg(&frame).contains(">bc</text>"));
+    }
+}
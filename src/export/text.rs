@@ -0,0 +1,458 @@
+//! Extracting the text of documents.
+//!
+//! The text is extracted from the semantic HTML form of a document rather than
+//! from its laid-out frames. Frames only hold positioned glyphs, from which
+//! hyphenated words, ligatures and the reading order would have to be pieced
+//! back together. The HTML form still holds the text as written along with the
+//! structure of headings, lists and tables. It is produced by
+//! [`compile_html`](crate::compile_html).
+
+use ecow::EcoString;
+
+use super::{HtmlDocument, HtmlElement, HtmlNode};
+
+/// Extract a document's text in reading order.
+///
+/// Paragraphs and other blocks are separated by blank lines, headings are
+/// marked with one `#` per level, list items are marked with bullets or
+/// numbers and table cells are separated by tabs.
+/// Content without a textual equivalent, like equations and shapes, is
+/// omitted.
+pub fn text(document: &HtmlDocument) -> String {
+    finish(&Extractor { markdown: false }.blocks(&document.body))
+}
+
+/// Extract a document's text as Markdown.
+///
+/// In addition to the structure kept by [`text`], this marks up emphasis,
+/// links, code and tables.
+pub fn markdown(document: &HtmlDocument) -> String {
+    finish(&Extractor { markdown: true }.blocks(&document.body))
+}
+
+/// Join blocks into a complete text file.
+fn finish(blocks: &[String]) -> String {
+    let mut text = blocks.join("\n\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+/// Converts HTML nodes into text.
+struct Extractor {
+    /// Whether to produce Markdown instead of plain text.
+    markdown: bool,
+}
+
+impl Extractor {
+    /// Convert nodes into blocks of text, which may span multiple lines.
+    fn blocks(&self, nodes: &[HtmlNode]) -> Vec<String> {
+        let mut blocks = vec![];
+        let mut run: Vec<&HtmlNode> = vec![];
+        for node in nodes {
+            match node {
+                HtmlNode::Element(elem) if elem.is_block() => {
+                    self.flush(&mut run, &mut blocks);
+                    blocks.extend(self.block(elem));
+                }
+                node => run.push(node),
+            }
+        }
+
+        self.flush(&mut run, &mut blocks);
+        blocks
+    }
+
+    /// Turn a run of inline nodes into a block.
+    fn flush(&self, run: &mut Vec<&HtmlNode>, blocks: &mut Vec<String>) {
+        let mut text = String::new();
+        for node in run.drain(..) {
+            self.inline(node, &mut text);
+        }
+
+        let text = text.trim();
+        if !text.is_empty() {
+            blocks.push(text.into());
+        }
+    }
+
+    /// Convert a block-level element.
+    fn block(&self, elem: &HtmlElement) -> Vec<String> {
+        let mut blocks = vec![];
+        match elem.tag.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = usize::from(elem.tag.as_bytes()[1] - b'0');
+                let text = self.line(&elem.children);
+                blocks.push(format!("{} {text}", "#".repeat(level)));
+            }
+            "ul" | "ol" => blocks.push(self.list(elem)),
+            "dl" => blocks.push(self.terms(elem)),
+            "pre" => blocks.push(self.code(elem)),
+            "table" => blocks.push(self.table(elem)),
+            "hr" => blocks.push(if self.markdown { "---" } else { "" }.into()),
+            _ => blocks.extend(self.blocks(&elem.children)),
+        }
+        blocks.retain(|block| !block.is_empty());
+        blocks
+    }
+
+    /// Convert a bullet or numbered list.
+    fn list(&self, elem: &HtmlElement) -> String {
+        let ordered = elem.tag == "ol";
+        let mut number = elem.attr("start").and_then(|s| s.parse().ok()).unwrap_or(1);
+        let mut lines = vec![];
+        for item in children(elem, &["li"]) {
+            if let Some(value) = item.attr("value").and_then(|s| s.parse().ok()) {
+                number = value;
+            }
+
+            let marker = if ordered { format!("{number}. ") } else { "- ".into() };
+            let body = self.blocks(&item.children).join("\n");
+            lines.push(indent(&marker, &body));
+            number += 1;
+        }
+        lines.join("\n")
+    }
+
+    /// Convert a term list.
+    fn terms(&self, elem: &HtmlElement) -> String {
+        let mut lines = vec![];
+        let mut term = None;
+        for child in children(elem, &["dt", "dd"]) {
+            if child.tag == "dt" {
+                term = Some(self.line(&child.children));
+                continue;
+            }
+
+            let term = term.take().unwrap_or_default();
+            let description = self.blocks(&child.children).join("\n");
+            lines.push(if self.markdown {
+                indent("- ", &format!("**{term}**: {description}"))
+            } else {
+                indent("", &format!("{term}: {description}"))
+            });
+        }
+        lines.join("\n")
+    }
+
+    /// Convert preformatted text, which is kept verbatim.
+    fn code(&self, elem: &HtmlElement) -> String {
+        let mut text = String::new();
+        let mut lang = None;
+        for child in &elem.children {
+            if let HtmlNode::Element(code) = child {
+                lang = code
+                    .attr("class")
+                    .and_then(|class| class.strip_prefix("language-"))
+                    .or(lang);
+            }
+            raw(child, &mut text);
+        }
+
+        if !self.markdown {
+            return text;
+        }
+
+        let fence = "`".repeat(longest_run(&text, '`').max(2) + 1);
+        format!("{fence}{}\n{text}\n{fence}", lang.unwrap_or_default())
+    }
+
+    /// Convert a table with one row per line.
+    fn table(&self, elem: &HtmlElement) -> String {
+        let rows: Vec<Vec<String>> = children(elem, &["tr"])
+            .map(|row| {
+                children(row, &["td", "th"])
+                    .map(|cell| self.line(&cell.children))
+                    .collect()
+            })
+            .collect();
+
+        if !self.markdown {
+            let lines: Vec<_> = rows.iter().map(|row| row.join("\t")).collect();
+            return lines.join("\n");
+        }
+
+        // Markdown tables need a header, for which the first row is used.
+        let mut lines = vec![];
+        for (i, row) in rows.iter().enumerate() {
+            let cells: Vec<_> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+            lines.push(format!("| {} |", cells.join(" | ")));
+            if i == 0 {
+                lines.push(format!("|{}", " --- |".repeat(row.len())));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Convert nodes into a single line of text.
+    fn line(&self, nodes: &[HtmlNode]) -> String {
+        self.blocks(nodes).join(" ").replace('\n', " ")
+    }
+
+    /// Convert an inline node.
+    fn inline(&self, node: &HtmlNode, text: &mut String) {
+        let elem = match node {
+            HtmlNode::Text(s) if self.markdown => return escape(s, text),
+            HtmlNode::Text(s) => return text.push_str(s),
+            HtmlNode::Element(elem) => elem,
+            HtmlNode::Frame(_) | HtmlNode::Image(_) => return,
+        };
+
+        let inner = || {
+            let mut inner = String::new();
+            for child in &elem.children {
+                self.inline(child, &mut inner);
+            }
+            inner
+        };
+
+        if !self.markdown {
+            match elem.tag.as_str() {
+                "br" => text.push('\n'),
+                _ => text.push_str(&inner()),
+            }
+            return;
+        }
+
+        match elem.tag.as_str() {
+            "br" => text.push_str("\\\n"),
+            "strong" => text.push_str(&wrap("**", &inner())),
+            "em" => text.push_str(&wrap("_", &inner())),
+            "code" => {
+                let mut code = String::new();
+                raw(node, &mut code);
+                let ticks = "`".repeat(longest_run(&code, '`') + 1);
+                let pad =
+                    if code.starts_with('`') || code.ends_with('`') { " " } else { "" };
+                text.push_str(&format!("{ticks}{pad}{code}{pad}{ticks}"));
+            }
+            "a" => match elem.attr("href") {
+                Some(href) => {
+                    text.push_str(&format!("[{}]({})", inner(), escape_href(href)))
+                }
+                None => text.push_str(&inner()),
+            },
+            _ => text.push_str(&inner()),
+        }
+    }
+}
+
+/// The child elements with one of the given tags.
+fn children<'a>(
+    elem: &'a HtmlElement,
+    tags: &'a [&str],
+) -> impl Iterator<Item = &'a HtmlElement> + 'a {
+    elem.children.iter().filter_map(|child| match child {
+        HtmlNode::Element(child) if tags.contains(&child.tag.as_str()) => Some(child),
+        _ => None,
+    })
+}
+
+/// Collect the verbatim text of a node.
+pub(super) fn raw(node: &HtmlNode, text: &mut String) {
+    match node {
+        HtmlNode::Text(s) => text.push_str(s),
+        HtmlNode::Element(elem) if elem.tag == "br" => text.push('\n'),
+        HtmlNode::Element(elem) => {
+            elem.children.iter().for_each(|child| raw(child, text));
+        }
+        HtmlNode::Frame(_) | HtmlNode::Image(_) => {}
+    }
+}
+
+/// Prefix the first line of a block with a marker and indent the others to
+/// align with it.
+fn indent(marker: &str, body: &str) -> String {
+    let mut text = String::from(marker);
+    for (i, line) in body.lines().enumerate() {
+        if i > 0 {
+            text.push('\n');
+            if !line.is_empty() {
+                text.push_str(&" ".repeat(marker.chars().count().max(2)));
+            }
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+/// Wrap Markdown text in delimiters, keeping surrounding whitespace outside of
+/// them.
+fn wrap(delim: &str, inner: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.into();
+    }
+
+    let start = inner.len() - inner.trim_start().len();
+    let end = start + trimmed.len();
+    format!("{}{delim}{trimmed}{delim}{}", &inner[..start], &inner[end..])
+}
+
+/// The length of the longest run of a character in a text.
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|d| d != c).map(str::len).max().unwrap_or(0)
+}
+
+/// Escape characters that have a meaning in Markdown.
+fn escape(s: &EcoString, text: &mut String) {
+    for c in s.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            text.push('\\');
+        }
+        text.push(c);
+    }
+}
+
+/// Percent-encode the characters that would end a Markdown link destination.
+fn escape_href(href: &str) -> String {
+    let mut escaped = String::new();
+    for c in href.chars() {
+        match c {
+            '(' | ')' | '<' | '>' | ' ' | '\t' | '\n' => {
+                escaped.push_str(&format!("%{:02X}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txt(text: &str) -> HtmlNode {
+        HtmlNode::Text(text.into())
+    }
+
+    fn elem(tag: &str, attrs: &[(&str, &str)], children: Vec<HtmlNode>) -> HtmlNode {
+        let mut elem = HtmlElement::new(tag).with_children(children);
+        for &(name, value) in attrs {
+            elem = elem.with_attr(name, value);
+        }
+        HtmlNode::Element(elem)
+    }
+
+    /// Extract the plain text and the Markdown of a body.
+    fn extract(body: Vec<HtmlNode>) -> (String, String) {
+        let document = HtmlDocument { body, ..Default::default() };
+        (text(&document), markdown(&document))
+    }
+
+    #[test]
+    fn test_text_headings_and_paragraphs() {
+        let (text, markdown) = extract(vec![
+            elem("h2", &[], vec![txt("Intro")]),
+            elem("p", &[], vec![txt("Hello "), elem("em", &[], vec![txt("world ")])]),
+            txt("Loose text"),
+        ]);
+
+        assert_eq!(text, "## Intro\n\nHello world\n\nLoose text\n");
+        assert_eq!(markdown, "## Intro\n\nHello _world_\n\nLoose text\n");
+    }
+
+    #[test]
+    fn test_text_lists() {
+        let (text, markdown) = extract(vec![
+            elem(
+                "ul",
+                &[],
+                vec![
+                    elem("li", &[], vec![txt("First")]),
+                    elem(
+                        "li",
+                        &[],
+                        vec![
+                            txt("Second"),
+                            elem("ul", &[], vec![elem("li", &[], vec![txt("Nested")])]),
+                        ],
+                    ),
+                ],
+            ),
+            elem(
+                "ol",
+                &[("start", "3")],
+                vec![
+                    elem("li", &[], vec![txt("Three")]),
+                    elem("li", &[("value", "7")], vec![txt("Seven")]),
+                    elem("li", &[], vec![txt("Eight")]),
+                ],
+            ),
+        ]);
+
+        let expected = "- First\n- Second\n  - Nested\n\n3. Three\n7. Seven\n8. Eight\n";
+        assert_eq!(text, expected);
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_text_tables() {
+        let (text, markdown) = extract(vec![elem(
+            "table",
+            &[],
+            vec![
+                elem(
+                    "tr",
+                    &[],
+                    vec![
+                        elem("td", &[], vec![txt("a")]),
+                        elem("td", &[], vec![txt("b|c")]),
+                    ],
+                ),
+                elem(
+                    "tr",
+                    &[],
+                    vec![
+                        elem("td", &[], vec![txt("d")]),
+                        elem("td", &[], vec![txt("e")]),
+                    ],
+                ),
+            ],
+        )]);
+
+        assert_eq!(text, "a\tb|c\nd\te\n");
+        assert_eq!(markdown, "| a | b\\|c |\n| --- | --- |\n| d | e |\n");
+    }
+
+    #[test]
+    fn test_text_code() {
+        let code = "let x = `y`;\nlet z = 1;";
+        let (text, markdown) = extract(vec![
+            elem(
+                "pre",
+                &[],
+                vec![elem("code", &[("class", "language-rs")], vec![txt(code)])],
+            ),
+            elem(
+                "p",
+                &[],
+                vec![txt("Use "), elem("code", &[], vec![txt("a`b")]), txt(" here.")],
+            ),
+        ]);
+
+        assert_eq!(text, format!("{code}\n\nUse a`b here.\n"));
+        assert_eq!(markdown, format!("```rs\n{code}\n```\n\nUse ``a`b`` here.\n"));
+    }
+
+    #[test]
+    fn test_text_escaping() {
+        let (text, markdown) = extract(vec![elem(
+            "p",
+            &[],
+            vec![
+                txt("a*b_c [d] #e "),
+                elem("strong", &[], vec![txt("f")]),
+                txt(" "),
+                elem("a", &[("href", "https://example.com/a b(c)")], vec![txt("link")]),
+            ],
+        )]);
+
+        assert_eq!(text, "a*b_c [d] #e f link\n");
+        assert_eq!(
+            markdown,
+            "a\\*b\\_c \\[d\\] \\#e **f** [link](https://example.com/a%20b%28c%29)\n"
+        );
+    }
+}
@@ -10,10 +10,10 @@ use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-use ttf_parser::GlyphId;
+use ttf_parser::{GlyphId, Tag};
 
 use crate::eval::Cast;
-use crate::geom::Em;
+use crate::geom::{Em, Scalar};
 use crate::util::Buffer;
 
 /// An OpenType font.
@@ -36,11 +36,23 @@ struct Repr {
     ttf: ttf_parser::Face<'static>,
     /// The underlying rustybuzz face.
     rusty: rustybuzz::Face<'static>,
+    /// The coordinates of the variation axes that differ from their defaults,
+    /// sorted by tag.
+    variations: Vec<(Tag, Scalar)>,
 }
 
 impl Font {
     /// Parse a font from data and collection index.
     pub fn new(data: Buffer, index: u32) -> Option<Self> {
+        Self::with_coordinates(data, index, vec![])
+    }
+
+    /// Parse a font and set the coordinates of its variation axes.
+    fn with_coordinates(
+        data: Buffer,
+        index: u32,
+        variations: Vec<(Tag, Scalar)>,
+    ) -> Option<Self> {
         // Safety:
         // - The slices's location is stable in memory:
         //   - We don't move the underlying vector
@@ -50,12 +62,20 @@ impl Font {
         let slice: &'static [u8] =
             unsafe { std::slice::from_raw_parts(data.as_ptr(), data.len()) };
 
-        let ttf = ttf_parser::Face::parse(slice, index).ok()?;
-        let rusty = rustybuzz::Face::from_slice(slice, index)?;
+        let mut ttf = ttf_parser::Face::parse(slice, index).ok()?;
+        let mut rusty = rustybuzz::Face::from_slice(slice, index)?;
+        for &(tag, value) in &variations {
+            ttf.set_variation(tag, value.0 as f32)?;
+            rusty.set_variations(&[rustybuzz::Variation {
+                tag: rustybuzz::Tag(tag.0),
+                value: value.0 as f32,
+            }]);
+        }
+
         let metrics = FontMetrics::from_ttf(&ttf);
         let info = FontInfo::from_ttf(&ttf)?;
 
-        Some(Self(Arc::new(Repr { data, index, info, metrics, ttf, rusty })))
+        Some(Self(Arc::new(Repr { data, index, info, metrics, ttf, rusty, variations })))
     }
 
     /// Parse all fonts in the given data.
@@ -74,6 +94,43 @@ impl Font {
         self.0.index
     }
 
+    /// Create an instance of a variable font with the given coordinates for
+    /// its variation axes, like `wght` for the weight.
+    ///
+    /// Coordinates for axes the font doesn't have are ignored and the others
+    /// are clamped to the axes' ranges. Later coordinates for the same axis
+    /// take precedence over earlier ones. Returns the font itself if nothing
+    /// changes.
+    pub fn with_variations(&self, variations: &[(Tag, f64)]) -> Self {
+        let mut coords = self.0.variations.clone();
+        for &(tag, value) in variations {
+            let axes = self.ttf().variation_axes();
+            let Some(axis) = axes.into_iter().find(|axis| axis.tag == tag) else {
+                continue;
+            };
+
+            let value = value.max(axis.min_value.into()).min(axis.max_value.into());
+            coords.retain(|&(t, _)| t != tag);
+            if value != f64::from(axis.def_value) {
+                coords.push((tag, Scalar(value)));
+            }
+        }
+
+        coords.sort_by_key(|&(tag, _)| tag);
+        if coords == self.0.variations {
+            return self.clone();
+        }
+
+        instantiate(&self.0.data, self.0.index, coords).unwrap_or_else(|| self.clone())
+    }
+
+    /// The coordinates of the font's variation axes that differ from their
+    /// defaults. This is empty unless the font is an instance of a variable
+    /// font created with [`with_variations`](Self::with_variations).
+    pub fn variations(&self) -> &[(Tag, Scalar)] {
+        &self.0.variations
+    }
+
     /// The font's metadata.
     pub fn info(&self) -> &FontInfo {
         &self.0.info
@@ -122,10 +179,21 @@ impl Font {
     }
 }
 
+/// Parse a font and set the coordinates of its variation axes.
+#[comemo::memoize]
+fn instantiate(
+    data: &Buffer,
+    index: u32,
+    variations: Vec<(Tag, Scalar)>,
+) -> Option<Font> {
+    Font::with_coordinates(data.clone(), index, variations)
+}
+
 impl Hash for Font {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.data.hash(state);
         self.0.index.hash(state);
+        self.0.variations.hash(state);
     }
 }
 
@@ -139,7 +207,9 @@ impl Eq for Font {}
 
 impl PartialEq for Font {
     fn eq(&self, other: &Self) -> bool {
-        self.0.data == other.0.data && self.0.index == other.0.index
+        self.0.data == other.0.data
+            && self.0.index == other.0.index
+            && self.0.variations == other.0.variations
     }
 }
 
@@ -244,3 +314,30 @@ pub enum VerticalFontMetric {
     /// The font's ascender, which typically exceeds the depth of all glyphs.
     Descender,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_font_variations() {
+        let data = include_bytes!("../../assets/fonts/InterVariable.ttf");
+        let font = Font::new(Buffer::from(&data[..]), 0).unwrap();
+        let wght = Tag::from_bytes(b"wght");
+        let id = font.ttf().glyph_index('a').unwrap();
+
+        // Coordinates are clamped to the axis and defaults as well as unknown
+        // axes don't create new instances.
+        let black = font.with_variations(&[(wght, 2000.0)]);
+        assert_eq!(black.variations(), [(wght, Scalar(900.0))]);
+        assert_eq!(font.with_variations(&[(wght, 400.0)]), font);
+        assert_eq!(font.with_variations(&[(Tag::from_bytes(b"wdth"), 50.0)]), font);
+
+        // The instance is shaped with wider advances and drawn with bolder
+        // outlines.
+        assert!(black.advance(id.0).unwrap() > font.advance(id.0).unwrap());
+        let regular = font.ttf().glyph_bounding_box(id).unwrap();
+        let bold = black.ttf().glyph_bounding_box(id).unwrap();
+        assert!(bold.width() > regular.width());
+    }
+}
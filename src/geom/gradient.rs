@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use super::*;
+
+/// A color gradient.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub enum Gradient {
+    /// A gradient along a straight line.
+    Linear(Arc<LinearGradient>),
+    /// A gradient that spreads out from a center point.
+    Radial(Arc<RadialGradient>),
+}
+
+/// A gradient along a straight line.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LinearGradient {
+    /// The color stops, sorted by their offsets.
+    pub stops: Vec<(Color, Ratio)>,
+    /// The direction of the gradient, measured clockwise from left-to-right.
+    pub angle: Angle,
+    /// What the gradient is relative to.
+    pub relative: Smart<RelativeTo>,
+}
+
+/// A gradient that spreads out from a center point.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RadialGradient {
+    /// The color stops, sorted by their offsets.
+    pub stops: Vec<(Color, Ratio)>,
+    /// The center of the gradient, relative to the bounding box.
+    pub center: Axes<Ratio>,
+    /// The radius of the gradient, relative to the bounding box.
+    pub radius: Ratio,
+    /// What the gradient is relative to.
+    pub relative: Smart<RelativeTo>,
+}
+
+/// The box that a gradient or pattern is relative to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum RelativeTo {
+    /// The bounding box of the filled or stroked shape or text.
+    Shape,
+    /// The page that the shape or text is on.
+    Page,
+}
+
+impl Gradient {
+    /// The color stops, sorted by their offsets.
+    pub fn stops(&self) -> &[(Color, Ratio)] {
+        match self {
+            Self::Linear(linear) => &linear.stops,
+            Self::Radial(radial) => &radial.stops,
+        }
+    }
+
+    /// What the gradient is relative to.
+    pub fn relative(&self) -> Smart<RelativeTo> {
+        match self {
+            Self::Linear(linear) => linear.relative,
+            Self::Radial(radial) => radial.relative,
+        }
+    }
+
+    /// What the gradient stretches over when it paints text or a shape.
+    ///
+    /// Unless specified otherwise, gradients on text stretch over the page
+    /// and gradients on shapes over the shape.
+    pub fn relative_to(&self, on_text: bool) -> RelativeTo {
+        self.relative().unwrap_or(RelativeTo::default_for(on_text))
+    }
+
+    /// The color of the gradient at the given offset, interpolated in RGBA.
+    pub fn sample(&self, t: Ratio) -> Color {
+        let stops = self.stops();
+        let t = t.get().clamp(0.0, 1.0);
+        let Some(i) = stops.iter().position(|&(_, offset)| offset.get() >= t) else {
+            return stops.last().map_or(Color::BLACK, |&(color, _)| color);
+        };
+
+        if i == 0 {
+            return stops[0].0;
+        }
+
+        let (c0, t0) = stops[i - 1];
+        let (c1, t1) = stops[i];
+        let span = t1.get() - t0.get();
+        let s = if span > 0.0 { (t - t0.get()) / span } else { 1.0 };
+        let (a, b) = (c0.to_rgba(), c1.to_rgba());
+        let lerp = |x: u8, y: u8| (x as f64 + (y as f64 - x as f64) * s).round() as u8;
+        RgbaColor::new(lerp(a.r, b.r), lerp(a.g, b.g), lerp(a.b, b.b), lerp(a.a, b.a))
+            .into()
+    }
+}
+
+impl LinearGradient {
+    /// The start and end points of the gradient's axis in a box of the given
+    /// size.
+    ///
+    /// The axis runs through the center of the box such that the offsets
+    /// zero and one fall onto the box's far corners.
+    pub fn axis(&self, size: Size) -> (Point, Point) {
+        let (sin, cos) = (self.angle.sin(), self.angle.cos());
+        let half = (size.x * cos.abs() + size.y * sin.abs()) / 2.0;
+        let center = Point::new(size.x / 2.0, size.y / 2.0);
+        let delta = Point::new(half * cos, half * sin);
+        (center - delta, center + delta)
+    }
+}
+
+impl RelativeTo {
+    /// What a paint is relative to if nothing is specified: The page for text
+    /// and the shape for everything else.
+    pub fn default_for(on_text: bool) -> Self {
+        if on_text {
+            Self::Page
+        } else {
+            Self::Shape
+        }
+    }
+
+    /// The name of the variant.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Shape => "shape",
+            Self::Page => "page",
+        }
+    }
+}
+
+impl Debug for Gradient {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Linear(_) => "linear",
+            Self::Radial(_) => "radial",
+        };
+
+        write!(f, "gradient.{name}(")?;
+        for (i, (color, offset)) in self.stops().iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "({color:?}, {offset:?})")?;
+        }
+
+        match self {
+            Self::Linear(linear) => write!(f, ", angle: {:?}", linear.angle)?,
+            Self::Radial(radial) => write!(
+                f,
+                ", center: ({:?}, {:?}), radius: {:?}",
+                radial.center.x, radial.center.y, radial.radius
+            )?,
+        }
+
+        if let Smart::Custom(relative) = self.relative() {
+            write!(f, ", relative: {:?}", relative.as_str())?;
+        }
+
+        f.write_str(")")
+    }
+}
+
+cast_from_value! {
+    Gradient: "gradient",
+}
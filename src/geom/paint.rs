@@ -7,6 +7,10 @@ use super::*;
 pub enum Paint {
     /// A solid color.
     Solid(Color),
+    /// A color gradient.
+    Gradient(Gradient),
+    /// A repeated tile of content.
+    Pattern(Pattern),
 }
 
 impl<T: Into<Color>> From<T> for Paint {
@@ -19,6 +23,8 @@ impl Debug for Paint {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             Self::Solid(color) => color.fmt(f),
+            Self::Gradient(gradient) => gradient.fmt(f),
+            Self::Pattern(pattern) => pattern.fmt(f),
         }
     }
 }
@@ -26,10 +32,16 @@ impl Debug for Paint {
 cast_from_value! {
     Paint,
     color: Color => Self::Solid(color),
+    gradient: Gradient => Self::Gradient(gradient),
+    pattern: Pattern => Self::Pattern(pattern),
 }
 
 cast_to_value! {
-    Paint::Solid(color): Paint => Value::Color(color)
+    v: Paint => match v {
+        Paint::Solid(color) => Value::Color(color),
+        Paint::Gradient(gradient) => gradient.into(),
+        Paint::Pattern(pattern) => pattern.into(),
+    }
 }
 
 /// A color in a dynamic format.
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use comemo::Prehashed;
+
+use super::*;
+use crate::doc::Frame;
+
+/// A tile of content that is repeated to fill an area.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct Pattern(Arc<Prehashed<Repr>>);
+
+/// The internal representation of a pattern.
+#[derive(Hash)]
+struct Repr {
+    /// The laid-out content of a single tile.
+    frame: Frame,
+    /// The gap between neighbouring tiles.
+    spacing: Size,
+    /// What the tiling is aligned to.
+    relative: Smart<RelativeTo>,
+}
+
+impl Pattern {
+    /// Create a new pattern from a laid-out tile.
+    pub fn new(frame: Frame, spacing: Size, relative: Smart<RelativeTo>) -> Self {
+        Self(Arc::new(Prehashed::new(Repr { frame, spacing, relative })))
+    }
+
+    /// The laid-out content of a single tile.
+    pub fn frame(&self) -> &Frame {
+        &self.0.frame
+    }
+
+    /// The size of a single tile.
+    pub fn size(&self) -> Size {
+        self.0.frame.size()
+    }
+
+    /// The gap between neighbouring tiles.
+    pub fn spacing(&self) -> Size {
+        self.0.spacing
+    }
+
+    /// The distance from the start of one tile to the start of the next.
+    pub fn cell(&self) -> Size {
+        self.size() + self.spacing()
+    }
+
+    /// What the tiling is aligned to.
+    pub fn relative(&self) -> Smart<RelativeTo> {
+        self.0.relative
+    }
+
+    /// What the tiling is aligned to when it paints text or a shape.
+    ///
+    /// Unless specified otherwise, patterns on text are aligned to the page
+    /// and patterns on shapes to the shape.
+    pub fn relative_to(&self, on_text: bool) -> RelativeTo {
+        self.relative().unwrap_or(RelativeTo::default_for(on_text))
+    }
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Size { x, y } = self.size();
+        write!(f, "pattern(size: ({x:?}, {y:?})")?;
+        if !self.spacing().is_zero() {
+            let Size { x, y } = self.spacing();
+            write!(f, ", spacing: ({x:?}, {y:?})")?;
+        }
+        if let Smart::Custom(relative) = self.relative() {
+            write!(f, ", relative: {:?}", relative.as_str())?;
+        }
+        f.write_str(")")
+    }
+}
+
+cast_from_value! {
+    Pattern: "pattern",
+}
@@ -32,4 +32,26 @@ impl Geometry {
     pub fn stroked(self, stroke: Stroke) -> Shape {
         Shape { geometry: self, fill: None, stroke: Some(stroke) }
     }
+
+    /// The bounding box of the geometry, given by its top-left corner and its
+    /// size.
+    ///
+    /// For paths, the box also covers the control points of curves.
+    pub fn bbox(&self) -> (Point, Size) {
+        let (min, max) = match self {
+            Self::Line(target) => (target.min(Point::zero()), target.max(Point::zero())),
+            Self::Rect(size) => (Point::zero(), size.to_point()),
+            Self::Path(path) => {
+                let mut points = path.0.iter().flat_map(|item| match *item {
+                    PathItem::MoveTo(p) | PathItem::LineTo(p) => vec![p],
+                    PathItem::CubicTo(p1, p2, p3) => vec![p1, p2, p3],
+                    PathItem::ClosePath => vec![],
+                });
+                let first = points.next().unwrap_or_default();
+                points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)))
+            }
+        };
+
+        (min, (max - min).to_size())
+    }
 }
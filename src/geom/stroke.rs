@@ -300,8 +300,8 @@ cast_from_value! {
         dash_pattern: Smart::Auto,
         miter_limit: Smart::Auto,
     },
-    color: Color => Self {
-        paint: Smart::Custom(color.into()),
+    paint: Paint => Self {
+        paint: Smart::Custom(paint),
         thickness: Smart::Auto,
         line_cap: Smart::Auto,
         line_join: Smart::Auto,
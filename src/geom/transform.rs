@@ -68,6 +68,24 @@ impl Transform {
     pub fn post_concat(self, next: Self) -> Self {
         next.pre_concat(self)
     }
+
+    /// The inverse of the transformation, if it is invertible.
+    pub fn invert(self) -> Option<Self> {
+        let Self { sx, ky, kx, sy, tx, ty } = self;
+        let det = sx.get() * sy.get() - kx.get() * ky.get();
+        if det.abs() < 1e-12 {
+            return None;
+        }
+
+        Some(Self {
+            sx: Ratio::new(sy.get() / det),
+            ky: Ratio::new(-ky.get() / det),
+            kx: Ratio::new(-kx.get() / det),
+            sy: Ratio::new(sx.get() / det),
+            tx: (ty * kx.get() - tx * sy.get()) / det,
+            ty: (tx * ky.get() - ty * sx.get()) / det,
+        })
+    }
 }
 
 impl Default for Transform {
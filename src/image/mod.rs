@@ -1,10 +1,15 @@
 //! Image handling.
 
+pub mod pdf;
+
+pub use self::pdf::PdfPage;
+
 use std::io;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 use crate::diag::{format_xml_like_error, StrResult};
-use crate::util::Buffer;
+use crate::util::{Buffer, NonZeroExt};
 
 /// A raster or vector image.
 ///
@@ -15,6 +20,8 @@ pub struct Image {
     data: Buffer,
     /// The format of the encoded `buffer`.
     format: ImageFormat,
+    /// The page of a multi-page document, starting at one.
+    page: NonZeroUsize,
     /// The width in pixels.
     width: u32,
     /// The height in pixels.
@@ -26,8 +33,19 @@ impl Image {
     ///
     /// Extracts the width and height.
     pub fn new(data: Buffer, format: ImageFormat) -> StrResult<Self> {
-        let (width, height) = determine_size(&data, format)?;
-        Ok(Self { data, format, width, height })
+        Self::with_page(data, format, NonZeroUsize::ONE)
+    }
+
+    /// Create an image from a page of a multi-page document, like a PDF file.
+    ///
+    /// Extracts the width and height.
+    pub fn with_page(
+        data: Buffer,
+        format: ImageFormat,
+        page: NonZeroUsize,
+    ) -> StrResult<Self> {
+        let (width, height) = determine_size(&data, format, page)?;
+        Ok(Self { data, format, page, width, height })
     }
 
     /// The raw image data.
@@ -40,6 +58,11 @@ impl Image {
         self.format
     }
 
+    /// The page of the document that the image shows, starting at one.
+    pub fn page(&self) -> NonZeroUsize {
+        self.page
+    }
+
     /// The width of the image in pixels.
     pub fn width(&self) -> u32 {
         self.width
@@ -60,6 +83,9 @@ impl Image {
                     .map_err(format_usvg_error)?;
                 DecodedImage::Svg(tree)
             }
+            ImageFormat::Vector(VectorFormat::Pdf) => {
+                DecodedImage::Pdf(Box::new(PdfPage::parse(&self.data, self.page)?))
+            }
             ImageFormat::Raster(format) => {
                 let cursor = io::Cursor::new(&self.data);
                 let reader = image::io::Reader::with_format(cursor, format.into());
@@ -95,6 +121,8 @@ pub enum RasterFormat {
 pub enum VectorFormat {
     /// The vector graphics format of the web.
     Svg,
+    /// The vector graphics format for print. Images show a single page.
+    Pdf,
 }
 
 impl From<RasterFormat> for image::ImageFormat {
@@ -127,11 +155,17 @@ pub enum DecodedImage {
     Raster(image::DynamicImage, RasterFormat),
     /// An decoded SVG tree.
     Svg(usvg::Tree),
+    /// A page of a PDF file.
+    Pdf(Box<PdfPage>),
 }
 
 /// Determine the image size in pixels.
 #[comemo::memoize]
-fn determine_size(data: &Buffer, format: ImageFormat) -> StrResult<(u32, u32)> {
+fn determine_size(
+    data: &Buffer,
+    format: ImageFormat,
+    page: NonZeroUsize,
+) -> StrResult<(u32, u32)> {
     match format {
         ImageFormat::Raster(format) => {
             let cursor = io::Cursor::new(&data);
@@ -148,6 +182,10 @@ fn determine_size(data: &Buffer, format: ImageFormat) -> StrResult<(u32, u32)> {
             let height = size.height().ceil() as u32;
             Ok((width, height))
         }
+        ImageFormat::Vector(VectorFormat::Pdf) => {
+            let (width, height) = PdfPage::parse(data, page)?.size();
+            Ok((width.ceil() as u32, height.ceil() as u32))
+        }
     }
 }
 
@@ -0,0 +1,1086 @@
+//! Reading pages of existing PDF files.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::rc::Rc;
+
+use ecow::eco_format;
+
+use crate::diag::StrResult;
+use crate::util::Buffer;
+
+/// A page of an existing PDF file.
+pub struct PdfPage {
+    /// The file the page belongs to.
+    file: PdfFile,
+    /// The visible area of the page in its default coordinate system, as
+    /// left, bottom, right and top.
+    bbox: [f64; 4],
+    /// How far the page is rotated clockwise when displayed, in degrees.
+    rotate: i64,
+    /// The page's decoded content stream.
+    content: Vec<u8>,
+    /// The page's resource dictionary.
+    resources: Object,
+}
+
+impl PdfPage {
+    /// Parse the page with the given number, starting at one, from a file.
+    pub fn parse(data: &Buffer, number: NonZeroUsize) -> StrResult<Self> {
+        let file = PdfFile::new(data.clone())?;
+        let root = file.trailer.get(b"Root").map(|root| file.deref(root));
+        let Some(Object::Dict(catalog)) = root else { return Err(MALFORMED.into()) };
+        let Some(pages) = catalog.get(b"Pages") else { return Err(MALFORMED.into()) };
+
+        let mut remaining = number.get() - 1;
+        let mut visited = HashSet::new();
+        let Some((page, inherited)) =
+            file.find_page(pages, Inherited::default(), &mut remaining, &mut visited, 0)?
+        else {
+            return Err(eco_format!("pdf file has no page {number}"));
+        };
+
+        let media_box = inherited.media_box.and_then(|b| file.rect(&b));
+        let crop_box = inherited.crop_box.and_then(|b| file.rect(&b));
+        let Some(bbox) = crop_box.or(media_box) else { return Err(MALFORMED.into()) };
+        if bbox[2] - bbox[0] <= 0.0 || bbox[3] - bbox[1] <= 0.0 {
+            return Err(MALFORMED.into());
+        }
+
+        let rotate = match inherited.rotate.map(|r| file.deref(&r)) {
+            Some(Object::Int(rotate)) => rotate.rem_euclid(360) / 90 * 90,
+            _ => 0,
+        };
+
+        let mut content = vec![];
+        let streams = match page.get(b"Contents").map(|c| file.deref(c)) {
+            Some(Object::Array(items)) => items,
+            Some(other) => vec![other],
+            None => vec![],
+        };
+
+        for stream in streams {
+            if let Object::Stream(dict, data) = file.deref(&stream) {
+                content.extend(file.decode(&dict, &data)?);
+                content.push(b'\n');
+            }
+        }
+
+        let resources = inherited.resources.unwrap_or(Object::Dict(Dict::default()));
+        Ok(Self { file, bbox, rotate, content, resources })
+    }
+
+    /// The size of the page as it is displayed, in points.
+    pub fn size(&self) -> (f64, f64) {
+        let [left, bottom, right, top] = self.bbox;
+        let (w, h) = (right - left, top - bottom);
+        if self.rotate % 180 == 0 {
+            (w, h)
+        } else {
+            (h, w)
+        }
+    }
+
+    /// The visible area of the page in its default coordinate system, as
+    /// left, bottom, right and top.
+    pub fn bbox(&self) -> [f64; 4] {
+        self.bbox
+    }
+
+    /// The transformation matrix that maps the visible area of the page onto
+    /// the unit square, taking the page's rotation into account.
+    pub fn matrix(&self) -> [f64; 6] {
+        let [left, bottom, right, top] = self.bbox;
+        let (w, h) = (right - left, top - bottom);
+        let [a, b, c, d, e, f] = match self.rotate {
+            90 => [0.0, -1.0 / w, 1.0 / h, 0.0, 0.0, 1.0],
+            180 => [-1.0 / w, 0.0, 0.0, -1.0 / h, 1.0, 1.0],
+            270 => [0.0, 1.0 / w, -1.0 / h, 0.0, 1.0, 0.0],
+            _ => [1.0 / w, 0.0, 0.0, 1.0 / h, 0.0, 0.0],
+        };
+
+        // Move the visible area to the origin first.
+        [a, b, c, d, e - a * left - c * bottom, f - b * left - d * bottom]
+    }
+
+    /// The page's decoded content stream.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+
+    /// The page's resource dictionary.
+    pub fn resources(&self) -> &Object {
+        &self.resources
+    }
+
+    /// Look up an indirect object of the file the page belongs to.
+    pub fn resolve(&self, id: ObjectId) -> Option<Object> {
+        self.file.resolve(id)
+    }
+}
+
+/// The number and generation of an indirect object.
+pub type ObjectId = (u32, u16);
+
+/// An object in a PDF file.
+#[derive(Debug, Clone)]
+pub enum Object {
+    /// The null object.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Int(i64),
+    /// A real number.
+    Real(f64),
+    /// A string of bytes.
+    Str(Vec<u8>),
+    /// A name, without the leading slash.
+    Name(Vec<u8>),
+    /// An array of objects.
+    Array(Vec<Object>),
+    /// A dictionary.
+    Dict(Dict),
+    /// A stream with its dictionary and still encoded data.
+    Stream(Dict, Vec<u8>),
+    /// A reference to an indirect object.
+    Ref(ObjectId),
+}
+
+/// A dictionary in a PDF file.
+#[derive(Debug, Default, Clone)]
+pub struct Dict(Vec<(Vec<u8>, Object)>);
+
+impl Dict {
+    /// Get the value for a key.
+    pub fn get(&self, key: &[u8]) -> Option<&Object> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Iterate over the keys and values.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &Object)> {
+        self.0.iter().map(|(k, v)| (k.as_slice(), v))
+    }
+}
+
+/// The error message for files that can't be read.
+const MALFORMED: &str = "failed to parse pdf file";
+
+/// The maximum depth of the page tree, of nested objects and of indirect
+/// objects that depend on each other, which protects against cycles.
+const MAX_DEPTH: usize = 64;
+
+/// A parsed PDF file whose objects are read on demand.
+struct PdfFile {
+    /// The raw file data.
+    data: Buffer,
+    /// Where each indirect object is located.
+    xref: HashMap<u32, Entry>,
+    /// The trailer of the most recent cross-reference section.
+    trailer: Dict,
+    /// The already unpacked object streams.
+    object_streams: RefCell<HashMap<u32, Rc<Vec<Object>>>>,
+    /// The indirect objects that are currently being resolved, for instance
+    /// because a stream's length is stored in an indirect object.
+    resolving: RefCell<HashSet<u32>>,
+}
+
+/// The location of an indirect object.
+#[derive(Debug, Copy, Clone)]
+enum Entry {
+    /// The object was deleted.
+    Free,
+    /// The object starts at a byte offset in the file.
+    Offset(usize),
+    /// The object is the n-th object in an object stream.
+    Compressed(u32, usize),
+}
+
+/// Page attributes that are inherited from the page tree.
+#[derive(Default, Clone)]
+struct Inherited {
+    resources: Option<Object>,
+    media_box: Option<Object>,
+    crop_box: Option<Object>,
+    rotate: Option<Object>,
+}
+
+impl PdfFile {
+    /// Read the cross-reference sections of a file.
+    fn new(data: Buffer) -> StrResult<Self> {
+        let mut file = Self {
+            data,
+            xref: HashMap::new(),
+            trailer: Dict::default(),
+            object_streams: RefCell::new(HashMap::new()),
+            resolving: RefCell::new(HashSet::new()),
+        };
+
+        let tail = file.data.len().saturating_sub(1024);
+        let Some(start) = find_last(&file.data[tail..], b"startxref") else {
+            return Err(MALFORMED.into());
+        };
+
+        let mut parser = Parser::new(&file.data, tail + start + 9);
+        let mut next = Some(parser.uint().ok_or(MALFORMED)?);
+        let mut visited = HashSet::new();
+        let mut first = true;
+
+        // Newer sections come first, so existing entries are never replaced.
+        while let Some(offset) = next.filter(|&offset| visited.insert(offset)) {
+            let trailer = file.read_section(offset)?;
+            if let Some(Object::Int(stream)) = trailer.get(b"XRefStm") {
+                file.read_section(usize::try_from(*stream).map_err(|_| MALFORMED)?)?;
+            }
+
+            next = match trailer.get(b"Prev") {
+                Some(Object::Int(prev)) => {
+                    Some(usize::try_from(*prev).map_err(|_| MALFORMED)?)
+                }
+                _ => None,
+            };
+
+            if first {
+                file.trailer = trailer;
+                first = false;
+            }
+        }
+
+        if file.trailer.get(b"Encrypt").is_some() {
+            return Err("encrypted pdf files are not supported".into());
+        }
+
+        Ok(file)
+    }
+
+    /// Read a cross-reference table or stream and return its trailer.
+    fn read_section(&mut self, offset: usize) -> StrResult<Dict> {
+        let data = self.data.clone();
+        let mut parser = Parser::new(&data, offset);
+        parser.skip_whitespace();
+
+        if !parser.eat(b"xref") {
+            let Some(Object::Stream(dict, raw)) = self.read_object(offset) else {
+                return Err(MALFORMED.into());
+            };
+            self.read_xref_stream(&dict, &raw)?;
+            return Ok(dict);
+        }
+
+        loop {
+            parser.skip_whitespace();
+            if parser.eat(b"trailer") {
+                break;
+            }
+
+            let range = subsection(parser.uint(), parser.uint()).ok_or(MALFORMED)?;
+            for number in range {
+                let offset = parser.uint().ok_or(MALFORMED)?;
+                parser.uint().ok_or(MALFORMED)?;
+                parser.skip_whitespace();
+                let entry = match parser.keyword() {
+                    b"n" => Entry::Offset(offset),
+                    b"f" => Entry::Free,
+                    _ => return Err(MALFORMED.into()),
+                };
+                self.xref.entry(number).or_insert(entry);
+            }
+        }
+
+        match parser.object() {
+            Some(Object::Dict(trailer)) => Ok(trailer),
+            _ => Err(MALFORMED.into()),
+        }
+    }
+
+    /// Read the entries of a cross-reference stream.
+    fn read_xref_stream(&mut self, dict: &Dict, raw: &[u8]) -> StrResult<()> {
+        let data = self.decode(dict, raw)?;
+        let widths: Vec<usize> = match dict.get(b"W") {
+            Some(Object::Array(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Object::Int(width) => usize::try_from(*width).ok(),
+                    _ => None,
+                })
+                .collect::<Option<_>>()
+                .ok_or(MALFORMED)?,
+            _ => return Err(MALFORMED.into()),
+        };
+
+        // A field wider than eight bytes doesn't fit into an offset.
+        let [w1, w2, w3] = widths[..] else { return Err(MALFORMED.into()) };
+        if w1 > 8 || w2 > 8 || w3 > 8 || w1 + w2 + w3 == 0 {
+            return Err(MALFORMED.into());
+        }
+
+        let size = match dict.get(b"Size") {
+            Some(Object::Int(size)) => *size,
+            _ => return Err(MALFORMED.into()),
+        };
+
+        let index = match dict.get(b"Index") {
+            Some(Object::Array(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Object::Int(v) => Some(*v),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or(MALFORMED)?,
+            _ => vec![0, size],
+        };
+
+        let field = |bytes: &[u8]| {
+            bytes.iter().fold(0usize, |acc, &byte| (acc << 8) | byte as usize)
+        };
+
+        let mut rows = data.chunks_exact(w1 + w2 + w3);
+        for pair in index.chunks_exact(2) {
+            let to_usize = |v: i64| usize::try_from(v).ok();
+            let range =
+                subsection(to_usize(pair[0]), to_usize(pair[1])).ok_or(MALFORMED)?;
+            for number in range {
+                let Some(row) = rows.next() else { return Ok(()) };
+                let kind = if w1 == 0 { 1 } else { field(&row[..w1]) };
+                let second = field(&row[w1..w1 + w2]);
+                let third = field(&row[w1 + w2..]);
+                let entry = match kind {
+                    0 => Entry::Free,
+                    1 => Entry::Offset(second),
+                    2 => match u32::try_from(second) {
+                        Ok(stream) => Entry::Compressed(stream, third),
+                        Err(_) => continue,
+                    },
+                    _ => continue,
+                };
+                self.xref.entry(number).or_insert(entry);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Look up an indirect object.
+    ///
+    /// Returns `None` for an object that is already being resolved, so that
+    /// objects which depend on themselves don't recurse forever.
+    fn resolve(&self, (number, _): ObjectId) -> Option<Object> {
+        let mut resolving = self.resolving.borrow_mut();
+        if resolving.len() >= MAX_DEPTH || !resolving.insert(number) {
+            return None;
+        }
+
+        drop(resolving);
+        let object = self.read_entry(number);
+        self.resolving.borrow_mut().remove(&number);
+        object
+    }
+
+    /// Read an indirect object from where its entry points to.
+    fn read_entry(&self, number: u32) -> Option<Object> {
+        match *self.xref.get(&number)? {
+            Entry::Free => None,
+            Entry::Offset(offset) => self.read_object(offset),
+            Entry::Compressed(stream, index) => {
+                let objects = self.object_stream(stream)?;
+                objects.get(index).cloned()
+            }
+        }
+    }
+
+    /// Follow a reference, if the object is one.
+    fn deref(&self, object: &Object) -> Object {
+        match object {
+            Object::Ref(id) => self.resolve(*id).unwrap_or(Object::Null),
+            other => other.clone(),
+        }
+    }
+
+    /// Read an indirect object at a byte offset.
+    fn read_object(&self, offset: usize) -> Option<Object> {
+        let mut parser = Parser::new(&self.data, offset);
+        parser.uint()?;
+        parser.uint()?;
+        parser.skip_whitespace();
+        if !parser.eat(b"obj") {
+            return None;
+        }
+
+        let object = parser.object()?;
+        parser.skip_whitespace();
+        let Object::Dict(dict) = object else { return Some(object) };
+        if !parser.eat(b"stream") {
+            return Some(Object::Dict(dict));
+        }
+
+        // The data starts after the end of the line.
+        if !parser.eat(b"\r\n") && !parser.eat(b"\n") {
+            parser.eat(b"\r");
+        }
+
+        let start = parser.pos;
+        let rest = &self.data[start..];
+        let end = match dict.get(b"Length").map(|length| self.deref(length)) {
+            Some(Object::Int(length))
+                if length >= 0
+                    && (length as usize) <= rest.len()
+                    && Parser::new(rest, length as usize).at_keyword(b"endstream") =>
+            {
+                length as usize
+            }
+            // Tolerate a wrong length by searching for the end instead.
+            _ => {
+                let end = find_first(rest, b"endstream")?;
+                let data = &rest[..end];
+                let data = data.strip_suffix(b"\n").unwrap_or(data);
+                let data = data.strip_suffix(b"\r").unwrap_or(data);
+                data.len()
+            }
+        };
+
+        Some(Object::Stream(dict, rest[..end].to_vec()))
+    }
+
+    /// Unpack the objects of an object stream.
+    fn object_stream(&self, number: u32) -> Option<Rc<Vec<Object>>> {
+        if let Some(objects) = self.object_streams.borrow().get(&number) {
+            return Some(objects.clone());
+        }
+
+        let Entry::Offset(offset) = *self.xref.get(&number)? else { return None };
+        let Object::Stream(dict, raw) = self.read_object(offset)? else { return None };
+        let data = self.decode(&dict, &raw).ok()?;
+        let (Some(Object::Int(count)), Some(Object::Int(first))) =
+            (dict.get(b"N"), dict.get(b"First"))
+        else {
+            return None;
+        };
+
+        let mut header = Parser::new(&data, 0);
+        let mut objects = vec![];
+        for _ in 0..*count {
+            header.uint()?;
+            let offset = header.uint()?;
+            let start = usize::try_from(*first).ok()?.checked_add(offset)?;
+            let mut parser = Parser::new(&data, start);
+            objects.push(parser.object().unwrap_or(Object::Null));
+        }
+
+        let objects = Rc::new(objects);
+        self.object_streams.borrow_mut().insert(number, objects.clone());
+        Some(objects)
+    }
+
+    /// Decode the data of a stream.
+    fn decode(&self, dict: &Dict, raw: &[u8]) -> StrResult<Vec<u8>> {
+        let as_list = |object: Option<&Object>| match object.map(|o| self.deref(o)) {
+            Some(Object::Array(items)) => {
+                items.iter().map(|item| self.deref(item)).collect()
+            }
+            Some(Object::Null) | None => vec![],
+            Some(other) => vec![other],
+        };
+
+        let filters = as_list(dict.get(b"Filter"));
+        let params = as_list(dict.get(b"DecodeParms"));
+
+        let mut data = raw.to_vec();
+        for (i, filter) in filters.iter().enumerate() {
+            let Object::Name(name) = filter else { return Err(MALFORMED.into()) };
+            if name != b"FlateDecode" && name != b"Fl" {
+                return Err(eco_format!(
+                    "pdf files with {} compression are not supported",
+                    String::from_utf8_lossy(name),
+                ));
+            }
+
+            data = miniz_oxide::inflate::decompress_to_vec_zlib(&data)
+                .or_else(|_| miniz_oxide::inflate::decompress_to_vec(&data))
+                .map_err(|_| MALFORMED)?;
+
+            if let Some(Object::Dict(params)) = params.get(i) {
+                data = self.unpredict(params, data)?;
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Undo the PNG prediction of decompressed data.
+    fn unpredict(&self, params: &Dict, data: Vec<u8>) -> StrResult<Vec<u8>> {
+        let param = |key: &[u8], default: usize| match params.get(key) {
+            Some(Object::Int(v)) => usize::try_from(*v).map_err(|_| MALFORMED),
+            _ => Ok(default),
+        };
+
+        let predictor = param(b"Predictor", 1)?;
+        if predictor < 10 || data.is_empty() {
+            return Ok(data);
+        }
+
+        // Each row holds at least one byte, and no more than the whole data.
+        let bits = param(b"Colors", 1)?
+            .checked_mul(param(b"BitsPerComponent", 8)?)
+            .ok_or(MALFORMED)?;
+        let bpp = ((bits + 7) / 8).max(1);
+        let stride = bits
+            .checked_mul(param(b"Columns", 1)?)
+            .and_then(|bits| bits.checked_add(7))
+            .map(|bits| bits / 8)
+            .filter(|&stride| stride > 0 && stride < data.len())
+            .ok_or(MALFORMED)?;
+
+        let mut output = Vec::with_capacity(data.len());
+        let mut prev = vec![0; stride];
+        for row in data.chunks(stride + 1) {
+            let (&kind, row) = row.split_first().ok_or(MALFORMED)?;
+            let mut current = row.to_vec();
+            current.resize(stride, 0);
+            for i in 0..stride {
+                let left = if i >= bpp { current[i - bpp] } else { 0 };
+                let up = prev[i];
+                let up_left = if i >= bpp { prev[i - bpp] } else { 0 };
+                let predicted = match kind {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    4 => paeth(left, up, up_left),
+                    _ => return Err(MALFORMED.into()),
+                };
+                current[i] = current[i].wrapping_add(predicted);
+            }
+            output.extend_from_slice(&current);
+            prev = current;
+        }
+
+        Ok(output)
+    }
+
+    /// Find the page with the remaining index in a node of the page tree.
+    ///
+    /// The `visited` set holds the object numbers of all nodes seen so far, so
+    /// that nodes which appear more than once are rejected instead of being
+    /// walked over and over.
+    fn find_page(
+        &self,
+        node: &Object,
+        mut inherited: Inherited,
+        remaining: &mut usize,
+        visited: &mut HashSet<u32>,
+        depth: usize,
+    ) -> StrResult<Option<(Dict, Inherited)>> {
+        if let Object::Ref((number, _)) = node {
+            if !visited.insert(*number) {
+                return Err(MALFORMED.into());
+            }
+        }
+
+        let Object::Dict(dict) = self.deref(node) else { return Err(MALFORMED.into()) };
+        if depth > MAX_DEPTH {
+            return Err(MALFORMED.into());
+        }
+
+        let inherit = |key: &[u8], slot: &mut Option<Object>| {
+            if let Some(value) = dict.get(key) {
+                *slot = Some(value.clone());
+            }
+        };
+
+        inherit(b"Resources", &mut inherited.resources);
+        inherit(b"MediaBox", &mut inherited.media_box);
+        inherit(b"CropBox", &mut inherited.crop_box);
+        inherit(b"Rotate", &mut inherited.rotate);
+
+        let kids = match dict.get(b"Kids").map(|kids| self.deref(kids)) {
+            Some(Object::Array(kids)) => kids,
+            _ if *remaining == 0 => return Ok(Some((dict, inherited))),
+            _ => {
+                *remaining -= 1;
+                return Ok(None);
+            }
+        };
+
+        // Skip whole subtrees if possible.
+        if let Some(Object::Int(count)) = dict.get(b"Count") {
+            if *count >= 0 && (*count as usize) <= *remaining {
+                *remaining -= *count as usize;
+                return Ok(None);
+            }
+        }
+
+        for kid in &kids {
+            if let Some(found) =
+                self.find_page(kid, inherited.clone(), remaining, visited, depth + 1)?
+            {
+                return Ok(Some(found));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Read a rectangle and normalize it to left, bottom, right and top.
+    fn rect(&self, object: &Object) -> Option<[f64; 4]> {
+        let Object::Array(items) = self.deref(object) else { return None };
+        let numbers: Vec<f64> = items
+            .iter()
+            .map(|item| match self.deref(item) {
+                Object::Int(v) => Some(v as f64),
+                Object::Real(v) => Some(v),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+
+        let [x1, y1, x2, y2] = numbers[..] else { return None };
+        Some([x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)])
+    }
+}
+
+/// The numbers of the objects in a subsection of a cross-reference table or
+/// stream with the given first number and count.
+fn subsection(start: Option<usize>, count: Option<usize>) -> Option<Range<u32>> {
+    let start = u32::try_from(start?).ok()?;
+    let end = start.checked_add(u32::try_from(count?).ok()?)?;
+    Some(start..end)
+}
+
+/// The Paeth predictor from the PNG specification.
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let p = left as i16 + up as i16 - up_left as i16;
+    let pa = (p - left as i16).abs();
+    let pb = (p - up as i16).abs();
+    let pc = (p - up_left as i16).abs();
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Find the first occurrence of a needle.
+fn find_first(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Find the last occurrence of a needle.
+fn find_last(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|window| window == needle)
+}
+
+/// Parses PDF objects.
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// How deeply the object that is being parsed is nested.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Create a parser that starts at a byte offset.
+    ///
+    /// An offset past the end of the data is moved to the end, so that
+    /// parsing fails instead of reading out of bounds.
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos: pos.min(data.len()), depth: 0 }
+    }
+
+    /// The byte at the current position.
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    /// Skip whitespace and comments.
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if is_whitespace(c) {
+                self.pos += 1;
+            } else if c == b'%' {
+                while !matches!(self.peek(), None | Some(b'\n' | b'\r')) {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Consume the given bytes if they come next.
+    fn eat(&mut self, bytes: &[u8]) -> bool {
+        let matches = self.data[self.pos..].starts_with(bytes);
+        if matches {
+            self.pos += bytes.len();
+        }
+        matches
+    }
+
+    /// Whether a keyword comes next, after whitespace.
+    fn at_keyword(mut self, keyword: &[u8]) -> bool {
+        self.skip_whitespace();
+        self.keyword() == keyword
+    }
+
+    /// Consume a run of regular characters.
+    fn keyword(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().map_or(false, is_regular) {
+            self.pos += 1;
+        }
+        self.data.get(start..self.pos).unwrap_or_default()
+    }
+
+    /// Parse an unsigned integer after whitespace.
+    fn uint(&mut self) -> Option<usize> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.peek().map_or(false, |c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(self.data.get(start..self.pos)?)
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// Parse an object after whitespace.
+    fn object(&mut self) -> Option<Object> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+
+        self.depth += 1;
+        let object = self.nested_object();
+        self.depth -= 1;
+        object
+    }
+
+    /// Parse an object after whitespace, one level deeper.
+    fn nested_object(&mut self) -> Option<Object> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'/' => {
+                self.pos += 1;
+                Some(Object::Name(self.name()))
+            }
+            b'(' => {
+                self.pos += 1;
+                Some(Object::Str(self.literal_string()))
+            }
+            b'<' if self.eat(b"<<") => self.dict().map(Object::Dict),
+            b'<' => {
+                self.pos += 1;
+                Some(Object::Str(self.hex_string()))
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = vec![];
+                loop {
+                    self.skip_whitespace();
+                    if self.eat(b"]") {
+                        break Some(Object::Array(items));
+                    }
+                    items.push(self.object()?);
+                }
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => self.number(),
+            _ => match self.keyword() {
+                b"true" => Some(Object::Bool(true)),
+                b"false" => Some(Object::Bool(false)),
+                b"null" => Some(Object::Null),
+                _ => None,
+            },
+        }
+    }
+
+    /// Parse a number or a reference.
+    fn number(&mut self) -> Option<Object> {
+        let start = self.pos;
+        while self
+            .peek()
+            .map_or(false, |c| matches!(c, b'+' | b'-' | b'.' | b'0'..=b'9'))
+        {
+            self.pos += 1;
+        }
+
+        let text = std::str::from_utf8(&self.data[start..self.pos]).ok()?;
+        if text.contains('.') {
+            return text.parse().ok().map(Object::Real);
+        }
+
+        let int: i64 = text.parse().ok()?;
+        let end = self.pos;
+        if let (Ok(number), true) =
+            (u32::try_from(int), text.bytes().all(|c| c.is_ascii_digit()))
+        {
+            if let Some(generation) = self.uint().and_then(|g| u16::try_from(g).ok()) {
+                self.skip_whitespace();
+                if self.keyword() == b"R" {
+                    return Some(Object::Ref((number, generation)));
+                }
+            }
+        }
+
+        self.pos = end;
+        Some(Object::Int(int))
+    }
+
+    /// Parse a name after the slash.
+    fn name(&mut self) -> Vec<u8> {
+        let name = self.keyword();
+
+        // Resolve escaped bytes like `#20`.
+        let mut decoded = Vec::with_capacity(name.len());
+        let mut i = 0;
+        while i < name.len() {
+            let hex = name.get(i + 1..i + 3).and_then(|hex| {
+                u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+            });
+            match (name[i], hex) {
+                (b'#', Some(byte)) => {
+                    decoded.push(byte);
+                    i += 3;
+                }
+                (c, _) => {
+                    decoded.push(c);
+                    i += 1;
+                }
+            }
+        }
+
+        decoded
+    }
+
+    /// Parse a literal string after the opening parenthesis.
+    fn literal_string(&mut self) -> Vec<u8> {
+        let mut string = vec![];
+        let mut depth = 1;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else { break };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => string.push(b'\n'),
+                        b'r' => string.push(b'\r'),
+                        b't' => string.push(b'\t'),
+                        b'b' => string.push(0x08),
+                        b'f' => string.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            string.push(value as u8);
+                        }
+                        // A backslash at the end of a line continues the
+                        // string on the next one.
+                        b'\r' => {
+                            self.eat(b"\n");
+                        }
+                        b'\n' => {}
+                        other => string.push(other),
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            string.push(c);
+        }
+        string
+    }
+
+    /// Parse a hexadecimal string after the opening angle bracket.
+    fn hex_string(&mut self) -> Vec<u8> {
+        let mut digits = vec![];
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'>' => break,
+                b'0'..=b'9' => digits.push(c - b'0'),
+                b'a'..=b'f' => digits.push(c - b'a' + 10),
+                b'A'..=b'F' => digits.push(c - b'A' + 10),
+                _ => {}
+            }
+        }
+
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+
+        digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect()
+    }
+
+    /// Parse a dictionary after the opening angle brackets.
+    fn dict(&mut self) -> Option<Dict> {
+        let mut pairs = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.eat(b">>") {
+                break Some(Dict(pairs));
+            }
+
+            if !self.eat(b"/") {
+                return None;
+            }
+
+            let key = self.name();
+            let value = self.object()?;
+            pairs.push((key, value));
+        }
+    }
+}
+
+/// Whether a byte is whitespace in PDF.
+fn is_whitespace(c: u8) -> bool {
+    matches!(c, b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+/// Whether a byte is neither whitespace nor a delimiter in PDF.
+fn is_regular(c: u8) -> bool {
+    !is_whitespace(c)
+        && !matches!(
+            c,
+            b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Assemble a file from its objects, numbered from one, and the
+    /// cross-reference table that points to them.
+    fn file(objects: &[&str]) -> Vec<u8> {
+        let mut data = b"%PDF-1.7\n".to_vec();
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(data.len());
+            data.extend(format!("{} 0 obj\n{object}\nendobj\n", i + 1).bytes());
+        }
+
+        let xref = data.len();
+        data.extend(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes(),
+        );
+        for offset in offsets {
+            data.extend(format!("{offset:010} 00000 n \n").bytes());
+        }
+
+        data.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1,
+            )
+            .bytes(),
+        );
+        data
+    }
+
+    /// A file with a single page whose content stream has the given length.
+    fn page(length: &str) -> Vec<u8> {
+        file(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 200 100] /Contents 4 0 R >>",
+            &format!("<< /Length {length} >>\nstream\n0 0 m 10 10 l S\nendstream"),
+        ])
+    }
+
+    fn parse(data: &[u8]) -> StrResult<PdfPage> {
+        PdfPage::parse(&Buffer::from(data), NonZeroUsize::new(1).unwrap())
+    }
+
+    #[test]
+    fn test_pdf_parse_page() {
+        let parsed = parse(&page("15")).unwrap();
+        assert_eq!(parsed.size(), (200.0, 100.0));
+        assert_eq!(parsed.content(), b"0 0 m 10 10 l S\n");
+        assert_eq!(
+            PdfPage::parse(&Buffer::from(page("15")), NonZeroUsize::new(2).unwrap())
+                .err()
+                .as_deref(),
+            Some("pdf file has no page 2"),
+        );
+    }
+
+    #[test]
+    fn test_pdf_parse_truncated() {
+        let data = page("15");
+        for len in 0..data.len() {
+            // Some prefixes still contain a complete file, but none of them
+            // must make the parser panic.
+            let _ = parse(&data[..len]);
+        }
+    }
+
+    #[test]
+    fn test_pdf_parse_cyclic() {
+        // A stream whose length is stored in the stream itself.
+        let page = parse(&page("4 0 R")).unwrap();
+        assert_eq!(page.content(), b"0 0 m 10 10 l S\n");
+
+        // A page tree that contains itself.
+        let data = file(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [2 0 R] >>",
+        ]);
+        assert!(parse(&data).is_err());
+
+        // A page tree that contains itself more than once, which would take
+        // exponentially long to walk if nodes were visited again.
+        let data = file(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [2 0 R 2 0 R] >>",
+        ]);
+        assert!(parse(&data).is_err());
+
+        // Objects that are nested too deeply.
+        let nested = format!("<< /Pages {}{} >>", "[".repeat(1000), "]".repeat(1000));
+        assert!(parse(&file(&[&nested])).is_err());
+    }
+
+    #[test]
+    fn test_pdf_parse_out_of_range() {
+        // A cross-reference section past the end of the file.
+        let mut data = page("15");
+        let start = find_last(&data, b"startxref").unwrap();
+        data.truncate(start);
+        data.extend(b"startxref\n999999999\n%%EOF\n");
+        assert!(parse(&data).is_err());
+
+        // A subsection whose numbers overflow.
+        let data = page("15");
+        let start = find_first(&data, b"xref\n").unwrap();
+        let mut patched = data[..start].to_vec();
+        patched.extend(b"xref\n4294967295 5\n");
+        patched.extend(&data[start + 5..]);
+        assert!(parse(&patched).is_err());
+    }
+
+    #[test]
+    fn test_pdf_subsection() {
+        assert_eq!(subsection(Some(3), Some(2)), Some(3..5));
+        assert_eq!(subsection(Some(u32::MAX as usize), Some(1)), None);
+        assert_eq!(subsection(Some(usize::MAX), Some(0)), None);
+        assert_eq!(subsection(None, Some(1)), None);
+    }
+}
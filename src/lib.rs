@@ -17,7 +17,7 @@
 //!   per page with items at fixed positions.
 //! - **Exporting:**
 //!   These frames can finally be exported into an output format (currently
-//!   supported are [PDF] and [raster images]).
+//!   supported are [PDF], [raster images] and [SVG]).
 //!
 //! [tokens]: syntax::SyntaxKind
 //! [parsed]: syntax::parse
@@ -31,6 +31,7 @@
 //! [frame]: doc::Frame
 //! [PDF]: export::pdf
 //! [raster images]: export::render
+//! [SVG]: export::svg
 
 #![recursion_limit = "1000"]
 
@@ -58,15 +59,21 @@ use comemo::{Prehashed, Track};
 use crate::diag::{FileResult, SourceResult};
 use crate::doc::Document;
 use crate::eval::{Library, Route, Tracer};
+use crate::export::HtmlDocument;
 use crate::font::{Font, FontBook};
 use crate::syntax::{Source, SourceId};
 use crate::util::Buffer;
 
 /// Compile a source file into a fully layouted document.
-pub fn compile(world: &(dyn World + 'static)) -> SourceResult<Document> {
+///
+/// Warnings that are emitted during compilation are collected in the `tracer`
+/// and can be retrieved with [`Tracer::warnings`].
+pub fn compile(
+    world: &(dyn World + 'static),
+    tracer: &mut Tracer,
+) -> SourceResult<Document> {
     // Evaluate the source file into a module.
     let route = Route::default();
-    let mut tracer = Tracer::default();
     let module =
         eval::eval(world.track(), route.track(), tracer.track_mut(), world.main())?;
 
@@ -74,6 +81,23 @@ pub fn compile(world: &(dyn World + 'static)) -> SourceResult<Document> {
     model::typeset(world.track(), tracer.track_mut(), &module.content())
 }
 
+/// Compile a source file into a semantic HTML document.
+///
+/// The document is laid out first so that counters, references and other
+/// introspections resolve just like in the laid-out document.
+pub fn compile_html(
+    world: &(dyn World + 'static),
+    tracer: &mut Tracer,
+) -> SourceResult<HtmlDocument> {
+    let route = Route::default();
+    let module =
+        eval::eval(world.track(), route.track(), tracer.track_mut(), world.main())?;
+
+    let content = module.content();
+    let document = model::typeset(world.track(), tracer.track_mut(), &content)?;
+    model::typeset_html(world.track(), tracer.track_mut(), &content, &document)
+}
+
 /// The environment in which typesetting occurs.
 #[comemo::track]
 pub trait World {
@@ -5,10 +5,11 @@ use std::ops::{Add, AddAssign};
 
 use comemo::{Prehashed, Tracked};
 use ecow::{eco_format, EcoString, EcoVec};
+use serde::{Serialize, Serializer};
 
 use super::{
     element, Behave, Behaviour, ElemFunc, Element, Fold, Guard, Introspector, Label,
-    Locatable, Location, Recipe, Selector, Style, Styles, Synthesize,
+    Locatable, Location, PlainText, Recipe, Selector, Style, Styles, Synthesize,
 };
 use crate::diag::{SourceResult, StrResult};
 use crate::doc::Meta;
@@ -249,6 +250,27 @@ impl Content {
         self.field(field).ok_or_else(|| missing_field(field))
     }
 
+    /// Extract the plain text of the content, leaving out everything that has
+    /// no plain-text representation.
+    pub fn plain_text(&self) -> EcoString {
+        let mut text = EcoString::new();
+        self.write_plain_text(&mut text);
+        text
+    }
+
+    /// Write the plain text of the content into the given buffer.
+    fn write_plain_text(&self, text: &mut EcoString) {
+        if let Some(children) = self.to_sequence() {
+            for child in children {
+                child.write_plain_text(text);
+            }
+        } else if let Some((child, _)) = self.to_styled() {
+            child.write_plain_text(text);
+        } else if let Some(elem) = self.with::<dyn PlainText>() {
+            elem.plain_text(text);
+        }
+    }
+
     /// The content's label.
     pub fn label(&self) -> Option<&Label> {
         match self.field_ref("label")? {
@@ -457,6 +479,19 @@ impl PartialEq for Content {
     }
 }
 
+impl Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let func = Value::Str(self.func.name().into());
+        serializer.collect_map(
+            std::iter::once(("func", func))
+                .chain(self.fields().map(|(name, value)| (name.as_str(), value))),
+        )
+    }
+}
+
 impl Add for Content {
     type Output = Self;
 
@@ -17,8 +17,10 @@ pub use typst_macros::element;
 use comemo::{Constraint, Track, Tracked, TrackedMut};
 
 use crate::diag::SourceResult;
-use crate::doc::Document;
+use crate::doc::{Document, Frame, FrameItem};
 use crate::eval::Tracer;
+use crate::export::HtmlDocument;
+use crate::font::Font;
 use crate::World;
 
 /// Typeset content into a fully layouted document.
@@ -60,6 +62,69 @@ pub fn typeset(
     Ok(document)
 }
 
+/// Convert content into a semantic HTML document.
+///
+/// Introspections like counters and references are resolved against the
+/// given laid-out document, from which the metadata is taken as well.
+pub fn typeset_html(
+    world: Tracked<dyn World>,
+    mut tracer: TrackedMut<Tracer>,
+    content: &Content,
+    document: &Document,
+) -> SourceResult<HtmlDocument> {
+    let library = world.library();
+    let styles = StyleChain::new(&library.styles);
+    let introspector = Introspector::new(&document.pages);
+    let mut provider = StabilityProvider::new();
+    let mut vt = Vt {
+        world,
+        tracer: TrackedMut::reborrow_mut(&mut tracer),
+        provider: provider.track_mut(),
+        introspector: introspector.track(),
+    };
+
+    Ok(HtmlDocument {
+        title: document.title.clone(),
+        author: document.author.clone(),
+        description: document.description.clone(),
+        keywords: document.keywords.clone(),
+        date: document.date,
+        lang: document.lang,
+        region: document.region,
+        fonts: used_fonts(&document.pages),
+        body: (library.items.html)(&mut vt, content, styles)?,
+    })
+}
+
+/// The fonts used by text in the given frames, in order of first use.
+///
+/// Instances of a variable font with different axis coordinates share their
+/// data and are only listed once.
+fn used_fonts(frames: &[Frame]) -> Vec<Font> {
+    fn visit(frame: &Frame, fonts: &mut Vec<Font>) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => visit(&group.frame, fonts),
+                FrameItem::Text(text)
+                    if !fonts.iter().any(|font| {
+                        font.data() == text.font.data()
+                            && font.index() == text.font.index()
+                    }) =>
+                {
+                    fonts.push(text.font.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut fonts = vec![];
+    for frame in frames {
+        visit(frame, &mut fonts);
+    }
+    fonts
+}
+
 /// A virtual typesetter.
 ///
 /// Holds the state needed to [typeset] content.
@@ -59,21 +59,8 @@ pub fn realize(
         return Ok(Some(elem));
     }
 
-    // Find out how many recipes there are.
-    let mut n = styles.recipes().count();
-
     // Find an applicable recipe.
-    let mut realized = None;
-    for recipe in styles.recipes() {
-        let guard = Guard::Nth(n);
-        if recipe.applicable(target) && !target.is_guarded(guard) {
-            if let Some(content) = try_apply(vt, target, recipe, guard)? {
-                realized = Some(content);
-                break;
-            }
-        }
-        n -= 1;
-    }
+    let mut realized = apply_recipes(vt, target, styles)?;
 
     // Realize if there was no matching recipe.
     if let Some(showable) = target.with::<dyn Show>() {
@@ -95,6 +82,29 @@ pub fn realize(
     Ok(realized)
 }
 
+/// Apply the first matching show rule in the given style chain to a target,
+/// without falling back to the target's base recipe.
+pub fn apply_recipes(
+    vt: &mut Vt,
+    target: &Content,
+    styles: StyleChain,
+) -> SourceResult<Option<Content>> {
+    // Find out how many recipes there are.
+    let mut n = styles.recipes().count();
+
+    for recipe in styles.recipes() {
+        let guard = Guard::Nth(n);
+        if recipe.applicable(target) && !target.is_guarded(guard) {
+            if let Some(content) = try_apply(vt, target, recipe, guard)? {
+                return Ok(Some(content));
+            }
+        }
+        n -= 1;
+    }
+
+    Ok(None)
+}
+
 /// Try to apply a recipe to the target.
 fn try_apply(
     vt: &mut Vt,
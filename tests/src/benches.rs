@@ -85,13 +85,14 @@ fn bench_typeset(iai: &mut Iai) {
 
 fn bench_compile(iai: &mut Iai) {
     let world = BenchWorld::new();
-    iai.run(|| typst::compile(&world));
+    iai.run(|| typst::compile(&world, &mut typst::eval::Tracer::default()));
 }
 
 fn bench_render(iai: &mut Iai) {
     let world = BenchWorld::new();
-    let document = typst::compile(&world).unwrap();
-    iai.run(|| typst::export::render(&document.pages[0], 1.0, Color::WHITE))
+    let mut tracer = typst::eval::Tracer::default();
+    let document = typst::compile(&world, &mut tracer).unwrap();
+    iai.run(|| typst::export::render(&document.pages[0], 1.0, Color::WHITE).unwrap())
 }
 
 struct BenchWorld {
@@ -10,9 +10,9 @@ use comemo::{Prehashed, Track};
 use elsa::FrozenVec;
 use once_cell::unsync::OnceCell;
 use tiny_skia as sk;
-use typst::diag::{bail, FileError, FileResult};
+use typst::diag::{bail, FileError, FileResult, Severity};
 use typst::doc::{Document, Frame, FrameItem, Meta};
-use typst::eval::{func, Library, Value};
+use typst::eval::{dict, func, Library, Value};
 use typst::font::{Font, FontBook};
 use typst::geom::{Abs, Color, RgbaColor, Sides, Smart};
 use typst::syntax::{Source, SourceId, Span, SyntaxNode};
@@ -172,7 +172,9 @@ fn library() -> Library {
         Value::None
     }
 
-    let mut lib = typst_library::build();
+    // Provide inputs like `--input` on the command line would.
+    let inputs = dict! { "name" => "Typst", "pages" => "12" };
+    let mut lib = typst_library::build_with_inputs(inputs);
 
     // Set page width to 120pt with 10pt margins, so that the inner page is
     // exactly 100pt wide. Page height is unbounded and font size is 10pt so
@@ -438,7 +440,7 @@ fn test_part(
         println!("Syntax Tree:\n{:#?}\n", source.root())
     }
 
-    let (local_compare_ref, mut ref_errors) = parse_metadata(&source);
+    let (local_compare_ref, mut ref_errors, ref_html) = parse_metadata(&source);
     let compare_ref = local_compare_ref.unwrap_or(compare_ref);
 
     ok &= test_spans(source.root());
@@ -453,26 +455,30 @@ fn test_part(
         println!("Model:\n{:#?}\n", module.content());
     }
 
-    let (mut frames, errors) = match typst::compile(world) {
+    let mut tracer = typst::eval::Tracer::default();
+    let (mut frames, mut errors) = match typst::compile(world, &mut tracer) {
         Ok(document) => (document.pages, vec![]),
         Err(errors) => (vec![], *errors),
     };
+    errors.extend(tracer.warnings().iter().cloned());
 
     // Don't retain frames if we don't wanna compare with reference images.
     if !compare_ref {
         frames.clear();
     }
 
-    // Map errors to range and message format, discard traces and errors from
-    // other files.
+    // Map errors and warnings to severity, range and message format, discard
+    // traces and diagnostics from other files.
     let mut errors: Vec<_> = errors
         .into_iter()
         .filter(|error| error.span.source() == id)
-        .map(|error| (error.range(world), error.message.replace('\\', "/")))
+        .map(|error| {
+            (error.severity, error.range(world), error.message.replace('\\', "/"))
+        })
         .collect();
 
-    errors.sort_by_key(|error| error.0.start);
-    ref_errors.sort_by_key(|error| error.0.start);
+    errors.sort_by_key(|error| error.1.start);
+    ref_errors.sort_by_key(|error| error.1.start);
 
     if errors != ref_errors {
         println!("  Subtest {i} does not match expected errors. ❌");
@@ -494,12 +500,61 @@ fn test_part(
         }
     }
 
+    if !ref_html.is_empty() {
+        ok &= test_html(world, i, &ref_html);
+    }
+
     (ok, compare_ref, frames)
 }
 
-fn parse_metadata(source: &Source) -> (Option<bool>, Vec<(Range<usize>, String)>) {
+/// Compare the body of the part's HTML export with the expected lines.
+///
+/// Embedded frames are replaced with `[frame]` since their SVG data depends
+/// on the layout.
+fn test_html(world: &TestWorld, i: usize, ref_html: &[String]) -> bool {
+    let mut tracer = typst::eval::Tracer::default();
+    let Ok(document) = typst::compile_html(world, &mut tracer) else {
+        println!("  Subtest {i} fails to export HTML. ❌");
+        return false;
+    };
+
+    let html = typst::export::html(&document);
+    let body = html
+        .split_once("<body>\n")
+        .and_then(|(_, rest)| rest.rsplit_once("</body>"))
+        .map_or("", |(body, _)| body);
+
+    let mut lines = vec![];
+    for line in body.lines() {
+        let mut line = line.to_string();
+        while let Some(start) = line.find("<img src=\"data:image/svg+xml") {
+            let end = line[start..].find('>').map_or(line.len(), |end| start + end + 1);
+            line.replace_range(start..end, "[frame]");
+        }
+        lines.push(line);
+    }
+
+    if lines == ref_html {
+        return true;
+    }
+
+    println!("  Subtest {i} does not match expected HTML. ❌");
+    for line in ref_html {
+        println!("    Expected | {line}");
+    }
+    for line in &lines {
+        println!("    Found    | {line}");
+    }
+
+    false
+}
+
+fn parse_metadata(
+    source: &Source,
+) -> (Option<bool>, Vec<(Severity, Range<usize>, String)>, Vec<String>) {
     let mut compare_ref = None;
     let mut errors = vec![];
+    let mut html = vec![];
 
     let lines: Vec<_> = source.text().lines().map(str::trim).collect();
     for (i, line) in lines.iter().enumerate() {
@@ -511,6 +566,11 @@ fn parse_metadata(source: &Source) -> (Option<bool>, Vec<(Range<usize>, String)>
             compare_ref = Some(true);
         }
 
+        if let Some(rest) = line.strip_prefix("// Html:") {
+            html.push(rest.strip_prefix(' ').unwrap_or(rest).to_string());
+            continue;
+        }
+
         fn num(s: &mut Scanner) -> usize {
             s.eat_while(char::is_numeric).parse().unwrap()
         }
@@ -526,24 +586,39 @@ fn parse_metadata(source: &Source) -> (Option<bool>, Vec<(Range<usize>, String)>
             source.line_column_to_byte(line, column).unwrap()
         };
 
-        let Some(rest) = line.strip_prefix("// Error: ") else { continue };
+        let (severity, rest) = if let Some(rest) = line.strip_prefix("// Error: ") {
+            (Severity::Error, rest)
+        } else if let Some(rest) = line.strip_prefix("// Warning: ") {
+            (Severity::Warning, rest)
+        } else {
+            continue;
+        };
+
         let mut s = Scanner::new(rest);
         let start = pos(&mut s);
         let end = if s.eat_if('-') { pos(&mut s) } else { start };
         let range = start..end;
 
-        errors.push((range, s.after().trim().to_string()));
+        errors.push((severity, range, s.after().trim().to_string()));
     }
 
-    (compare_ref, errors)
+    (compare_ref, errors, html)
 }
 
-fn print_error(source: &Source, line: usize, (range, message): &(Range<usize>, String)) {
+fn print_error(
+    source: &Source,
+    line: usize,
+    (severity, range, message): &(Severity, Range<usize>, String),
+) {
     let start_line = 1 + line + source.byte_to_line(range.start).unwrap();
     let start_col = 1 + source.byte_to_column(range.start).unwrap();
     let end_line = 1 + line + source.byte_to_line(range.end).unwrap();
     let end_col = 1 + source.byte_to_column(range.end).unwrap();
-    println!("Error: {start_line}:{start_col}-{end_line}:{end_col}: {message}");
+    let prefix = match severity {
+        Severity::Error => "Error",
+        Severity::Warning => "Warning",
+    };
+    println!("{prefix}: {start_line}:{start_col}-{end_line}:{end_col}: {message}");
 }
 
 /// Pseudorandomly edit the source file and test whether a reparse produces the
@@ -695,7 +770,7 @@ fn render(frames: &[Frame]) -> sk::Pixmap {
             if frame.width() > limit || frame.height() > limit {
                 panic!("overlarge frame: {:?}", frame.size());
             }
-            typst::export::render(frame, pixel_per_pt, Color::WHITE)
+            typst::export::render(frame, pixel_per_pt, Color::WHITE).unwrap()
         })
         .collect();
 
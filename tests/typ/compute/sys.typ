@@ -0,0 +1,20 @@
+// Test the `sys` module.
+// Ref: false
+
+---
+// The test suite passes `name=Typst` and `pages=12` as inputs.
+#test(type(sys.inputs), "dictionary")
+#test(sys.inputs.len(), 2)
+#test(sys.inputs.keys(), ("name", "pages"))
+#test(sys.inputs.name, "Typst")
+#test(sys.inputs.at("pages"), "12")
+#test(int(sys.inputs.pages) + 1, 13)
+
+---
+// Inputs are always strings.
+#test(type(sys.inputs.pages), "string")
+#test("missing" in sys.inputs, false)
+
+---
+// Error: 13-20 dictionary does not contain key "missing"
+#sys.inputs.missing
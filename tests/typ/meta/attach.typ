@@ -0,0 +1,10 @@
+// Test attaching files to the PDF.
+// Ref: false
+
+---
+#pdf.attach("/data.csv", description: "Raw data", mime-type: "text/csv")
+#pdf.attach("/hello.txt", name: "greeting.txt")[Open the greeting]
+
+---
+// Error: 13-26 file not found (searched at typ/meta/missing.csv)
+#pdf.attach("missing.csv")
@@ -16,6 +16,31 @@ What's up?
 #set document(author: (123,))
 What's up?
 
+---
+// Ref: false
+#set document(
+  description: "A test document",
+  keywords: ("typst", "metadata"),
+  date: "2023-05-01",
+  properties: (department: "Research"),
+)
+
+---
+// Error: 21-33 date is out of range
+#set document(date: "2023-13-01")
+
+---
+// Error: 21-33 date is out of range
+#set document(date: "2023-02-29")
+
+---
+// Ref: false
+#set document(date: "2024-02-29")
+
+---
+// Error: 27-43 property name is reserved: Title
+#set document(properties: (Title: "Hello"))
+
 ---
 Hello
 
@@ -0,0 +1,27 @@
+// Test interactive form fields.
+// Ref: false
+
+---
+Name: #form.text-field("name", value: "Jane")
+
+#form.checkbox("terms", checked: true) I accept the terms.
+
+Size:
+#form.radio("size", "s") S
+#form.radio("size", "m", checked: true) M
+
+Team: #form.dropdown("team", ("Design", "People"), selected: "People")
+
+---
+// Error: 2-52 selected value must be one of the options
+#form.dropdown("team", ("Design",), selected: "HR")
+
+---
+// Error: 2-27 radio button value must not be "Off"
+#form.radio("size", "Off")
+
+---
+// Fields of different kinds can't share a name.
+// Error: 2-25 form field name "choice" is already used by a radio button
+#form.checkbox("choice")
+#form.radio("choice", "a")
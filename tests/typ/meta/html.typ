@@ -0,0 +1,86 @@
+// Test the semantic HTML export.
+// Ref: false
+
+---
+// Test headings, paragraphs and links.
+// Html: <h1 id="intro">Intro</h1>
+// Html: <p>Tom &amp; Jerry are <strong>fast</strong>, <em>very</em> fast.</p>
+// Html: <p><a href="https://typst.app">Typst</a> and <a href="#intro">the intro</a>.</p>
+= Intro <intro>
+Tom & Jerry are *fast*, _very_ fast.
+
+#link("https://typst.app")[Typst] and #link(<intro>)[the intro].
+
+---
+// Test bullet, numbered and term lists.
+// Html: <ul>
+// Html: <li>First</li>
+// Html: <li>Second</li>
+// Html: </ul>
+// Html: <ol>
+// Html: <li>One</li>
+// Html: <li value="5">Five</li>
+// Html: </ol>
+// Html: <ol start="2">
+// Html: <li>A</li>
+// Html: <li>B</li>
+// Html: </ol>
+// Html: <dl>
+// Html: <dt>Term</dt>
+// Html: <dd>Description</dd>
+// Html: </dl>
+- First
+- Second
+
++ One
+5. Five
+
+#enum(start: 2)[A][B]
+
+/ Term: Description
+
+---
+// Test tables and figures.
+// Html: <table>
+// Html: <tr>
+// Html: <td>a</td>
+// Html: <td>b</td>
+// Html: </tr>
+// Html: <tr>
+// Html: <td>c</td>
+// Html: <td>d</td>
+// Html: </tr>
+// Html: </table>
+// Html: <figure id="data">
+// Html: <table>
+// Html: <tr>
+// Html: <td>x</td>
+// Html: </tr>
+// Html: </table>
+// Html: <figcaption>Data</figcaption>
+// Html: </figure>
+#table(columns: 2, [a], [b], [c], [d])
+#figure(
+  table(columns: 1, [x]),
+  caption: [Data],
+  numbering: none,
+) <data>
+
+---
+// Test inline and block raw text.
+// Html: <p>Use <code>x &lt; y</code> here.</p>
+// Html: <pre><code class="language-rs">let x = 1;
+// Html: let y = 2;</code></pre>
+Use `x < y` here.
+
+```rs
+let x = 1;
+let y = 2;
+```
+
+---
+// Test inline and block equations, which are embedded as frames.
+// Html: <p>Inline [frame] and block:</p>
+// Html: <div class="equation">[frame]</div>
+Inline $x$ and block:
+$ x^2 $
@@ -0,0 +1,16 @@
+// Test the metadata element.
+// Ref: false
+
+---
+#metadata("Hello") <note>
+#metadata((1, 2)) <note>
+#metadata(3)
+
+#locate(loc => {
+  let notes = query(<note>, loc)
+  let all = query(metadata, loc)
+  if all.len() > 0 {
+    test(notes.map(elem => elem.value), ("Hello", (1, 2)))
+    test(all.len(), 3)
+  }
+})
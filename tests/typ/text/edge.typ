@@ -1,6 +1,7 @@
 // Test top and bottom text edge.
 
 ---
+// Warning: 5:19-5:34 unknown font family: IBM Plex Mono
 #set page(width: 160pt)
 #set text(size: 8pt)
 
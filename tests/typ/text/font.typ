@@ -32,6 +32,7 @@ Emoji: 🐪, 🌋, 🏞
 
 // Disable font fallback beyond the user-specified list.
 // Without disabling, New Computer Modern Math would come to the rescue.
+// Warning: 17-51 unknown font family: Twitter Color Emoji
 #set text(font: ("PT Sans", "Twitter Color Emoji"), fallback: false)
 2π = 𝛼 + 𝛽. ✅
 
@@ -58,3 +59,7 @@ Emoji: 🐪, 🌋, 🏞
 ---
 // Error: 11-31 unexpected argument
 #set text(something: "invalid")
+
+---
+// Error: 23-36 variation coordinates must be finite
+#set text(variations: (wght: 1e400))
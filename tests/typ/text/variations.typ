@@ -0,0 +1,17 @@
+// Test variation axes of variable fonts.
+
+---
+#set text(font: "Inter Variable")
+Regular \
+#text(variations: (wght: 900))[Black] \
+#text(variations: (wght: 200, opsz: 32))[Light and large] \
+#text(weight: "bold")[Bold from the weight] \
+#text(variations: (wdth: 50))[Unknown axes are ignored]
+
+---
+// Error: 23-36 variation axes must be four-letter tags
+#set text(variations: (weight: 500))
+
+---
+// Error: 23-33 variation axes must be four-letter tags
+#set text(variations: (wgh: 500))
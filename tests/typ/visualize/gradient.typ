@@ -0,0 +1,62 @@
+// Test gradients.
+
+---
+#set page(width: 120pt, height: auto)
+#rect(width: 100%, height: 15pt, fill: gradient.linear(red, yellow, blue))
+#stack(
+  dir: ltr,
+  spacing: 5pt,
+  square(size: 25pt, fill: gradient.linear(red, blue, angle: 45deg)),
+  circle(radius: 12.5pt, fill: gradient.radial(white, aqua, navy)),
+  rect(
+    width: 40pt,
+    height: 25pt,
+    fill: gradient.radial(yellow, orange, center: (30%, 30%), radius: 70%),
+  ),
+)
+
+---
+// Test gradient strokes and text.
+#set page(width: 120pt, height: auto)
+#rect(width: 100%, height: 15pt, stroke: 3pt + gradient.linear(eastern, purple))
+#text(20pt, fill: gradient.linear(red, blue))[Gradient]
+
+---
+// Test that shapes share a gradient that is relative to the page.
+#set page(width: 120pt, height: auto)
+#let grad = gradient.linear(red, blue, relative: "page")
+#stack(dir: ltr, spacing: 5pt, ..range(4).map(_ => square(size: 20pt, fill: grad)))
+
+---
+// Test stops that are not opaque.
+#set page(width: 120pt, height: auto)
+#let fading = gradient.linear(rgb("#0074d9"), rgb("#0074d900"))
+#rect(width: 100%, height: 15pt, fill: fading)
+#place(top + left, dx: 20pt, rect(width: 40pt, height: 30pt, fill: red))
+#rect(width: 100%, height: 30pt, fill: fading, stroke: 2pt + black)
+#circle(radius: 15pt, fill: gradient.radial(green, rgb("#2ecc4000")))
+
+---
+// Stops without offsets are spread out evenly.
+// Ref: false
+#test(
+  gradient.linear(red, green, blue),
+  gradient.linear((red, 0%), (green, 50%), (blue, 100%)),
+)
+#test(type(gradient.radial(red, blue)), "gradient")
+#test(
+  repr(gradient.linear(red, blue, angle: 90deg)),
+  "gradient.linear((rgb(\"#ff4136\"), 0%), (rgb(\"#0074d9\"), 100%), angle: 90deg)",
+)
+
+---
+// Error: 17-22 a gradient must have at least two stops
+#gradient.linear(red)
+
+---
+// Error: 18-21 either all or none of the stops must have an offset
+#gradient.linear(red, (blue, 50%))
+
+---
+// Error: 30-41 offsets must be in ascending order
+#gradient.linear((red, 60%), (blue, 40%))
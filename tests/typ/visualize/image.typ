@@ -49,6 +49,16 @@ A #box(image("/tiger.jpg", height: 1cm, width: 80%)) B
 // Test advanced SVG features.
 #image("/pattern.svg")
 
+---
+// Test showing pages of a PDF file.
+#set page(height: 80pt)
+#box(image("/diagrams.pdf", width: 50%))
+#box(image("/diagrams.pdf", page: 2, width: 40%))
+
+---
+// Error: 2-33 pdf file has no page 3
+#image("/diagrams.pdf", page: 3)
+
 ---
 // Error: 8-29 file not found (searched at typ/visualize/path/does/not/exist)
 #image("path/does/not/exist")
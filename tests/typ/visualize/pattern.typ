@@ -0,0 +1,37 @@
+// Test patterns.
+
+---
+#let dots = pattern(size: (10pt, 10pt), circle(radius: 2pt, fill: teal))
+#test(type(dots), "pattern")
+#test(repr(dots), "pattern(size: (10pt, 10pt))")
+#test(
+  repr(pattern(spacing: (2pt, 0pt), relative: "page", square(size: 4pt))),
+  "pattern(size: (4pt, 4pt), spacing: (2pt, 0pt), relative: \"page\")",
+)
+#rect(width: 100%, height: 20pt, fill: dots, stroke: dots)
+
+---
+// Test spacing and tiles that are aligned to the page.
+#set page(width: 120pt, height: auto)
+#let hatch = pattern(
+  size: (6pt, 6pt),
+  line(start: (0%, 100%), end: (100%, 0%), stroke: 0.5pt),
+)
+#rect(width: 100%, height: 15pt, fill: hatch)
+#let tiles = pattern(spacing: (2pt, 2pt), relative: "page", square(size: 4pt, fill: navy))
+#stack(dir: ltr, spacing: 5pt, ..range(4).map(_ => square(size: 20pt, fill: tiles)))
+#text(20pt, fill: tiles)[Pattern]
+
+---
+// Test that a tile can be laid out with the styles at the call site.
+#set page(width: 120pt, height: auto)
+#set text(fill: eastern, 8pt)
+#style(styles => rect(
+  width: 100%,
+  height: 20pt,
+  fill: pattern(size: (10pt, 10pt), styles: styles)[\*],
+))
+
+---
+// Error: 9-13 pattern tile must have a positive size
+#pattern([])